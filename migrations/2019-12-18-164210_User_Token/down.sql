@@ -0,0 +1,3 @@
+-- This file should undo anything in `up.sql`
+DROP FUNCTION bb.get_user_by_token;
+DROP TABLE bb.user_token;
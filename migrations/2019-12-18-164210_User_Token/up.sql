@@ -0,0 +1,25 @@
+-- Your SQL goes here
+CREATE TABLE IF NOT EXISTS bb.user_token (
+  token_hash BYTEA NOT NULL CONSTRAINT PK_user_token PRIMARY KEY,
+  user_id INT NOT NULL CONSTRAINT FK_user_token_user REFERENCES bb."user"(id),
+  expires_date TIMESTAMP NOT NULL,
+  created_date TIMESTAMP NOT NULL DEFAULT NOW(),
+  is_active BOOLEAN NOT NULL DEFAULT TRUE,
+  last_modified TIMESTAMP NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX IX_user_token_user ON bb.user_token (user_id);
+
+-- Tokens are never stored in plain text. The raw token is hashed on lookup,
+-- so a leaked table cannot be replayed against the API.
+CREATE OR REPLACE FUNCTION bb.get_user_by_token(in_token TEXT) RETURNS INTEGER
+AS $$
+  SELECT u.id
+  FROM bb.user_token AS t
+    INNER JOIN bb."user" AS u ON u.id = t.user_id
+  WHERE
+    t.token_hash = sha256(convert_to(in_token, 'UTF8'))
+    AND t.expires_date > NOW()
+    AND t.is_active = true AND u.is_active = true;
+$$
+LANGUAGE SQL;
@@ -0,0 +1,4 @@
+-- This file should undo anything in `up.sql`
+DROP PROCEDURE bb.create_user_token;
+DROP FUNCTION bb.login_user_identity;
+DROP TABLE bb.user_identity;
@@ -0,0 +1,84 @@
+-- Your SQL goes here
+CREATE TABLE IF NOT EXISTS bb.user_identity (
+  provider TEXT NOT NULL,
+  subject TEXT NOT NULL,
+  user_id INT NOT NULL CONSTRAINT FK_user_identity_user REFERENCES bb."user"(id),
+  created_date TIMESTAMP NOT NULL DEFAULT NOW(),
+  is_active BOOLEAN NOT NULL DEFAULT TRUE,
+  last_modified TIMESTAMP NOT NULL DEFAULT NOW(),
+  CONSTRAINT PK_user_identity PRIMARY KEY (provider, subject)
+);
+
+-- Resolves the user for a provider's subject. The first login either links the
+-- identity to an already-authenticated user (in_link_user_id), or creates a new
+-- user. The provider's username is used when it is not already taken, and the
+-- fallback username otherwise.
+-- Returns NULL when the identity belongs to an inactive user.
+CREATE OR REPLACE FUNCTION bb.login_user_identity(
+  in_provider TEXT,
+  in_subject TEXT,
+  in_username TEXT,
+  in_fallback_username TEXT,
+  in_link_user_id INT
+) RETURNS INTEGER
+AS $$
+DECLARE
+  out_user_id INT;
+  user_is_active BOOLEAN;
+BEGIN
+  SELECT u.id, u.is_active
+    FROM bb.user_identity AS i
+      INNER JOIN bb."user" AS u ON u.id = i.user_id
+    WHERE
+      i.provider = in_provider
+      AND i.subject = in_subject
+      AND i.is_active = true
+  INTO out_user_id, user_is_active;
+
+  IF out_user_id IS NOT NULL THEN
+    IF NOT user_is_active THEN
+      RETURN NULL;
+    END IF;
+    RETURN out_user_id;
+  END IF;
+
+  IF in_link_user_id IS NOT NULL THEN
+    out_user_id := in_link_user_id;
+  ELSE
+    INSERT INTO bb."user" (username)
+    VALUES (
+      CASE
+        WHEN in_username IS NULL
+          OR EXISTS (SELECT 1 FROM bb."user" AS u WHERE u.username = in_username)
+        THEN in_fallback_username
+        ELSE in_username
+      END)
+    RETURNING id INTO out_user_id;
+  END IF;
+
+  INSERT INTO bb.user_identity (provider, subject, user_id)
+  VALUES (in_provider, in_subject, out_user_id)
+    ON CONFLICT
+    ON CONSTRAINT PK_user_identity
+    DO UPDATE SET user_id = EXCLUDED.user_id, is_active = true, last_modified = NOW();
+
+  RETURN out_user_id;
+END;
+$$
+LANGUAGE 'plpgsql';
+
+CREATE OR REPLACE PROCEDURE bb.create_user_token(
+  in_token TEXT,
+  in_user_id INT,
+  in_lifetime_days INT
+)
+AS $$
+BEGIN
+  INSERT INTO bb.user_token (token_hash, user_id, expires_date)
+  VALUES (
+    sha256(convert_to(in_token, 'UTF8')),
+    in_user_id,
+    NOW() + make_interval(days => in_lifetime_days));
+END;
+$$
+LANGUAGE 'plpgsql';
@@ -0,0 +1,116 @@
+-- Your SQL goes here
+DROP FUNCTION bb.get_cards(text,boolean,integer,integer,integer[],boolean,real,boolean);
+
+CREATE OR REPLACE FUNCTION bb.get_cards(
+	search text,
+	filter_black boolean,
+	previous_cursor integer,
+	n_cards integer,
+	card_sets integer[],
+	get_random boolean,
+	random_seed real,
+  user_submitted boolean)
+    RETURNS TABLE(id integer, format_text text, is_black boolean, parent_set_id integer, parent_set_name text, total_votes integer, average_rating real, submitted_by_user_id integer, submitted_by_username text)
+    LANGUAGE 'plpgsql'
+
+    COST 100
+    VOLATILE
+    ROWS 1000
+
+AS $BODY$
+BEGIN
+
+  IF get_random THEN
+
+    IF random_seed IS NOT NULL THEN
+      PERFORM setseed(random_seed);
+    END IF;
+
+    RETURN QUERY SELECT
+        c.id,
+        c.format_text,
+        c.is_black,
+        p.parent_set_id AS "parent_set_id",
+        ps.name AS "parent_set_name",
+        c.total_votes,
+        c.average_rating,
+        u.id AS "submitted_by_user_id",
+        u.username AS "submitted_by_username"
+      FROM bb.card AS c
+        INNER JOIN bb.parent_set_card AS p ON p.card_id = c.id
+        INNER JOIN bb.parent_set AS ps ON ps.id = p.parent_set_id
+        LEFT JOIN bb."user" AS u ON u.id = c.submitted_by_user_id
+      WHERE
+        (search IS NULL OR to_tsquery('english', search) @@ text_searchable_format_text)
+        AND (filter_black IS NULL OR c.is_black = filter_black)
+        AND (card_sets IS NULL OR p.parent_set_id = ANY(card_sets))
+        AND c.is_active = true AND p.is_active = true AND ps.is_active = true
+        AND (
+          -- NULL == CardSource.All
+          user_submitted IS NULL OR
+          -- TRUE == CardSource.User
+          (user_submitted AND c.submitted_by_user_id IS NOT NULL) OR
+          -- FALSE == CardSource.Official
+          (NOT user_submitted AND c.submitted_by_user_id IS NULL)
+        )
+      ORDER BY RANDOM()
+      LIMIT n_cards OFFSET previous_cursor;
+  ELSE
+
+    RETURN QUERY SELECT
+      c.id,
+      c.format_text AS "format_text",
+      c.is_black AS "is_black",
+      p.parent_set_id AS "parent_set_id",
+      ps.name AS "parent_set_name",
+      c.total_votes,
+      c.average_rating,
+      u.id AS "submitted_by_user_id",
+      u.username AS "submitted_by_username"
+    FROM bb.card AS c
+      INNER JOIN bb.parent_set_card AS p ON p.card_id = c.id
+      INNER JOIN bb.parent_set AS ps ON ps.id = p.parent_set_id
+      LEFT JOIN bb."user" AS u ON u.id = c.submitted_by_user_id
+    WHERE
+      (search IS NULL OR to_tsquery('english', search) @@ text_searchable_format_text)
+      AND (filter_black IS NULL OR c.is_black = filter_black)
+      AND (card_sets IS NULL OR p.parent_set_id = ANY(card_sets))
+      AND c.is_active = true AND p.is_active = true AND ps.is_active = true
+      AND (
+        -- NULL == CardSource.All
+        user_submitted IS NULL OR
+        -- TRUE == CardSource.User
+        (user_submitted AND c.submitted_by_user_id IS NOT NULL) OR
+        -- FALSE == CardSource.Official
+        (NOT user_submitted AND c.submitted_by_user_id IS NULL)
+      )
+      AND (previous_cursor IS NULL OR c.id > previous_cursor)
+    ORDER BY c.id
+    LIMIT n_cards;
+
+  END IF;
+END;
+$BODY$;
+
+-- Returns NULL when the username has already been taken
+CREATE OR REPLACE FUNCTION bb.register_user(in_username TEXT) RETURNS INTEGER
+AS $$
+  INSERT INTO bb."user" (username)
+  VALUES (in_username)
+    ON CONFLICT ON CONSTRAINT UX_User_Name DO NOTHING
+  RETURNING id;
+$$
+LANGUAGE SQL;
+
+-- Raises a unique violation (UX_User_Name) when the username has already been
+-- taken by another user
+CREATE OR REPLACE FUNCTION bb.update_username(in_user_id INT, in_username TEXT) RETURNS BOOLEAN
+AS $$
+  UPDATE ONLY bb."user"
+    SET
+      username = in_username,
+      last_modified = NOW()
+    WHERE id = in_user_id
+  RETURNING true;
+$$
+LANGUAGE SQL;
@@ -0,0 +1,7 @@
+-- This file should undo anything in `up.sql`
+-- The combination ratings swapped back by up.sql are kept in the right order
+DROP INDEX bb.IX_card_submitted_by_user;
+DROP FUNCTION bb.get_user_card_combination_ratings;
+DROP FUNCTION bb.get_user_card_ratings;
+DROP FUNCTION bb.get_user_submitted_cards;
+DROP FUNCTION bb.get_cards_by_ids;
@@ -0,0 +1,150 @@
+-- Your SQL goes here
+CREATE OR REPLACE FUNCTION bb.get_cards_by_ids(card_ids INT[])
+RETURNS TABLE (
+  id INT,
+  format_text TEXT,
+  is_black BOOLEAN,
+  parent_set_id INT,
+  parent_set_name TEXT,
+  total_votes INT,
+  average_rating REAL,
+  submitted_by_user_id INT,
+  submitted_by_username TEXT
+)
+AS $$
+  SELECT
+    c.id,
+    c.format_text,
+    c.is_black,
+    ps.id AS "parent_set_id",
+    ps.name AS "parent_set_name",
+    c.total_votes,
+    c.average_rating,
+    u.id AS "submitted_by_user_id",
+    u.username AS "submitted_by_username"
+  FROM bb.card AS c
+    LEFT JOIN LATERAL (
+      SELECT s.id, s.name
+      FROM bb.parent_set_card AS p
+        INNER JOIN bb.parent_set AS s ON s.id = p.parent_set_id
+      WHERE p.card_id = c.id AND p.is_active = true AND s.is_active = true
+      ORDER BY s.id
+      LIMIT 1
+    ) AS ps ON true
+    LEFT JOIN bb."user" AS u ON u.id = c.submitted_by_user_id
+  WHERE c.id = ANY(card_ids) AND c.is_active = true
+  ORDER BY c.id;
+$$
+LANGUAGE SQL;
+
+CREATE OR REPLACE FUNCTION bb.get_user_submitted_cards(
+  in_user_id INT,
+  previous_cursor INT,
+  n_cards INT
+) RETURNS TABLE (
+  id INT,
+  format_text TEXT,
+  is_black BOOLEAN,
+  parent_set_id INT,
+  parent_set_name TEXT,
+  total_votes INT,
+  average_rating REAL,
+  submitted_by_user_id INT,
+  submitted_by_username TEXT
+)
+AS $$
+  SELECT * FROM bb.get_cards_by_ids(ARRAY(
+    SELECT c.id
+    FROM bb.card AS c
+    WHERE
+      c.submitted_by_user_id = in_user_id
+      AND c.is_active = true
+      AND (previous_cursor IS NULL OR c.id > previous_cursor)
+    ORDER BY c.id
+    LIMIT n_cards
+  ));
+$$
+LANGUAGE SQL;
+
+CREATE OR REPLACE FUNCTION bb.get_user_card_ratings(
+  in_user_id INT,
+  previous_cursor INT,
+  n_ratings INT
+) RETURNS TABLE (
+  card_id INT,
+  rating REAL
+)
+AS $$
+  SELECT
+    r.card_id,
+    r.rating
+  FROM bb.user_card_rating AS r
+    INNER JOIN bb.card AS c ON c.id = r.card_id
+  WHERE
+    r.user_id = in_user_id
+    AND c.is_active = true
+    AND (previous_cursor IS NULL OR r.card_id > previous_cursor)
+  ORDER BY r.card_id
+  LIMIT n_ratings;
+$$
+LANGUAGE SQL;
+
+-- Combination ratings are keyed by (black card, white card, ordinal),
+-- so the cursor is made up of all three
+CREATE OR REPLACE FUNCTION bb.get_user_card_combination_ratings(
+  in_user_id INT,
+  previous_black_card_id INT,
+  previous_white_card_id INT,
+  previous_ordinal INT,
+  n_ratings INT
+) RETURNS TABLE (
+  black_card_id INT,
+  white_card_id INT,
+  ordinal INT,
+  rating REAL
+)
+AS $$
+  SELECT
+    r.black_card_id,
+    r.white_card_id,
+    r.ordinal,
+    r.rating
+  FROM bb.user_card_combination_rating AS r
+    INNER JOIN bb.card AS b ON b.id = r.black_card_id
+    INNER JOIN bb.card AS w ON w.id = r.white_card_id
+  WHERE
+    r.user_id = in_user_id
+    AND b.is_active = true
+    AND w.is_active = true
+    AND (
+      previous_black_card_id IS NULL
+      OR (r.black_card_id, r.white_card_id, r.ordinal) > (previous_black_card_id, previous_white_card_id, previous_ordinal)
+    )
+  ORDER BY r.black_card_id, r.white_card_id, r.ordinal
+  LIMIT n_ratings;
+$$
+LANGUAGE SQL;
+
+CREATE INDEX IX_card_submitted_by_user ON bb.card (submitted_by_user_id);
+
+-- Combination ratings used to be stored with the white and black card IDs
+-- swapped. Swap them back, dropping the swapped rows of combinations the user
+-- has also rated with the IDs in the right order.
+DELETE FROM bb.user_card_combination_rating AS r
+  USING bb.card AS w, bb.card AS b
+  WHERE w.id = r.white_card_id AND w.is_black = true
+    AND b.id = r.black_card_id AND b.is_black = false
+    AND EXISTS (
+      SELECT 1
+      FROM bb.user_card_combination_rating AS o
+      WHERE o.user_id = r.user_id
+        AND o.white_card_id = r.black_card_id
+        AND o.black_card_id = r.white_card_id
+        AND o.ordinal = r.ordinal
+    );
+
+UPDATE bb.user_card_combination_rating AS r
+  SET white_card_id = r.black_card_id, black_card_id = r.white_card_id
+  FROM bb.card AS w, bb.card AS b
+  WHERE w.id = r.white_card_id AND w.is_black = true
+    AND b.id = r.black_card_id AND b.is_black = false;
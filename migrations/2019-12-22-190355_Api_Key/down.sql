@@ -0,0 +1,6 @@
+-- This file should undo anything in `up.sql`
+DROP FUNCTION bb.revoke_api_key;
+DROP FUNCTION bb.create_api_key;
+DROP FUNCTION bb.get_api_key;
+DROP TABLE bb.api_key;
+ALTER TABLE bb."user" DROP COLUMN is_admin;
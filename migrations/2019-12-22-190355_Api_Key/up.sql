@@ -0,0 +1,71 @@
+-- Your SQL goes here
+ALTER TABLE bb."user" ADD is_admin BOOLEAN NOT NULL DEFAULT false;
+
+CREATE TABLE IF NOT EXISTS bb.api_key (
+  id SERIAL NOT NULL CONSTRAINT PK_api_key PRIMARY KEY,
+  key_hash BYTEA NOT NULL CONSTRAINT UX_api_key_hash UNIQUE,
+  user_id INT NOT NULL CONSTRAINT FK_api_key_user REFERENCES bb."user"(id),
+  name TEXT NOT NULL,
+  scopes TEXT[] NOT NULL
+    CONSTRAINT CK_api_key_scopes CHECK (scopes <@ ARRAY['read', 'rate', 'submit', 'admin']),
+  created_date TIMESTAMP NOT NULL DEFAULT NOW(),
+  is_active BOOLEAN NOT NULL DEFAULT TRUE,
+  last_modified TIMESTAMP NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX IX_api_key_user ON bb.api_key (user_id);
+
+-- Like user tokens, API keys are only stored hashed. The `admin` scope is
+-- only honored while the owner of the key is still an admin.
+CREATE OR REPLACE FUNCTION bb.get_api_key(in_key TEXT)
+RETURNS TABLE (
+  id INT,
+  user_id INT,
+  scopes TEXT[],
+  is_admin BOOLEAN
+)
+AS $$
+  SELECT
+    k.id,
+    k.user_id,
+    k.scopes,
+    u.is_admin
+  FROM bb.api_key AS k
+    INNER JOIN bb."user" AS u ON u.id = k.user_id
+  WHERE
+    k.key_hash = sha256(convert_to(in_key, 'UTF8'))
+    AND k.is_active = true AND u.is_active = true;
+$$
+LANGUAGE SQL;
+
+CREATE OR REPLACE FUNCTION bb.create_api_key(
+  in_key TEXT,
+  in_user_id INT,
+  in_name TEXT,
+  in_scopes TEXT[]
+) RETURNS INTEGER
+AS $$
+  INSERT INTO bb.api_key (key_hash, user_id, name, scopes)
+  VALUES (sha256(convert_to(in_key, 'UTF8')), in_user_id, in_name, in_scopes)
+  RETURNING id;
+$$
+LANGUAGE SQL;
+
+-- A NULL in_user_id revokes the key regardless of its owner (admin use).
+-- Returns false when there was no active key to revoke.
+CREATE OR REPLACE FUNCTION bb.revoke_api_key(in_id INT, in_user_id INT) RETURNS BOOLEAN
+AS $$
+  WITH revoked AS (
+    UPDATE ONLY bb.api_key
+      SET
+        is_active = false,
+        last_modified = NOW()
+      WHERE
+        id = in_id
+        AND is_active = true
+        AND (in_user_id IS NULL OR user_id = in_user_id)
+      RETURNING id
+  )
+  SELECT EXISTS (SELECT 1 FROM revoked);
+$$
+LANGUAGE SQL;
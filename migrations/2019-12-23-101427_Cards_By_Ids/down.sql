@@ -0,0 +1,163 @@
+-- This file should undo anything in `up.sql`
+DROP INDEX bb.IX_parent_set_card_card;
+DROP FUNCTION bb.get_user_submitted_cards;
+DROP FUNCTION bb.get_cards_by_ids;
+DROP FUNCTION bb.get_cards(text,boolean,integer,integer,integer[],boolean,real,boolean);
+
+CREATE OR REPLACE FUNCTION bb.get_cards(
+	search text,
+	filter_black boolean,
+	previous_cursor integer,
+	n_cards integer,
+	card_sets integer[],
+	get_random boolean,
+	random_seed real,
+  user_submitted boolean)
+    RETURNS TABLE(id integer, format_text text, is_black boolean, parent_set_id integer, parent_set_name text, total_votes integer, average_rating real, submitted_by_user_id integer, submitted_by_username text)
+    LANGUAGE 'plpgsql'
+
+    COST 100
+    VOLATILE
+    ROWS 1000
+
+AS $BODY$
+BEGIN
+
+  IF get_random THEN
+
+    IF random_seed IS NOT NULL THEN
+      PERFORM setseed(random_seed);
+    END IF;
+
+    RETURN QUERY SELECT
+        c.id,
+        c.format_text,
+        c.is_black,
+        p.parent_set_id AS "parent_set_id",
+        ps.name AS "parent_set_name",
+        c.total_votes,
+        c.average_rating,
+        u.id AS "submitted_by_user_id",
+        u.username AS "submitted_by_username"
+      FROM bb.card AS c
+        INNER JOIN bb.parent_set_card AS p ON p.card_id = c.id
+        INNER JOIN bb.parent_set AS ps ON ps.id = p.parent_set_id
+        LEFT JOIN bb."user" AS u ON u.id = c.submitted_by_user_id
+      WHERE
+        (search IS NULL OR to_tsquery('english', search) @@ text_searchable_format_text)
+        AND (filter_black IS NULL OR c.is_black = filter_black)
+        AND (card_sets IS NULL OR p.parent_set_id = ANY(card_sets))
+        AND c.is_active = true AND p.is_active = true AND ps.is_active = true
+        AND (
+          -- NULL == CardSource.All
+          user_submitted IS NULL OR
+          -- TRUE == CardSource.User
+          (user_submitted AND c.submitted_by_user_id IS NOT NULL) OR
+          -- FALSE == CardSource.Official
+          (NOT user_submitted AND c.submitted_by_user_id IS NULL)
+        )
+      ORDER BY RANDOM()
+      LIMIT n_cards OFFSET previous_cursor;
+  ELSE
+
+    RETURN QUERY SELECT
+      c.id,
+      c.format_text AS "format_text",
+      c.is_black AS "is_black",
+      p.parent_set_id AS "parent_set_id",
+      ps.name AS "parent_set_name",
+      c.total_votes,
+      c.average_rating,
+      u.id AS "submitted_by_user_id",
+      u.username AS "submitted_by_username"
+    FROM bb.card AS c
+      INNER JOIN bb.parent_set_card AS p ON p.card_id = c.id
+      INNER JOIN bb.parent_set AS ps ON ps.id = p.parent_set_id
+      LEFT JOIN bb."user" AS u ON u.id = c.submitted_by_user_id
+    WHERE
+      (search IS NULL OR to_tsquery('english', search) @@ text_searchable_format_text)
+      AND (filter_black IS NULL OR c.is_black = filter_black)
+      AND (card_sets IS NULL OR p.parent_set_id = ANY(card_sets))
+      AND c.is_active = true AND p.is_active = true AND ps.is_active = true
+      AND (
+        -- NULL == CardSource.All
+        user_submitted IS NULL OR
+        -- TRUE == CardSource.User
+        (user_submitted AND c.submitted_by_user_id IS NOT NULL) OR
+        -- FALSE == CardSource.Official
+        (NOT user_submitted AND c.submitted_by_user_id IS NULL)
+      )
+      AND (previous_cursor IS NULL OR c.id > previous_cursor)
+    ORDER BY c.id
+    LIMIT n_cards;
+
+  END IF;
+END;
+$BODY$;
+
+CREATE OR REPLACE FUNCTION bb.get_cards_by_ids(card_ids INT[])
+RETURNS TABLE (
+  id INT,
+  format_text TEXT,
+  is_black BOOLEAN,
+  parent_set_id INT,
+  parent_set_name TEXT,
+  total_votes INT,
+  average_rating REAL,
+  submitted_by_user_id INT,
+  submitted_by_username TEXT
+)
+AS $$
+  SELECT
+    c.id,
+    c.format_text,
+    c.is_black,
+    ps.id AS "parent_set_id",
+    ps.name AS "parent_set_name",
+    c.total_votes,
+    c.average_rating,
+    u.id AS "submitted_by_user_id",
+    u.username AS "submitted_by_username"
+  FROM bb.card AS c
+    LEFT JOIN LATERAL (
+      SELECT s.id, s.name
+      FROM bb.parent_set_card AS p
+        INNER JOIN bb.parent_set AS s ON s.id = p.parent_set_id
+      WHERE p.card_id = c.id AND p.is_active = true AND s.is_active = true
+      ORDER BY s.id
+      LIMIT 1
+    ) AS ps ON true
+    LEFT JOIN bb."user" AS u ON u.id = c.submitted_by_user_id
+  WHERE c.id = ANY(card_ids) AND c.is_active = true
+  ORDER BY c.id;
+$$
+LANGUAGE SQL;
+
+CREATE OR REPLACE FUNCTION bb.get_user_submitted_cards(
+  in_user_id INT,
+  previous_cursor INT,
+  n_cards INT
+) RETURNS TABLE (
+  id INT,
+  format_text TEXT,
+  is_black BOOLEAN,
+  parent_set_id INT,
+  parent_set_name TEXT,
+  total_votes INT,
+  average_rating REAL,
+  submitted_by_user_id INT,
+  submitted_by_username TEXT
+)
+AS $$
+  SELECT * FROM bb.get_cards_by_ids(ARRAY(
+    SELECT c.id
+    FROM bb.card AS c
+    WHERE
+      c.submitted_by_user_id = in_user_id
+      AND c.is_active = true
+      AND (previous_cursor IS NULL OR c.id > previous_cursor)
+    ORDER BY c.id
+    LIMIT n_cards
+  ));
+$$
+LANGUAGE SQL;
@@ -0,0 +1,87 @@
+-- This file should undo anything in `up.sql`
+CREATE OR REPLACE FUNCTION bb.get_cards(
+	search text,
+	filter_black boolean,
+	previous_cursor integer,
+	n_cards integer,
+	card_sets integer[],
+	get_random boolean,
+	random_seed real,
+  user_submitted boolean)
+    RETURNS TABLE(id integer, format_text text, is_black boolean, parent_set_id integer, parent_set_name text, total_votes integer, average_rating real, submitted_by_user_id integer, submitted_by_username text, parent_set_ids integer[], parent_set_names text[])
+    LANGUAGE 'plpgsql'
+
+    COST 100
+    VOLATILE
+    ROWS 1000
+
+AS $BODY$
+BEGIN
+
+  IF get_random THEN
+
+    IF random_seed IS NOT NULL THEN
+      PERFORM setseed(random_seed);
+    END IF;
+
+  END IF;
+
+  -- The page is selected first, so that every set of a card is only
+  -- aggregated for the cards that are actually returned
+  RETURN QUERY SELECT
+      page.id,
+      page.format_text,
+      page.is_black,
+      page.parent_set_id,
+      page.parent_set_name,
+      page.total_votes,
+      page.average_rating,
+      u.id AS "submitted_by_user_id",
+      u.username AS "submitted_by_username",
+      COALESCE(sets.ids, '{}') AS "parent_set_ids",
+      COALESCE(sets.names, '{}') AS "parent_set_names"
+    FROM (
+      SELECT
+        c.id,
+        c.format_text,
+        c.is_black,
+        p.parent_set_id,
+        ps.name AS "parent_set_name",
+        c.total_votes,
+        c.average_rating,
+        c.submitted_by_user_id,
+        CASE WHEN get_random THEN RANDOM() ELSE c.id END AS "sort_key"
+      FROM bb.card AS c
+        INNER JOIN bb.parent_set_card AS p ON p.card_id = c.id
+        INNER JOIN bb.parent_set AS ps ON ps.id = p.parent_set_id
+      WHERE
+        (search IS NULL OR to_tsquery('english', search) @@ text_searchable_format_text)
+        AND (filter_black IS NULL OR c.is_black = filter_black)
+        AND (card_sets IS NULL OR p.parent_set_id = ANY(card_sets))
+        AND c.is_active = true AND p.is_active = true AND ps.is_active = true
+        AND (
+          -- NULL == CardSource.All
+          user_submitted IS NULL OR
+          -- TRUE == CardSource.User
+          (user_submitted AND c.submitted_by_user_id IS NOT NULL) OR
+          -- FALSE == CardSource.Official
+          (NOT user_submitted AND c.submitted_by_user_id IS NULL)
+        )
+        -- Randomized results are paginated by offset, others by the last card ID
+        AND (get_random OR previous_cursor IS NULL OR c.id > previous_cursor)
+      ORDER BY sort_key
+      LIMIT n_cards OFFSET CASE WHEN get_random THEN previous_cursor END
+    ) AS page
+      LEFT JOIN bb."user" AS u ON u.id = page.submitted_by_user_id
+      LEFT JOIN LATERAL (
+        SELECT
+          array_agg(s.id ORDER BY s.id) AS ids,
+          array_agg(s.name ORDER BY s.id) AS names
+        FROM bb.parent_set_card AS sc
+          INNER JOIN bb.parent_set AS s ON s.id = sc.parent_set_id
+        WHERE sc.card_id = page.id AND sc.is_active = true AND s.is_active = true
+      ) AS sets ON true
+    ORDER BY page.sort_key;
+
+END;
+$BODY$;
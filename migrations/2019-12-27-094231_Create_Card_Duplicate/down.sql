@@ -0,0 +1,15 @@
+-- This file should undo anything in `up.sql`
+DROP FUNCTION bb.create_card;
+
+CREATE OR REPLACE FUNCTION bb.create_card(
+  format_text TEXT,
+  is_black BOOLEAN,
+  submitted_by_user_id INT
+) RETURNS INTEGER
+AS $$
+  INSERT INTO bb.card
+    (format_text, is_black, submitted_by_user_id)
+  VALUES (format_text, is_black, submitted_by_user_id)
+  RETURNING id;
+$$
+LANGUAGE SQL;
@@ -0,0 +1,31 @@
+-- Your SQL goes here
+DROP FUNCTION bb.create_card;
+
+-- Returns the existing card (with is_duplicate) when the card has already been submitted
+CREATE FUNCTION bb.create_card(
+  in_format_text TEXT,
+  in_is_black BOOLEAN,
+  in_submitted_by_user_id INT
+) RETURNS TABLE (
+  id INT,
+  is_duplicate BOOLEAN
+)
+AS $$
+  WITH inserted AS (
+    INSERT INTO bb.card
+      (format_text, is_black, submitted_by_user_id)
+    VALUES (in_format_text, in_is_black, in_submitted_by_user_id)
+      ON CONFLICT ON CONSTRAINT UX_card_color_text DO NOTHING
+    RETURNING card.id
+  )
+  SELECT i.id, false
+  FROM inserted AS i
+  UNION ALL
+  SELECT c.id, true
+  FROM bb.card AS c
+  WHERE
+    NOT EXISTS (SELECT 1 FROM inserted)
+    AND c.is_black = in_is_black
+    AND c.format_text = in_format_text;
+$$
+LANGUAGE SQL;
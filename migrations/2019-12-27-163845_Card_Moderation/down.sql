@@ -0,0 +1,322 @@
+-- This file should undo anything in `up.sql`
+CREATE OR REPLACE PROCEDURE bb.generate_daily_set(top_n_cards INT)
+AS $$
+BEGIN
+  with
+    inserted_set AS (
+      INSERT INTO bb.parent_set (name)
+        VALUES ('Daily Set: ' || TO_CHAR(NOW()::DATE, 'yyyy-mm-dd'))
+      RETURNING id)
+    , card_ratings AS (
+      SELECT
+        r.card_id
+        , SUM(CASE WHEN rating < 0.5 THEN 1 ELSE 0 END) AS "downvotes"
+        , SUM(CASE WHEN rating > 0.5 THEN 1 ELSE 0 END) AS "upvotes"
+        , AVG(CASE WHEN rating < 0.5 THEN rating ELSE NULL END) AS "low_votes"
+        , AVG(CASE WHEN rating > 0.5 THEN rating ELSE NULL END) AS "high_votes"
+      FROM bb.user_card_rating as r
+      GROUP BY r.card_id
+    )
+    , top_cards AS (
+      SELECT c.id
+      FROM bb.card AS c
+        INNER JOIN card_ratings AS r ON r.card_id = c.id
+      WHERE DATE_PART('DAY', created_date - NOW()::timestamp) <= 1
+        AND c.submitted_by_user_id IS NOT NULL
+      ORDER BY ( r.upvotes * (r.high_votes - 0.5) ) + ( r.downvotes * (r.low_votes - 0.5) )
+      LIMIT top_n_cards
+    )
+  INSERT INTO bb.parent_set_card (parent_set_id, card_id)
+    SELECT
+      s.id,
+      c.id
+    FROM inserted_set AS s
+      CROSS JOIN top_cards as c;
+END;
+$$
+LANGUAGE 'plpgsql';
+
+CREATE OR REPLACE PROCEDURE bb.generate_weekly_set(top_n_cards INT)
+AS $$
+BEGIN
+  with
+    inserted_set AS (
+      INSERT INTO bb.parent_set (name)
+        VALUES ('Weekly Set: ' || TO_CHAR(NOW()::DATE, 'yyyy-mm-dd'))
+      RETURNING id)
+    , card_ratings AS (
+      SELECT
+        r.card_id
+        , SUM(CASE WHEN rating < 0.5 THEN 1 ELSE 0 END) AS "downvotes"
+        , SUM(CASE WHEN rating > 0.5 THEN 1 ELSE 0 END) AS "upvotes"
+        , AVG(CASE WHEN rating < 0.5 THEN rating ELSE NULL END) AS "low_votes"
+        , AVG(CASE WHEN rating > 0.5 THEN rating ELSE NULL END) AS "high_votes"
+      FROM bb.user_card_rating as r
+      GROUP BY r.card_id
+    )
+    , top_cards AS (
+      SELECT c.id
+      FROM bb.card AS c
+        INNER JOIN card_ratings AS r ON r.card_id = c.id
+      WHERE DATE_PART('day', created_date - NOW()::timestamp) <= 7
+        AND c.submitted_by_user_id IS NOT NULL
+      ORDER BY ( r.upvotes * (r.high_votes - 0.5) ) + ( r.downvotes * (r.low_votes - 0.5) )
+      LIMIT top_n_cards
+    )
+  INSERT INTO bb.parent_set_card (parent_set_id, card_id)
+    SELECT
+      s.id,
+      c.id
+    FROM inserted_set AS s
+      CROSS JOIN top_cards as c;
+END;
+$$
+LANGUAGE 'plpgsql';
+
+CREATE OR REPLACE PROCEDURE bb.generate_monthly_set(top_n_cards INT)
+AS $$
+BEGIN
+  with
+    inserted_set AS (
+      INSERT INTO bb.parent_set (name)
+        VALUES ('Montly Set: ' || TO_CHAR(NOW()::DATE, 'yyyy-mm'))
+      RETURNING id)
+    , card_ratings AS (
+      SELECT
+        r.card_id
+        , SUM(CASE WHEN rating < 0.5 THEN 1 ELSE 0 END) AS "downvotes"
+        , SUM(CASE WHEN rating > 0.5 THEN 1 ELSE 0 END) AS "upvotes"
+        , AVG(CASE WHEN rating < 0.5 THEN rating ELSE NULL END) AS "low_votes"
+        , AVG(CASE WHEN rating > 0.5 THEN rating ELSE NULL END) AS "high_votes"
+      FROM bb.user_card_rating as r
+      GROUP BY r.card_id
+    )
+    , top_cards AS (
+      SELECT c.id
+      FROM bb.card AS c
+        INNER JOIN card_ratings AS r ON r.card_id = c.id
+      WHERE DATE_PART('month', created_date - NOW()::timestamp) <= 1
+        AND c.submitted_by_user_id IS NOT NULL
+      ORDER BY ( r.upvotes * (r.high_votes - 0.5) ) + ( r.downvotes * (r.low_votes - 0.5) )
+      LIMIT top_n_cards
+    )
+  INSERT INTO bb.parent_set_card (parent_set_id, card_id)
+    SELECT
+      s.id,
+      c.id
+    FROM inserted_set AS s
+      CROSS JOIN top_cards as c;
+END;
+$$
+LANGUAGE 'plpgsql';
+
+CREATE OR REPLACE PROCEDURE bb.generate_yearly_set(top_n_cards INT)
+AS $$
+BEGIN
+  with
+    inserted_set AS (
+      INSERT INTO bb.parent_set (name)
+        VALUES ('Yearly Set: ' || TO_CHAR(NOW()::DATE, 'yyyy'))
+      RETURNING id)
+    , card_ratings AS (
+      SELECT
+        r.card_id
+        , SUM(CASE WHEN rating < 0.5 THEN 1 ELSE 0 END) AS "downvotes"
+        , SUM(CASE WHEN rating > 0.5 THEN 1 ELSE 0 END) AS "upvotes"
+        , AVG(CASE WHEN rating < 0.5 THEN rating ELSE NULL END) AS "low_votes"
+        , AVG(CASE WHEN rating > 0.5 THEN rating ELSE NULL END) AS "high_votes"
+      FROM bb.user_card_rating as r
+      GROUP BY r.card_id
+    )
+    , top_cards AS (
+      SELECT c.id
+      FROM bb.card AS c
+        INNER JOIN card_ratings AS r ON r.card_id = c.id
+      WHERE DATE_PART('year', created_date - NOW()::timestamp) <= 1
+        AND c.submitted_by_user_id IS NOT NULL
+      ORDER BY ( r.upvotes * (r.high_votes - 0.5) ) + ( r.downvotes * (r.low_votes - 0.5) )
+      LIMIT top_n_cards
+    )
+  INSERT INTO bb.parent_set_card (parent_set_id, card_id)
+    SELECT
+      s.id,
+      c.id
+    FROM inserted_set AS s
+      CROSS JOIN top_cards as c;
+END;
+$$
+LANGUAGE 'plpgsql';
+
+DROP FUNCTION bb.get_card_moderations;
+DROP FUNCTION bb.moderate_card;
+DROP FUNCTION bb.get_visible_cards_by_ids;
+DROP FUNCTION bb.get_moderation_queue;
+DROP FUNCTION bb.get_user_submitted_cards;
+
+CREATE OR REPLACE FUNCTION bb.get_user_submitted_cards(
+  in_user_id INT,
+  previous_cursor INT,
+  n_cards INT
+) RETURNS TABLE (
+  id INT,
+  format_text TEXT,
+  is_black BOOLEAN,
+  parent_set_id INT,
+  parent_set_name TEXT,
+  total_votes INT,
+  average_rating REAL,
+  submitted_by_user_id INT,
+  submitted_by_username TEXT,
+  parent_set_ids INT[],
+  parent_set_names TEXT[],
+  pick INT,
+  draw INT
+)
+AS $$
+  SELECT * FROM bb.get_cards_by_ids(ARRAY(
+    SELECT c.id
+    FROM bb.card AS c
+    WHERE
+      c.submitted_by_user_id = in_user_id
+      AND c.is_active = true
+      AND (previous_cursor IS NULL OR c.id > previous_cursor)
+    ORDER BY c.id
+    LIMIT n_cards
+  ));
+$$
+LANGUAGE SQL;
+
+CREATE OR REPLACE FUNCTION bb.get_cards(
+	search text,
+	filter_black boolean,
+	previous_cursor integer,
+	n_cards integer,
+	card_sets integer[],
+	get_random boolean,
+	random_seed real,
+  user_submitted boolean,
+  filter_pick integer)
+    RETURNS TABLE(id integer, format_text text, is_black boolean, parent_set_id integer, parent_set_name text, total_votes integer, average_rating real, submitted_by_user_id integer, submitted_by_username text, parent_set_ids integer[], parent_set_names text[], pick integer, draw integer)
+    LANGUAGE 'plpgsql'
+
+    COST 100
+    VOLATILE
+    ROWS 1000
+
+AS $BODY$
+BEGIN
+
+  IF get_random THEN
+
+    IF random_seed IS NOT NULL THEN
+      PERFORM setseed(random_seed);
+    END IF;
+
+  END IF;
+
+  -- The page is selected first, so that every set of a card is only
+  -- aggregated for the cards that are actually returned
+  RETURN QUERY SELECT
+      page.id,
+      page.format_text,
+      page.is_black,
+      page.parent_set_id,
+      page.parent_set_name,
+      page.total_votes,
+      page.average_rating,
+      u.id AS "submitted_by_user_id",
+      u.username AS "submitted_by_username",
+      COALESCE(sets.ids, '{}') AS "parent_set_ids",
+      COALESCE(sets.names, '{}') AS "parent_set_names",
+      page.pick,
+      page.draw
+    FROM (
+      SELECT
+        m.*,
+        CASE WHEN get_random THEN RANDOM() ELSE m.id END AS "sort_key"
+      FROM (
+        -- A card is returned once, even when it belongs to several of the
+        -- matched sets. `parent_set_id` is the first of those sets.
+        SELECT DISTINCT ON (c.id)
+          c.id,
+          c.format_text,
+          c.is_black,
+          p.parent_set_id,
+          ps.name AS "parent_set_name",
+          c.total_votes,
+          c.average_rating,
+          c.submitted_by_user_id,
+          c.pick,
+          c.draw
+        FROM bb.card AS c
+          INNER JOIN bb.parent_set_card AS p ON p.card_id = c.id
+          INNER JOIN bb.parent_set AS ps ON ps.id = p.parent_set_id
+        WHERE
+          (search IS NULL OR to_tsquery('english', search) @@ text_searchable_format_text)
+          AND (filter_black IS NULL OR c.is_black = filter_black)
+          AND (filter_pick IS NULL OR c.pick = filter_pick)
+          AND (card_sets IS NULL OR p.parent_set_id = ANY(card_sets))
+          AND c.is_active = true AND p.is_active = true AND ps.is_active = true
+          AND (
+            -- NULL == CardSource.All
+            user_submitted IS NULL OR
+            -- TRUE == CardSource.User
+            (user_submitted AND c.submitted_by_user_id IS NOT NULL) OR
+            -- FALSE == CardSource.Official
+            (NOT user_submitted AND c.submitted_by_user_id IS NULL)
+          )
+          -- Randomized results are paginated by offset, others by the last card ID
+          AND (get_random OR previous_cursor IS NULL OR c.id > previous_cursor)
+        ORDER BY c.id, p.parent_set_id
+      ) AS m
+      ORDER BY sort_key
+      LIMIT n_cards OFFSET CASE WHEN get_random THEN previous_cursor END
+    ) AS page
+      LEFT JOIN bb."user" AS u ON u.id = page.submitted_by_user_id
+      LEFT JOIN LATERAL (
+        SELECT
+          array_agg(s.id ORDER BY s.id) AS ids,
+          array_agg(s.name ORDER BY s.id) AS names
+        FROM bb.parent_set_card AS sc
+          INNER JOIN bb.parent_set AS s ON s.id = sc.parent_set_id
+        WHERE sc.card_id = page.id AND sc.is_active = true AND s.is_active = true
+      ) AS sets ON true
+    ORDER BY page.sort_key;
+
+END;
+$BODY$;
+
+CREATE OR REPLACE FUNCTION bb.create_card(
+  in_format_text TEXT,
+  in_is_black BOOLEAN,
+  in_submitted_by_user_id INT
+) RETURNS TABLE (
+  id INT,
+  is_duplicate BOOLEAN
+)
+AS $$
+  WITH inserted AS (
+    INSERT INTO bb.card
+      (format_text, is_black, submitted_by_user_id)
+    VALUES (in_format_text, in_is_black, in_submitted_by_user_id)
+      ON CONFLICT ON CONSTRAINT UX_card_color_text DO NOTHING
+    RETURNING card.id
+  )
+  SELECT i.id, false
+  FROM inserted AS i
+  UNION ALL
+  SELECT c.id, true
+  FROM bb.card AS c
+  WHERE
+    NOT EXISTS (SELECT 1 FROM inserted)
+    AND c.is_black = in_is_black
+    AND c.format_text = in_format_text;
+$$
+LANGUAGE SQL;
+
+DROP INDEX bb.IX_card_pending;
+
+ALTER TABLE bb.card DROP COLUMN rejection_reason;
+ALTER TABLE bb.card DROP COLUMN moderated_date;
+ALTER TABLE bb.card DROP COLUMN moderated_by_user_id;
+ALTER TABLE bb.card DROP COLUMN moderation_status;
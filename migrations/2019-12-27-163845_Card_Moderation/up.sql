@@ -0,0 +1,447 @@
+-- Your SQL goes here
+-- Cards already in the database are approved, cards created by `bb.create_card` start out pending
+ALTER TABLE bb.card ADD moderation_status TEXT NOT NULL DEFAULT 'approved'
+  CONSTRAINT CK_card_moderation_status CHECK (moderation_status IN ('pending', 'approved', 'rejected'));
+ALTER TABLE bb.card ADD moderated_by_user_id INT CONSTRAINT FK_card_moderated_by_user REFERENCES bb."user"(id);
+ALTER TABLE bb.card ADD moderated_date TIMESTAMP WITHOUT TIME ZONE;
+ALTER TABLE bb.card ADD rejection_reason TEXT;
+
+CREATE INDEX IX_card_pending ON bb.card (id) WHERE moderation_status = 'pending';
+
+CREATE OR REPLACE FUNCTION bb.create_card(
+  in_format_text TEXT,
+  in_is_black BOOLEAN,
+  in_submitted_by_user_id INT
+) RETURNS TABLE (
+  id INT,
+  is_duplicate BOOLEAN
+)
+AS $$
+  WITH inserted AS (
+    INSERT INTO bb.card
+      (format_text, is_black, submitted_by_user_id, moderation_status)
+    VALUES (in_format_text, in_is_black, in_submitted_by_user_id, 'pending')
+      ON CONFLICT ON CONSTRAINT UX_card_color_text DO NOTHING
+    RETURNING card.id
+  )
+  SELECT i.id, false
+  FROM inserted AS i
+  UNION ALL
+  SELECT c.id, true
+  FROM bb.card AS c
+  WHERE
+    NOT EXISTS (SELECT 1 FROM inserted)
+    AND c.is_black = in_is_black
+    AND c.format_text = in_format_text;
+$$
+LANGUAGE SQL;
+
+CREATE OR REPLACE FUNCTION bb.get_cards(
+	search text,
+	filter_black boolean,
+	previous_cursor integer,
+	n_cards integer,
+	card_sets integer[],
+	get_random boolean,
+	random_seed real,
+  user_submitted boolean,
+  filter_pick integer)
+    RETURNS TABLE(id integer, format_text text, is_black boolean, parent_set_id integer, parent_set_name text, total_votes integer, average_rating real, submitted_by_user_id integer, submitted_by_username text, parent_set_ids integer[], parent_set_names text[], pick integer, draw integer)
+    LANGUAGE 'plpgsql'
+
+    COST 100
+    VOLATILE
+    ROWS 1000
+
+AS $BODY$
+BEGIN
+
+  IF get_random THEN
+
+    IF random_seed IS NOT NULL THEN
+      PERFORM setseed(random_seed);
+    END IF;
+
+  END IF;
+
+  -- The page is selected first, so that every set of a card is only
+  -- aggregated for the cards that are actually returned
+  RETURN QUERY SELECT
+      page.id,
+      page.format_text,
+      page.is_black,
+      page.parent_set_id,
+      page.parent_set_name,
+      page.total_votes,
+      page.average_rating,
+      u.id AS "submitted_by_user_id",
+      u.username AS "submitted_by_username",
+      COALESCE(sets.ids, '{}') AS "parent_set_ids",
+      COALESCE(sets.names, '{}') AS "parent_set_names",
+      page.pick,
+      page.draw
+    FROM (
+      SELECT
+        m.*,
+        CASE WHEN get_random THEN RANDOM() ELSE m.id END AS "sort_key"
+      FROM (
+        -- A card is returned once, even when it belongs to several of the
+        -- matched sets. `parent_set_id` is the first of those sets.
+        SELECT DISTINCT ON (c.id)
+          c.id,
+          c.format_text,
+          c.is_black,
+          p.parent_set_id,
+          ps.name AS "parent_set_name",
+          c.total_votes,
+          c.average_rating,
+          c.submitted_by_user_id,
+          c.pick,
+          c.draw
+        FROM bb.card AS c
+          -- User-submitted cards are returned even when they have not been
+          -- added to a set
+          LEFT JOIN (
+            bb.parent_set_card AS p
+              INNER JOIN bb.parent_set AS ps ON ps.id = p.parent_set_id AND ps.is_active = true
+          ) ON p.card_id = c.id AND p.is_active = true
+        WHERE
+          (search IS NULL OR to_tsquery('english', search) @@ text_searchable_format_text)
+          AND (filter_black IS NULL OR c.is_black = filter_black)
+          AND (filter_pick IS NULL OR c.pick = filter_pick)
+          AND (card_sets IS NULL OR p.parent_set_id = ANY(card_sets))
+          AND c.is_active = true
+          AND (p.card_id IS NOT NULL OR c.submitted_by_user_id IS NOT NULL)
+          AND c.moderation_status = 'approved'
+          AND (
+            -- NULL == CardSource.All
+            user_submitted IS NULL OR
+            -- TRUE == CardSource.User
+            (user_submitted AND c.submitted_by_user_id IS NOT NULL) OR
+            -- FALSE == CardSource.Official
+            (NOT user_submitted AND c.submitted_by_user_id IS NULL)
+          )
+          -- Randomized results are paginated by offset, others by the last card ID
+          AND (get_random OR previous_cursor IS NULL OR c.id > previous_cursor)
+        ORDER BY c.id, p.parent_set_id
+      ) AS m
+      ORDER BY sort_key
+      LIMIT n_cards OFFSET CASE WHEN get_random THEN previous_cursor END
+    ) AS page
+      LEFT JOIN bb."user" AS u ON u.id = page.submitted_by_user_id
+      LEFT JOIN LATERAL (
+        SELECT
+          array_agg(s.id ORDER BY s.id) AS ids,
+          array_agg(s.name ORDER BY s.id) AS names
+        FROM bb.parent_set_card AS sc
+          INNER JOIN bb.parent_set AS s ON s.id = sc.parent_set_id
+        WHERE sc.card_id = page.id AND sc.is_active = true AND s.is_active = true
+      ) AS sets ON true
+    ORDER BY page.sort_key;
+
+END;
+$BODY$;
+
+DROP FUNCTION bb.get_user_submitted_cards;
+
+-- Pending and rejected cards are only included for the submitter (and admins)
+CREATE FUNCTION bb.get_user_submitted_cards(
+  in_user_id INT,
+  previous_cursor INT,
+  n_cards INT,
+  include_unapproved BOOLEAN
+) RETURNS TABLE (
+  id INT,
+  format_text TEXT,
+  is_black BOOLEAN,
+  parent_set_id INT,
+  parent_set_name TEXT,
+  total_votes INT,
+  average_rating REAL,
+  submitted_by_user_id INT,
+  submitted_by_username TEXT,
+  parent_set_ids INT[],
+  parent_set_names TEXT[],
+  pick INT,
+  draw INT
+)
+AS $$
+  SELECT * FROM bb.get_cards_by_ids(ARRAY(
+    SELECT c.id
+    FROM bb.card AS c
+    WHERE
+      c.submitted_by_user_id = in_user_id
+      AND c.is_active = true
+      AND (include_unapproved OR c.moderation_status = 'approved')
+      AND (previous_cursor IS NULL OR c.id > previous_cursor)
+    ORDER BY c.id
+    LIMIT n_cards
+  ));
+$$
+LANGUAGE SQL;
+
+CREATE OR REPLACE FUNCTION bb.get_moderation_queue(
+  previous_cursor INT,
+  n_cards INT
+) RETURNS TABLE (
+  id INT,
+  format_text TEXT,
+  is_black BOOLEAN,
+  parent_set_id INT,
+  parent_set_name TEXT,
+  total_votes INT,
+  average_rating REAL,
+  submitted_by_user_id INT,
+  submitted_by_username TEXT,
+  parent_set_ids INT[],
+  parent_set_names TEXT[],
+  pick INT,
+  draw INT
+)
+AS $$
+  SELECT * FROM bb.get_cards_by_ids(ARRAY(
+    SELECT c.id
+    FROM bb.card AS c
+    WHERE
+      c.moderation_status = 'pending'
+      AND c.is_active = true
+      AND (previous_cursor IS NULL OR c.id > previous_cursor)
+    ORDER BY c.id
+    LIMIT n_cards
+  ));
+$$
+LANGUAGE SQL;
+
+-- Pending and rejected cards are only included for their submitter (and admins)
+CREATE FUNCTION bb.get_visible_cards_by_ids(
+  card_ids INT[],
+  in_user_id INT,
+  include_unapproved BOOLEAN
+) RETURNS TABLE (
+  id INT,
+  format_text TEXT,
+  is_black BOOLEAN,
+  parent_set_id INT,
+  parent_set_name TEXT,
+  total_votes INT,
+  average_rating REAL,
+  submitted_by_user_id INT,
+  submitted_by_username TEXT,
+  parent_set_ids INT[],
+  parent_set_names TEXT[],
+  pick INT,
+  draw INT
+)
+AS $$
+  SELECT * FROM bb.get_cards_by_ids(ARRAY(
+    SELECT c.id
+    FROM bb.card AS c
+    WHERE
+      c.id = ANY(card_ids)
+      AND (
+        include_unapproved
+        OR c.moderation_status = 'approved'
+        OR c.submitted_by_user_id = in_user_id
+      )
+  ));
+$$
+LANGUAGE SQL;
+
+-- Returns false when the card does not exist (or is inactive)
+CREATE OR REPLACE FUNCTION bb.moderate_card(
+  in_card_id INT,
+  in_moderation_status TEXT,
+  in_moderated_by_user_id INT,
+  in_rejection_reason TEXT
+) RETURNS BOOLEAN
+AS $$
+  WITH updated AS (
+    UPDATE ONLY bb.card
+      SET
+        moderation_status = in_moderation_status,
+        moderated_by_user_id = in_moderated_by_user_id,
+        moderated_date = NOW(),
+        rejection_reason = in_rejection_reason,
+        last_modified = NOW()
+      WHERE id = in_card_id AND is_active = true
+    RETURNING id
+  )
+  SELECT EXISTS (SELECT 1 FROM updated);
+$$
+LANGUAGE SQL;
+
+-- Inactive and unknown cards are left out of the results
+CREATE OR REPLACE FUNCTION bb.get_card_moderations(card_ids INT[])
+RETURNS TABLE (
+  card_id INT,
+  moderation_status TEXT,
+  moderated_by_user_id INT,
+  moderated_by_username TEXT,
+  moderated_date TEXT,
+  rejection_reason TEXT
+)
+AS $$
+  SELECT
+    c.id AS "card_id",
+    c.moderation_status,
+    u.id AS "moderated_by_user_id",
+    u.username AS "moderated_by_username",
+    TO_CHAR(timezone('UTC', c.moderated_date::TIMESTAMPTZ), 'YYYY-MM-DD"T"HH24:MI:SS"Z"') AS "moderated_date",
+    c.rejection_reason
+  FROM bb.card AS c
+    LEFT JOIN bb."user" AS u ON u.id = c.moderated_by_user_id
+  WHERE c.id = ANY(card_ids) AND c.is_active = true;
+$$
+LANGUAGE SQL;
+
+CREATE OR REPLACE PROCEDURE bb.generate_daily_set(top_n_cards INT)
+AS $$
+BEGIN
+  with
+    inserted_set AS (
+      INSERT INTO bb.parent_set (name)
+        VALUES ('Daily Set: ' || TO_CHAR(NOW()::DATE, 'yyyy-mm-dd'))
+      RETURNING id)
+    , card_ratings AS (
+      SELECT
+        r.card_id
+        , SUM(CASE WHEN rating < 0.5 THEN 1 ELSE 0 END) AS "downvotes"
+        , SUM(CASE WHEN rating > 0.5 THEN 1 ELSE 0 END) AS "upvotes"
+        , AVG(CASE WHEN rating < 0.5 THEN rating ELSE NULL END) AS "low_votes"
+        , AVG(CASE WHEN rating > 0.5 THEN rating ELSE NULL END) AS "high_votes"
+      FROM bb.user_card_rating as r
+      GROUP BY r.card_id
+    )
+    , top_cards AS (
+      SELECT c.id
+      FROM bb.card AS c
+        INNER JOIN card_ratings AS r ON r.card_id = c.id
+      WHERE DATE_PART('DAY', created_date - NOW()::timestamp) <= 1
+        AND c.submitted_by_user_id IS NOT NULL
+        AND c.moderation_status = 'approved'
+      ORDER BY ( r.upvotes * (r.high_votes - 0.5) ) + ( r.downvotes * (r.low_votes - 0.5) )
+      LIMIT top_n_cards
+    )
+  INSERT INTO bb.parent_set_card (parent_set_id, card_id)
+    SELECT
+      s.id,
+      c.id
+    FROM inserted_set AS s
+      CROSS JOIN top_cards as c;
+END;
+$$
+LANGUAGE 'plpgsql';
+
+CREATE OR REPLACE PROCEDURE bb.generate_weekly_set(top_n_cards INT)
+AS $$
+BEGIN
+  with
+    inserted_set AS (
+      INSERT INTO bb.parent_set (name)
+        VALUES ('Weekly Set: ' || TO_CHAR(NOW()::DATE, 'yyyy-mm-dd'))
+      RETURNING id)
+    , card_ratings AS (
+      SELECT
+        r.card_id
+        , SUM(CASE WHEN rating < 0.5 THEN 1 ELSE 0 END) AS "downvotes"
+        , SUM(CASE WHEN rating > 0.5 THEN 1 ELSE 0 END) AS "upvotes"
+        , AVG(CASE WHEN rating < 0.5 THEN rating ELSE NULL END) AS "low_votes"
+        , AVG(CASE WHEN rating > 0.5 THEN rating ELSE NULL END) AS "high_votes"
+      FROM bb.user_card_rating as r
+      GROUP BY r.card_id
+    )
+    , top_cards AS (
+      SELECT c.id
+      FROM bb.card AS c
+        INNER JOIN card_ratings AS r ON r.card_id = c.id
+      WHERE DATE_PART('day', created_date - NOW()::timestamp) <= 7
+        AND c.submitted_by_user_id IS NOT NULL
+        AND c.moderation_status = 'approved'
+      ORDER BY ( r.upvotes * (r.high_votes - 0.5) ) + ( r.downvotes * (r.low_votes - 0.5) )
+      LIMIT top_n_cards
+    )
+  INSERT INTO bb.parent_set_card (parent_set_id, card_id)
+    SELECT
+      s.id,
+      c.id
+    FROM inserted_set AS s
+      CROSS JOIN top_cards as c;
+END;
+$$
+LANGUAGE 'plpgsql';
+
+CREATE OR REPLACE PROCEDURE bb.generate_monthly_set(top_n_cards INT)
+AS $$
+BEGIN
+  with
+    inserted_set AS (
+      INSERT INTO bb.parent_set (name)
+        VALUES ('Montly Set: ' || TO_CHAR(NOW()::DATE, 'yyyy-mm'))
+      RETURNING id)
+    , card_ratings AS (
+      SELECT
+        r.card_id
+        , SUM(CASE WHEN rating < 0.5 THEN 1 ELSE 0 END) AS "downvotes"
+        , SUM(CASE WHEN rating > 0.5 THEN 1 ELSE 0 END) AS "upvotes"
+        , AVG(CASE WHEN rating < 0.5 THEN rating ELSE NULL END) AS "low_votes"
+        , AVG(CASE WHEN rating > 0.5 THEN rating ELSE NULL END) AS "high_votes"
+      FROM bb.user_card_rating as r
+      GROUP BY r.card_id
+    )
+    , top_cards AS (
+      SELECT c.id
+      FROM bb.card AS c
+        INNER JOIN card_ratings AS r ON r.card_id = c.id
+      WHERE DATE_PART('month', created_date - NOW()::timestamp) <= 1
+        AND c.submitted_by_user_id IS NOT NULL
+        AND c.moderation_status = 'approved'
+      ORDER BY ( r.upvotes * (r.high_votes - 0.5) ) + ( r.downvotes * (r.low_votes - 0.5) )
+      LIMIT top_n_cards
+    )
+  INSERT INTO bb.parent_set_card (parent_set_id, card_id)
+    SELECT
+      s.id,
+      c.id
+    FROM inserted_set AS s
+      CROSS JOIN top_cards as c;
+END;
+$$
+LANGUAGE 'plpgsql';
+
+CREATE OR REPLACE PROCEDURE bb.generate_yearly_set(top_n_cards INT)
+AS $$
+BEGIN
+  with
+    inserted_set AS (
+      INSERT INTO bb.parent_set (name)
+        VALUES ('Yearly Set: ' || TO_CHAR(NOW()::DATE, 'yyyy'))
+      RETURNING id)
+    , card_ratings AS (
+      SELECT
+        r.card_id
+        , SUM(CASE WHEN rating < 0.5 THEN 1 ELSE 0 END) AS "downvotes"
+        , SUM(CASE WHEN rating > 0.5 THEN 1 ELSE 0 END) AS "upvotes"
+        , AVG(CASE WHEN rating < 0.5 THEN rating ELSE NULL END) AS "low_votes"
+        , AVG(CASE WHEN rating > 0.5 THEN rating ELSE NULL END) AS "high_votes"
+      FROM bb.user_card_rating as r
+      GROUP BY r.card_id
+    )
+    , top_cards AS (
+      SELECT c.id
+      FROM bb.card AS c
+        INNER JOIN card_ratings AS r ON r.card_id = c.id
+      WHERE DATE_PART('year', created_date - NOW()::timestamp) <= 1
+        AND c.submitted_by_user_id IS NOT NULL
+        AND c.moderation_status = 'approved'
+      ORDER BY ( r.upvotes * (r.high_votes - 0.5) ) + ( r.downvotes * (r.low_votes - 0.5) )
+      LIMIT top_n_cards
+    )
+  INSERT INTO bb.parent_set_card (parent_set_id, card_id)
+    SELECT
+      s.id,
+      c.id
+    FROM inserted_set AS s
+      CROSS JOIN top_cards as c;
+END;
+$$
+LANGUAGE 'plpgsql';
@@ -0,0 +1,5 @@
+-- This file should undo anything in `up.sql`
+DROP FUNCTION bb.get_card_reports;
+DROP FUNCTION bb.report_card;
+
+DROP TABLE bb.card_report;
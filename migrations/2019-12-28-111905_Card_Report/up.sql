@@ -0,0 +1,92 @@
+-- Your SQL goes here
+CREATE TABLE IF NOT EXISTS bb.card_report (
+  card_id INT NOT NULL CONSTRAINT FK_card_report_card REFERENCES bb.card(id),
+  user_id INT NOT NULL CONSTRAINT FK_card_report_user REFERENCES bb."user"(id),
+  reason TEXT NOT NULL,
+  created_date TIMESTAMP NOT NULL DEFAULT NOW(),
+  is_active BOOLEAN NOT NULL DEFAULT TRUE,
+  last_modified TIMESTAMP NOT NULL DEFAULT NOW(),
+  CONSTRAINT PK_card_report PRIMARY KEY (card_id, user_id)
+);
+
+CREATE INDEX IX_card_report_user ON bb.card_report (user_id);
+
+-- A user reports a card once; reporting it again replaces the reason.
+-- The card is deactivated once `report_threshold` distinct users have reported it.
+-- Returns false when the card does not exist (or is inactive).
+CREATE OR REPLACE FUNCTION bb.report_card(
+  in_card_id INT,
+  in_user_id INT,
+  in_reason TEXT,
+  report_threshold INT
+) RETURNS BOOLEAN
+AS $$
+BEGIN
+  IF NOT EXISTS (SELECT 1 FROM bb.card AS c WHERE c.id = in_card_id AND c.is_active = true) THEN
+    RETURN false;
+  END IF;
+
+  INSERT INTO bb.card_report (card_id, user_id, reason)
+  VALUES (in_card_id, in_user_id, in_reason)
+    ON CONFLICT ON CONSTRAINT PK_card_report DO UPDATE
+    SET
+      reason = EXCLUDED.reason,
+      is_active = true,
+      last_modified = NOW();
+
+  UPDATE ONLY bb.card
+    SET
+      is_active = false,
+      last_modified = NOW()
+    WHERE
+      id = in_card_id
+      AND (
+        SELECT COUNT(*)
+        FROM bb.card_report AS r
+        WHERE r.card_id = in_card_id AND r.is_active = true
+      ) >= report_threshold;
+
+  RETURN true;
+END;
+$$
+LANGUAGE 'plpgsql';
+
+-- Reports grouped by card, including cards that have been deactivated
+-- by their reports. Reports of each card are ordered from oldest to newest.
+CREATE OR REPLACE FUNCTION bb.get_card_reports(
+  previous_cursor INT,
+  n_cards INT
+) RETURNS TABLE (
+  card_id INT,
+  format_text TEXT,
+  is_black BOOLEAN,
+  is_active BOOLEAN,
+  reported_by_user_ids INT[],
+  reported_by_usernames TEXT[],
+  reasons TEXT[],
+  reported_dates TEXT[]
+)
+AS $$
+  SELECT
+    c.id,
+    c.format_text,
+    c.is_black,
+    c.is_active,
+    array_agg(u.id ORDER BY r.created_date, u.id),
+    array_agg(u.username ORDER BY r.created_date, u.id),
+    array_agg(r.reason ORDER BY r.created_date, u.id),
+    array_agg(
+      TO_CHAR(timezone('UTC', r.created_date::TIMESTAMPTZ), 'YYYY-MM-DD"T"HH24:MI:SS"Z"')
+      ORDER BY r.created_date, u.id
+    )
+  FROM bb.card_report AS r
+    INNER JOIN bb.card AS c ON c.id = r.card_id
+    INNER JOIN bb."user" AS u ON u.id = r.user_id
+  WHERE
+    r.is_active = true
+    AND (previous_cursor IS NULL OR c.id > previous_cursor)
+  GROUP BY c.id
+  ORDER BY c.id
+  LIMIT n_cards;
+$$
+LANGUAGE SQL;
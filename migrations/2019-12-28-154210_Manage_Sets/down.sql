@@ -0,0 +1,6 @@
+-- This file should undo anything in `up.sql`
+DROP FUNCTION bb.remove_cards_from_set;
+DROP FUNCTION bb.add_cards_to_set;
+DROP FUNCTION bb.deactivate_set;
+DROP FUNCTION bb.rename_set;
+DROP FUNCTION bb.create_set;
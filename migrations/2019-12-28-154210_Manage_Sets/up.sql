@@ -0,0 +1,71 @@
+-- Your SQL goes here
+-- Returns NULL when the name has already been taken by another set
+CREATE OR REPLACE FUNCTION bb.create_set(in_name TEXT) RETURNS INTEGER
+AS $$
+  INSERT INTO bb.parent_set (name)
+  VALUES (in_name)
+    ON CONFLICT ON CONSTRAINT UX_ParentSet_Name DO NOTHING
+  RETURNING id;
+$$
+LANGUAGE SQL;
+
+-- Returns false when the name has already been taken by another set
+CREATE OR REPLACE FUNCTION bb.rename_set(in_set_id INT, in_name TEXT) RETURNS BOOLEAN
+AS $$
+BEGIN
+  IF EXISTS (SELECT 1 FROM bb.parent_set AS s WHERE s.name = in_name AND s.id <> in_set_id) THEN
+    RETURN false;
+  END IF;
+
+  UPDATE ONLY bb.parent_set
+    SET
+      name = in_name,
+      last_modified = NOW()
+    WHERE id = in_set_id;
+
+  RETURN true;
+END;
+$$
+LANGUAGE 'plpgsql';
+
+-- Returns false when the set does not exist (or is already inactive)
+CREATE OR REPLACE FUNCTION bb.deactivate_set(in_set_id INT) RETURNS BOOLEAN
+AS $$
+  WITH updated AS (
+    UPDATE ONLY bb.parent_set
+      SET
+        is_active = false,
+        last_modified = NOW()
+      WHERE id = in_set_id AND is_active = true
+    RETURNING id
+  )
+  SELECT EXISTS (SELECT 1 FROM updated);
+$$
+LANGUAGE SQL;
+
+-- Cards that were removed from the set are added back
+CREATE OR REPLACE FUNCTION bb.add_cards_to_set(in_set_id INT, card_ids INT[]) RETURNS VOID
+AS $$
+  INSERT INTO bb.parent_set_card (parent_set_id, card_id)
+  SELECT DISTINCT in_set_id, c.id
+  FROM UNNEST(card_ids) AS c(id)
+    ON CONFLICT ON CONSTRAINT PK_ParentSetCard DO UPDATE
+    SET
+      is_active = true,
+      last_modified = NOW()
+    WHERE parent_set_card.is_active = false;
+$$
+LANGUAGE SQL;
+
+CREATE OR REPLACE FUNCTION bb.remove_cards_from_set(in_set_id INT, card_ids INT[]) RETURNS VOID
+AS $$
+  UPDATE ONLY bb.parent_set_card
+    SET
+      is_active = false,
+      last_modified = NOW()
+    WHERE
+      parent_set_id = in_set_id
+      AND card_id = ANY(card_ids)
+      AND is_active = true;
+$$
+LANGUAGE SQL;
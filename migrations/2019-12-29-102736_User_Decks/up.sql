@@ -0,0 +1,303 @@
+-- Your SQL goes here
+-- Decks are sets owned by a user. Only the names of official sets are unique.
+ALTER TABLE bb.parent_set ADD owner_user_id INT CONSTRAINT FK_parent_set_owner_user REFERENCES bb."user"(id);
+ALTER TABLE bb.parent_set ADD visibility TEXT NOT NULL DEFAULT 'public'
+  CONSTRAINT CK_parent_set_visibility CHECK (visibility IN ('private', 'unlisted', 'public'));
+
+CREATE INDEX IX_parent_set_owner_user ON bb.parent_set (owner_user_id);
+
+ALTER TABLE bb.parent_set DROP CONSTRAINT UX_ParentSet_Name;
+CREATE UNIQUE INDEX UX_parent_set_name ON bb.parent_set (name) WHERE owner_user_id IS NULL;
+
+-- Returns NULL when the name has already been taken by another set
+CREATE OR REPLACE FUNCTION bb.create_set(in_name TEXT) RETURNS INTEGER
+AS $$
+  INSERT INTO bb.parent_set (name)
+  VALUES (in_name)
+    ON CONFLICT (name) WHERE owner_user_id IS NULL DO NOTHING
+  RETURNING id;
+$$
+LANGUAGE SQL;
+
+-- Returns false when the name has already been taken by another set
+CREATE OR REPLACE FUNCTION bb.rename_set(in_set_id INT, in_name TEXT) RETURNS BOOLEAN
+AS $$
+BEGIN
+  IF EXISTS (
+    SELECT 1
+    FROM bb.parent_set AS s
+    WHERE s.name = in_name AND s.id <> in_set_id AND s.owner_user_id IS NULL
+  ) THEN
+    RETURN false;
+  END IF;
+
+  UPDATE ONLY bb.parent_set
+    SET
+      name = in_name,
+      last_modified = NOW()
+    WHERE id = in_set_id;
+
+  RETURN true;
+END;
+$$
+LANGUAGE 'plpgsql';
+
+CREATE OR REPLACE FUNCTION bb.get_sets(
+  search TEXT,
+  n_results INT,
+  cursor INT
+) RETURNS TABLE (
+  id INT,
+  name TEXT
+)
+AS $$
+BEGIN
+  RETURN QUERY SELECT
+    s.id,
+    s.name
+    FROM bb.parent_set AS s
+    WHERE
+    (search IS NULL OR text_searchable_name @@ to_tsquery('english', search))
+    AND (cursor IS NULL OR s.id > cursor)
+    AND s.is_active = true
+    AND s.owner_user_id IS NULL
+    ORDER by s.id
+    LIMIT n_results;
+END;
+$$
+LANGUAGE 'plpgsql';
+
+CREATE OR REPLACE FUNCTION bb.get_cards(
+	search text,
+	filter_black boolean,
+	previous_cursor integer,
+	n_cards integer,
+	card_sets integer[],
+	get_random boolean,
+	random_seed real,
+  user_submitted boolean,
+  filter_pick integer)
+    RETURNS TABLE(id integer, format_text text, is_black boolean, parent_set_id integer, parent_set_name text, total_votes integer, average_rating real, submitted_by_user_id integer, submitted_by_username text, parent_set_ids integer[], parent_set_names text[], pick integer, draw integer)
+    LANGUAGE 'plpgsql'
+
+    COST 100
+    VOLATILE
+    ROWS 1000
+
+AS $BODY$
+BEGIN
+
+  IF get_random THEN
+
+    IF random_seed IS NOT NULL THEN
+      PERFORM setseed(random_seed);
+    END IF;
+
+  END IF;
+
+  -- The page is selected first, so that every set of a card is only
+  -- aggregated for the cards that are actually returned
+  RETURN QUERY SELECT
+      page.id,
+      page.format_text,
+      page.is_black,
+      page.parent_set_id,
+      page.parent_set_name,
+      page.total_votes,
+      page.average_rating,
+      u.id AS "submitted_by_user_id",
+      u.username AS "submitted_by_username",
+      COALESCE(sets.ids, '{}') AS "parent_set_ids",
+      COALESCE(sets.names, '{}') AS "parent_set_names",
+      page.pick,
+      page.draw
+    FROM (
+      SELECT
+        m.*,
+        CASE WHEN get_random THEN RANDOM() ELSE m.id END AS "sort_key"
+      FROM (
+        -- A card is returned once, even when it belongs to several of the
+        -- matched sets. `parent_set_id` is the first of those sets.
+        SELECT DISTINCT ON (c.id)
+          c.id,
+          c.format_text,
+          c.is_black,
+          p.parent_set_id,
+          ps.name AS "parent_set_name",
+          c.total_votes,
+          c.average_rating,
+          c.submitted_by_user_id,
+          c.pick,
+          c.draw
+        FROM bb.card AS c
+          -- User-submitted cards are returned even when they have not been
+          -- added to a set
+          -- Decks are only joined when they are searched for
+          LEFT JOIN (
+            bb.parent_set_card AS p
+              INNER JOIN bb.parent_set AS ps ON ps.id = p.parent_set_id AND ps.is_active = true
+          ) ON p.card_id = c.id AND p.is_active = true
+            AND (ps.owner_user_id IS NULL OR ps.id = ANY(card_sets))
+        WHERE
+          (search IS NULL OR to_tsquery('english', search) @@ text_searchable_format_text)
+          AND (filter_black IS NULL OR c.is_black = filter_black)
+          AND (filter_pick IS NULL OR c.pick = filter_pick)
+          AND (card_sets IS NULL OR p.parent_set_id = ANY(card_sets))
+          AND c.is_active = true
+          AND (p.card_id IS NOT NULL OR c.submitted_by_user_id IS NOT NULL)
+          AND c.moderation_status = 'approved'
+          AND (
+            -- NULL == CardSource.All
+            user_submitted IS NULL OR
+            -- TRUE == CardSource.User
+            (user_submitted AND c.submitted_by_user_id IS NOT NULL) OR
+            -- FALSE == CardSource.Official
+            (NOT user_submitted AND c.submitted_by_user_id IS NULL)
+          )
+          -- Randomized results are paginated by offset, others by the last card ID
+          AND (get_random OR previous_cursor IS NULL OR c.id > previous_cursor)
+        ORDER BY c.id, p.parent_set_id
+      ) AS m
+      ORDER BY sort_key
+      LIMIT n_cards OFFSET CASE WHEN get_random THEN previous_cursor END
+    ) AS page
+      LEFT JOIN bb."user" AS u ON u.id = page.submitted_by_user_id
+      LEFT JOIN LATERAL (
+        SELECT
+          array_agg(s.id ORDER BY s.id) AS ids,
+          array_agg(s.name ORDER BY s.id) AS names
+        FROM bb.parent_set_card AS sc
+          INNER JOIN bb.parent_set AS s ON s.id = sc.parent_set_id
+        WHERE
+          sc.card_id = page.id AND sc.is_active = true AND s.is_active = true
+          AND s.owner_user_id IS NULL
+      ) AS sets ON true
+    ORDER BY page.sort_key;
+
+END;
+$BODY$;
+
+CREATE OR REPLACE FUNCTION bb.get_cards_by_ids(card_ids INT[])
+RETURNS TABLE (
+  id INT,
+  format_text TEXT,
+  is_black BOOLEAN,
+  parent_set_id INT,
+  parent_set_name TEXT,
+  total_votes INT,
+  average_rating REAL,
+  submitted_by_user_id INT,
+  submitted_by_username TEXT,
+  parent_set_ids INT[],
+  parent_set_names TEXT[],
+  pick INT,
+  draw INT
+)
+AS $$
+  SELECT
+    c.id,
+    c.format_text,
+    c.is_black,
+    sets.ids[1] AS "parent_set_id",
+    sets.names[1] AS "parent_set_name",
+    c.total_votes,
+    c.average_rating,
+    u.id AS "submitted_by_user_id",
+    u.username AS "submitted_by_username",
+    COALESCE(sets.ids, '{}') AS "parent_set_ids",
+    COALESCE(sets.names, '{}') AS "parent_set_names",
+    c.pick,
+    c.draw
+  FROM bb.card AS c
+    LEFT JOIN LATERAL (
+      SELECT
+        array_agg(s.id ORDER BY s.id) AS ids,
+        array_agg(s.name ORDER BY s.id) AS names
+      FROM bb.parent_set_card AS sc
+        INNER JOIN bb.parent_set AS s ON s.id = sc.parent_set_id
+      WHERE
+        sc.card_id = c.id AND sc.is_active = true AND s.is_active = true
+        AND s.owner_user_id IS NULL
+    ) AS sets ON true
+    LEFT JOIN bb."user" AS u ON u.id = c.submitted_by_user_id
+  WHERE c.id = ANY(card_ids) AND c.is_active = true
+  ORDER BY c.id;
+$$
+LANGUAGE SQL;
+
+CREATE OR REPLACE FUNCTION bb.create_deck(
+  in_owner_user_id INT,
+  in_name TEXT,
+  in_visibility TEXT
+) RETURNS INTEGER
+AS $$
+  INSERT INTO bb.parent_set (name, owner_user_id, visibility)
+  VALUES (in_name, in_owner_user_id, in_visibility)
+  RETURNING id;
+$$
+LANGUAGE SQL;
+
+-- NULL arguments are left unchanged
+CREATE OR REPLACE FUNCTION bb.update_deck(
+  in_deck_id INT,
+  in_name TEXT,
+  in_visibility TEXT
+) RETURNS VOID
+AS $$
+  UPDATE ONLY bb.parent_set
+    SET
+      name = COALESCE(in_name, name),
+      visibility = COALESCE(in_visibility, visibility),
+      last_modified = NOW()
+    WHERE id = in_deck_id AND owner_user_id IS NOT NULL;
+$$
+LANGUAGE SQL;
+
+CREATE OR REPLACE FUNCTION bb.get_decks_by_ids(deck_ids INT[])
+RETURNS TABLE (
+  id INT,
+  name TEXT,
+  visibility TEXT,
+  owner_user_id INT,
+  owner_username TEXT
+)
+AS $$
+  SELECT
+    s.id,
+    s.name,
+    s.visibility,
+    u.id AS "owner_user_id",
+    u.username AS "owner_username"
+  FROM bb.parent_set AS s
+    INNER JOIN bb."user" AS u ON u.id = s.owner_user_id
+  WHERE s.id = ANY(deck_ids) AND s.is_active = true
+  ORDER BY s.id;
+$$
+LANGUAGE SQL;
+
+CREATE OR REPLACE FUNCTION bb.get_user_decks(
+  in_user_id INT,
+  previous_cursor INT,
+  n_decks INT,
+  include_unlisted BOOLEAN
+) RETURNS TABLE (
+  id INT,
+  name TEXT,
+  visibility TEXT,
+  owner_user_id INT,
+  owner_username TEXT
+)
+AS $$
+  SELECT * FROM bb.get_decks_by_ids(ARRAY(
+    SELECT s.id
+    FROM bb.parent_set AS s
+    WHERE
+      s.owner_user_id = in_user_id
+      AND s.is_active = true
+      AND (include_unlisted OR s.visibility = 'public')
+      AND (previous_cursor IS NULL OR s.id > previous_cursor)
+    ORDER BY s.id
+    LIMIT n_decks
+  ));
+$$
+LANGUAGE SQL;
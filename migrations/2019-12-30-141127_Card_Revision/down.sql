@@ -0,0 +1,18 @@
+-- This file should undo anything in `up.sql`
+DROP FUNCTION bb.get_card_revisions;
+DROP FUNCTION bb.deactivate_card;
+DROP FUNCTION bb.edit_card;
+
+DROP TRIGGER revision_update_user_card_combination_rating ON bb.user_card_combination_rating;
+DROP FUNCTION bb.user_card_combination_rating_revision_trigger;
+DROP TRIGGER revision_update_user_card_rating ON bb.user_card_rating;
+DROP FUNCTION bb.user_card_rating_revision_trigger;
+DROP TRIGGER revision_insert_card ON bb.card;
+DROP FUNCTION bb.card_revision_trigger;
+
+ALTER TABLE bb.user_card_combination_rating DROP COLUMN black_card_revision;
+ALTER TABLE bb.user_card_combination_rating DROP COLUMN white_card_revision;
+ALTER TABLE bb.user_card_rating DROP COLUMN card_revision;
+
+DROP TABLE bb.card_revision;
+ALTER TABLE bb.card DROP COLUMN revision;
@@ -0,0 +1,167 @@
+-- Your SQL goes here
+ALTER TABLE bb.card ADD revision INT NOT NULL DEFAULT 1;
+
+-- Every wording a card has had. Revision 1 is the card as it was created.
+CREATE TABLE IF NOT EXISTS bb.card_revision (
+  card_id INT NOT NULL CONSTRAINT FK_card_revision_card REFERENCES bb.card(id),
+  revision INT NOT NULL,
+  format_text TEXT NOT NULL,
+  edited_by_user_id INT CONSTRAINT FK_card_revision_user REFERENCES bb."user"(id),
+  created_date TIMESTAMP NOT NULL DEFAULT NOW(),
+  CONSTRAINT PK_card_revision PRIMARY KEY (card_id, revision)
+);
+
+INSERT INTO bb.card_revision (card_id, revision, format_text, edited_by_user_id, created_date)
+  SELECT c.id, c.revision, c.format_text, c.submitted_by_user_id, c.created_date
+  FROM bb.card AS c;
+
+-- Ratings record the revision of the card that was rated, which is updated
+-- whenever the rating is changed
+ALTER TABLE bb.user_card_rating ADD card_revision INT NOT NULL DEFAULT 1;
+ALTER TABLE bb.user_card_combination_rating ADD white_card_revision INT NOT NULL DEFAULT 1;
+ALTER TABLE bb.user_card_combination_rating ADD black_card_revision INT NOT NULL DEFAULT 1;
+
+-- Records revision 1 of cards inserted by `bb.create_card`, the seed import,
+-- or any other statement
+CREATE OR REPLACE FUNCTION bb.card_revision_trigger() RETURNS TRIGGER
+AS $$
+BEGIN
+  INSERT INTO bb.card_revision (card_id, revision, format_text, edited_by_user_id, created_date)
+  VALUES (NEW.id, NEW.revision, NEW.format_text, NEW.submitted_by_user_id, NEW.created_date);
+  RETURN NEW;
+END;
+$$
+LANGUAGE 'plpgsql';
+
+CREATE TRIGGER revision_insert_card
+    AFTER INSERT
+    ON bb.card
+    FOR EACH ROW
+    EXECUTE PROCEDURE bb.card_revision_trigger();
+
+CREATE OR REPLACE FUNCTION bb.user_card_rating_revision_trigger() RETURNS TRIGGER
+AS $$
+BEGIN
+  SELECT c.revision FROM bb.card AS c WHERE c.id = NEW.card_id INTO NEW.card_revision;
+  RETURN NEW;
+END;
+$$
+LANGUAGE 'plpgsql';
+
+CREATE TRIGGER revision_update_user_card_rating
+    BEFORE INSERT OR UPDATE OF rating
+    ON bb.user_card_rating
+    FOR EACH ROW
+    EXECUTE PROCEDURE bb.user_card_rating_revision_trigger();
+
+CREATE OR REPLACE FUNCTION bb.user_card_combination_rating_revision_trigger() RETURNS TRIGGER
+AS $$
+BEGIN
+  SELECT c.revision FROM bb.card AS c WHERE c.id = NEW.white_card_id INTO NEW.white_card_revision;
+  SELECT c.revision FROM bb.card AS c WHERE c.id = NEW.black_card_id INTO NEW.black_card_revision;
+  RETURN NEW;
+END;
+$$
+LANGUAGE 'plpgsql';
+
+CREATE TRIGGER revision_update_user_card_combination_rating
+    BEFORE INSERT OR UPDATE OF rating
+    ON bb.user_card_combination_rating
+    FOR EACH ROW
+    EXECUTE PROCEDURE bb.user_card_combination_rating_revision_trigger();
+
+-- Changes the wording of an active card, recording it as a new revision.
+-- When `resubmit`, the card goes back to the moderation queue.
+-- Returns the ID of the card already using the wording, if any, in which case
+-- nothing is changed. Editing a card to its current wording is a no-op.
+CREATE OR REPLACE FUNCTION bb.edit_card(
+  in_card_id INT,
+  in_format_text TEXT,
+  in_edited_by_user_id INT,
+  resubmit BOOLEAN
+) RETURNS INTEGER
+AS $$
+DECLARE
+  duplicate_card_id INT;
+  new_revision INT;
+BEGIN
+  SELECT d.id
+    FROM bb.card AS c
+      INNER JOIN bb.card AS d ON d.is_black = c.is_black AND d.format_text = in_format_text
+    WHERE c.id = in_card_id AND d.id <> in_card_id
+  INTO duplicate_card_id;
+
+  IF duplicate_card_id IS NOT NULL THEN
+    RETURN duplicate_card_id;
+  END IF;
+
+  UPDATE ONLY bb.card
+    SET
+      format_text = in_format_text,
+      revision = revision + 1,
+      moderation_status = CASE WHEN resubmit THEN 'pending' ELSE moderation_status END,
+      moderated_by_user_id = CASE WHEN resubmit THEN NULL ELSE moderated_by_user_id END,
+      moderated_date = CASE WHEN resubmit THEN NULL ELSE moderated_date END,
+      rejection_reason = CASE WHEN resubmit THEN NULL ELSE rejection_reason END,
+      last_modified = NOW()
+    WHERE id = in_card_id AND is_active = true AND format_text <> in_format_text
+    RETURNING revision INTO new_revision;
+
+  IF new_revision IS NOT NULL THEN
+    INSERT INTO bb.card_revision (card_id, revision, format_text, edited_by_user_id)
+    VALUES (in_card_id, new_revision, in_format_text, in_edited_by_user_id);
+  END IF;
+
+  RETURN NULL;
+END;
+$$
+LANGUAGE 'plpgsql';
+
+-- Soft-deletes a card. Returns false when the card does not exist (or is inactive).
+CREATE OR REPLACE FUNCTION bb.deactivate_card(in_card_id INT) RETURNS BOOLEAN
+AS $$
+  WITH deactivated AS (
+    UPDATE ONLY bb.card
+      SET
+        is_active = false,
+        last_modified = NOW()
+      WHERE id = in_card_id AND is_active = true
+      RETURNING id
+  )
+  SELECT EXISTS (SELECT 1 FROM deactivated);
+$$
+LANGUAGE SQL;
+
+-- Revisions of an active card, oldest first, along with the active ratings
+-- given to each revision
+CREATE OR REPLACE FUNCTION bb.get_card_revisions(in_card_id INT)
+RETURNS TABLE (
+  revision INT,
+  format_text TEXT,
+  edited_by_user_id INT,
+  edited_by_username TEXT,
+  created_date TEXT,
+  total_votes INT,
+  average_rating REAL
+)
+AS $$
+  SELECT
+    r.revision,
+    r.format_text,
+    u.id AS "edited_by_user_id",
+    u.username AS "edited_by_username",
+    TO_CHAR(timezone('UTC', r.created_date::TIMESTAMPTZ), 'YYYY-MM-DD"T"HH24:MI:SS"Z"') AS "created_date",
+    COUNT(ucr.rating)::INT AS "total_votes",
+    AVG(ucr.rating)::REAL AS "average_rating"
+  FROM bb.card_revision AS r
+    INNER JOIN bb.card AS c ON c.id = r.card_id
+    LEFT JOIN bb."user" AS u ON u.id = r.edited_by_user_id
+    LEFT JOIN bb.user_card_rating AS ucr
+      ON ucr.card_id = r.card_id
+      AND ucr.card_revision = r.revision
+      AND ucr.is_active = true
+  WHERE r.card_id = in_card_id AND c.is_active = true
+  GROUP BY r.revision, r.format_text, r.created_date, u.id, u.username
+  ORDER BY r.revision;
+$$
+LANGUAGE SQL;
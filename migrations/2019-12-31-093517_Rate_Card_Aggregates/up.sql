@@ -0,0 +1,143 @@
+-- Your SQL goes here
+-- Aggregates are recomputed from the active ratings instead of being adjusted
+-- in place, so they can no longer drift from the ratings they summarize
+CREATE OR REPLACE FUNCTION bb.update_card_rating_aggregates(in_card_id INT)
+RETURNS TABLE (
+  total_votes INT,
+  average_rating REAL
+)
+AS $$
+  UPDATE ONLY bb.card AS c
+    SET
+      total_votes = r.total_votes,
+      average_rating = r.average_rating
+    FROM (
+      SELECT
+        COUNT(ucr.rating)::INT AS "total_votes",
+        AVG(ucr.rating)::REAL AS "average_rating"
+      FROM bb.user_card_rating AS ucr
+      WHERE ucr.card_id = in_card_id AND ucr.is_active = true
+    ) AS r
+    WHERE c.id = in_card_id
+    RETURNING c.total_votes, c.average_rating;
+$$
+LANGUAGE SQL;
+
+UPDATE ONLY bb.card AS c
+  SET
+    total_votes = COALESCE(r.total_votes, 0),
+    average_rating = r.average_rating
+  FROM bb.card AS a
+    LEFT JOIN (
+      SELECT
+        ucr.card_id,
+        COUNT(ucr.rating)::INT AS "total_votes",
+        AVG(ucr.rating)::REAL AS "average_rating"
+      FROM bb.user_card_rating AS ucr
+      WHERE ucr.is_active = true
+      GROUP BY ucr.card_id
+    ) AS r ON r.card_id = a.id
+  WHERE a.id = c.id
+    AND (c.total_votes <> COALESCE(r.total_votes, 0) OR c.average_rating IS DISTINCT FROM r.average_rating);
+
+DROP FUNCTION bb.user_rate_card;
+
+-- Rating a card again replaces the user's previous rating, which is returned
+-- (NULL for a first vote). No row is returned when the card does not exist
+-- (or is inactive).
+CREATE OR REPLACE FUNCTION bb.user_rate_card(
+  in_user_id INT,
+  in_card_id INT,
+  in_rating REAL
+) RETURNS TABLE (
+  total_votes INT,
+  average_rating REAL,
+  previous_rating REAL
+)
+AS $$
+DECLARE
+  existing_rating REAL;
+BEGIN
+  PERFORM 1 FROM bb.card AS c WHERE c.id = in_card_id AND c.is_active = true FOR UPDATE;
+  IF NOT FOUND THEN
+    RETURN;
+  END IF;
+
+  SELECT r.rating
+    FROM bb.user_card_rating AS r
+    WHERE r.user_id = in_user_id AND r.card_id = in_card_id AND r.is_active = true
+  INTO existing_rating;
+
+  INSERT INTO bb.user_card_rating (user_id, card_id, rating)
+  VALUES (in_user_id, in_card_id, in_rating)
+    ON CONFLICT ON CONSTRAINT PK_user_card_rating DO UPDATE
+    SET
+      rating = EXCLUDED.rating,
+      is_active = true,
+      last_modified = NOW();
+
+  RETURN QUERY
+    SELECT a.total_votes, a.average_rating, existing_rating
+    FROM bb.update_card_rating_aggregates(in_card_id) AS a;
+END;
+$$
+LANGUAGE 'plpgsql';
+
+-- Removes the user's vote on a card, returning the removed rating (NULL when the
+-- user had not rated the card). No row is returned when the card does not exist
+-- (or is inactive).
+CREATE OR REPLACE FUNCTION bb.user_unrate_card(
+  in_user_id INT,
+  in_card_id INT
+) RETURNS TABLE (
+  total_votes INT,
+  average_rating REAL,
+  previous_rating REAL
+)
+AS $$
+DECLARE
+  existing_rating REAL;
+BEGIN
+  PERFORM 1 FROM bb.card AS c WHERE c.id = in_card_id AND c.is_active = true FOR UPDATE;
+  IF NOT FOUND THEN
+    RETURN;
+  END IF;
+
+  UPDATE ONLY bb.user_card_rating AS r
+    SET
+      is_active = false,
+      last_modified = NOW()
+    WHERE r.user_id = in_user_id AND r.card_id = in_card_id AND r.is_active = true
+    RETURNING r.rating INTO existing_rating;
+
+  RETURN QUERY
+    SELECT a.total_votes, a.average_rating, existing_rating
+    FROM bb.update_card_rating_aggregates(in_card_id) AS a;
+END;
+$$
+LANGUAGE 'plpgsql';
+
+-- Removed votes are no longer listed
+CREATE OR REPLACE FUNCTION bb.get_user_card_ratings(
+  in_user_id INT,
+  previous_cursor INT,
+  n_ratings INT
+) RETURNS TABLE (
+  card_id INT,
+  rating REAL
+)
+AS $$
+  SELECT
+    r.card_id,
+    r.rating
+  FROM bb.user_card_rating AS r
+    INNER JOIN bb.card AS c ON c.id = r.card_id
+  WHERE
+    r.user_id = in_user_id
+    AND r.is_active = true
+    AND c.is_active = true
+    AND (previous_cursor IS NULL OR r.card_id > previous_cursor)
+  ORDER BY r.card_id
+  LIMIT n_ratings;
+$$
+LANGUAGE SQL;
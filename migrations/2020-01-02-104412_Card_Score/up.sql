@@ -0,0 +1,439 @@
+-- Your SQL goes here
+-- Ranking score of a card: the Bayesian average of its ratings, starting from
+-- 10 votes of 0.5. A single 1.0 vote scores 0.55, while 500 votes averaging
+-- 0.9 score 0.89. Cards without votes score 0.5.
+CREATE OR REPLACE FUNCTION bb.card_score(total_votes INTEGER, average_rating REAL) RETURNS REAL
+AS $$
+  SELECT ((total_votes * COALESCE(average_rating, 0) + 10 * 0.5) / (total_votes + 10))::REAL;
+$$
+LANGUAGE SQL
+IMMUTABLE;
+
+ALTER TABLE bb.card ADD score REAL NOT NULL DEFAULT 0.5;
+
+-- Keeps the score up to date whenever the rating aggregates change
+CREATE OR REPLACE FUNCTION bb.card_score_trigger() RETURNS TRIGGER
+AS $$
+BEGIN
+  NEW.score := bb.card_score(NEW.total_votes, NEW.average_rating);
+  RETURN NEW;
+END;
+$$
+LANGUAGE 'plpgsql';
+
+CREATE TRIGGER score_update_card
+    BEFORE INSERT OR UPDATE OF total_votes, average_rating
+    ON bb.card
+    FOR EACH ROW
+    EXECUTE PROCEDURE bb.card_score_trigger();
+
+UPDATE bb.card
+  SET score = bb.card_score(total_votes, average_rating)
+  WHERE total_votes > 0;
+
+CREATE INDEX IX_card_score ON bb.card (score DESC, id);
+
+DROP FUNCTION bb.get_visible_cards_by_ids;
+DROP FUNCTION bb.get_moderation_queue;
+DROP FUNCTION bb.get_user_submitted_cards;
+DROP FUNCTION bb.get_cards_by_ids;
+DROP FUNCTION bb.get_cards(text,boolean,integer,integer,integer[],boolean,real,boolean,integer);
+
+CREATE OR REPLACE FUNCTION bb.get_cards(
+	search text,
+	filter_black boolean,
+	previous_cursor integer,
+	n_cards integer,
+	card_sets integer[],
+	get_random boolean,
+	random_seed real,
+  user_submitted boolean,
+  filter_pick integer,
+  sort_by text,
+  previous_sort_key double precision)
+    RETURNS TABLE(id integer, format_text text, is_black boolean, parent_set_id integer, parent_set_name text, total_votes integer, average_rating real, submitted_by_user_id integer, submitted_by_username text, parent_set_ids integer[], parent_set_names text[], pick integer, draw integer, score real)
+    LANGUAGE 'plpgsql'
+
+    COST 100
+    VOLATILE
+    ROWS 1000
+
+AS $BODY$
+BEGIN
+
+  IF get_random THEN
+
+    IF random_seed IS NOT NULL THEN
+      PERFORM setseed(random_seed);
+    END IF;
+
+  END IF;
+
+  -- The page is selected first, so that every set of a card is only
+  -- aggregated for the cards that are actually returned
+  RETURN QUERY SELECT
+      page.id,
+      page.format_text,
+      page.is_black,
+      page.parent_set_id,
+      page.parent_set_name,
+      page.total_votes,
+      page.average_rating,
+      u.id AS "submitted_by_user_id",
+      u.username AS "submitted_by_username",
+      COALESCE(sets.ids, '{}') AS "parent_set_ids",
+      COALESCE(sets.names, '{}') AS "parent_set_names",
+      page.pick,
+      page.draw,
+      page.score
+    FROM (
+      SELECT
+        m.*,
+        CASE
+          WHEN get_random THEN RANDOM()
+          -- Highest score first
+          WHEN sort_by = 'score' THEN -m.score
+          ELSE m.id
+        END AS "sort_key"
+      FROM (
+        -- A card is returned once, even when it belongs to several of the
+        -- matched sets. `parent_set_id` is the first of those sets.
+        SELECT DISTINCT ON (c.id)
+          c.id,
+          c.format_text,
+          c.is_black,
+          p.parent_set_id,
+          ps.name AS "parent_set_name",
+          c.total_votes,
+          c.average_rating,
+          c.submitted_by_user_id,
+          c.pick,
+          c.draw,
+          c.score
+        FROM bb.card AS c
+          -- User-submitted cards are returned even when they have not been
+          -- added to a set
+          -- Decks are only joined when they are searched for
+          LEFT JOIN (
+            bb.parent_set_card AS p
+              INNER JOIN bb.parent_set AS ps ON ps.id = p.parent_set_id AND ps.is_active = true
+          ) ON p.card_id = c.id AND p.is_active = true
+            AND (ps.owner_user_id IS NULL OR ps.id = ANY(card_sets))
+        WHERE
+          (search IS NULL OR to_tsquery('english', search) @@ text_searchable_format_text)
+          AND (filter_black IS NULL OR c.is_black = filter_black)
+          AND (filter_pick IS NULL OR c.pick = filter_pick)
+          AND (card_sets IS NULL OR p.parent_set_id = ANY(card_sets))
+          AND c.is_active = true
+          AND (p.card_id IS NOT NULL OR c.submitted_by_user_id IS NOT NULL)
+          AND c.moderation_status = 'approved'
+          AND (
+            -- NULL == CardSource.All
+            user_submitted IS NULL OR
+            -- TRUE == CardSource.User
+            (user_submitted AND c.submitted_by_user_id IS NOT NULL) OR
+            -- FALSE == CardSource.Official
+            (NOT user_submitted AND c.submitted_by_user_id IS NULL)
+          )
+          -- Randomized results are paginated by offset, others by the sort key
+          -- and ID of the last card
+          AND (
+            get_random OR previous_cursor IS NULL
+            OR (sort_by = 'score' AND (
+              c.score < previous_sort_key
+              OR (c.score = previous_sort_key AND c.id > previous_cursor)
+            ))
+            OR (sort_by IS DISTINCT FROM 'score' AND c.id > previous_cursor)
+          )
+        ORDER BY c.id, p.parent_set_id
+      ) AS m
+      ORDER BY sort_key, m.id
+      LIMIT n_cards OFFSET CASE WHEN get_random THEN previous_cursor END
+    ) AS page
+      LEFT JOIN bb."user" AS u ON u.id = page.submitted_by_user_id
+      LEFT JOIN LATERAL (
+        SELECT
+          array_agg(s.id ORDER BY s.id) AS ids,
+          array_agg(s.name ORDER BY s.id) AS names
+        FROM bb.parent_set_card AS sc
+          INNER JOIN bb.parent_set AS s ON s.id = sc.parent_set_id
+        WHERE
+          sc.card_id = page.id AND sc.is_active = true AND s.is_active = true
+          AND s.owner_user_id IS NULL
+      ) AS sets ON true
+    ORDER BY page.sort_key, page.id;
+
+END;
+$BODY$;
+
+CREATE OR REPLACE FUNCTION bb.get_cards_by_ids(card_ids INT[])
+RETURNS TABLE (
+  id INT,
+  format_text TEXT,
+  is_black BOOLEAN,
+  parent_set_id INT,
+  parent_set_name TEXT,
+  total_votes INT,
+  average_rating REAL,
+  submitted_by_user_id INT,
+  submitted_by_username TEXT,
+  parent_set_ids INT[],
+  parent_set_names TEXT[],
+  pick INT,
+  draw INT,
+  score REAL
+)
+AS $$
+  SELECT
+    c.id,
+    c.format_text,
+    c.is_black,
+    sets.ids[1] AS "parent_set_id",
+    sets.names[1] AS "parent_set_name",
+    c.total_votes,
+    c.average_rating,
+    u.id AS "submitted_by_user_id",
+    u.username AS "submitted_by_username",
+    COALESCE(sets.ids, '{}') AS "parent_set_ids",
+    COALESCE(sets.names, '{}') AS "parent_set_names",
+    c.pick,
+    c.draw,
+    c.score
+  FROM bb.card AS c
+    LEFT JOIN LATERAL (
+      SELECT
+        array_agg(s.id ORDER BY s.id) AS ids,
+        array_agg(s.name ORDER BY s.id) AS names
+      FROM bb.parent_set_card AS sc
+        INNER JOIN bb.parent_set AS s ON s.id = sc.parent_set_id
+      WHERE
+        sc.card_id = c.id AND sc.is_active = true AND s.is_active = true
+        AND s.owner_user_id IS NULL
+    ) AS sets ON true
+    LEFT JOIN bb."user" AS u ON u.id = c.submitted_by_user_id
+  WHERE c.id = ANY(card_ids) AND c.is_active = true
+  ORDER BY c.id;
+$$
+LANGUAGE SQL;
+
+CREATE FUNCTION bb.get_user_submitted_cards(
+  in_user_id INT,
+  previous_cursor INT,
+  n_cards INT,
+  include_unapproved BOOLEAN
+) RETURNS TABLE (
+  id INT,
+  format_text TEXT,
+  is_black BOOLEAN,
+  parent_set_id INT,
+  parent_set_name TEXT,
+  total_votes INT,
+  average_rating REAL,
+  submitted_by_user_id INT,
+  submitted_by_username TEXT,
+  parent_set_ids INT[],
+  parent_set_names TEXT[],
+  pick INT,
+  draw INT,
+  score REAL
+)
+AS $$
+  SELECT * FROM bb.get_cards_by_ids(ARRAY(
+    SELECT c.id
+    FROM bb.card AS c
+    WHERE
+      c.submitted_by_user_id = in_user_id
+      AND c.is_active = true
+      AND (include_unapproved OR c.moderation_status = 'approved')
+      AND (previous_cursor IS NULL OR c.id > previous_cursor)
+    ORDER BY c.id
+    LIMIT n_cards
+  ));
+$$
+LANGUAGE SQL;
+
+CREATE OR REPLACE FUNCTION bb.get_moderation_queue(
+  previous_cursor INT,
+  n_cards INT
+) RETURNS TABLE (
+  id INT,
+  format_text TEXT,
+  is_black BOOLEAN,
+  parent_set_id INT,
+  parent_set_name TEXT,
+  total_votes INT,
+  average_rating REAL,
+  submitted_by_user_id INT,
+  submitted_by_username TEXT,
+  parent_set_ids INT[],
+  parent_set_names TEXT[],
+  pick INT,
+  draw INT,
+  score REAL
+)
+AS $$
+  SELECT * FROM bb.get_cards_by_ids(ARRAY(
+    SELECT c.id
+    FROM bb.card AS c
+    WHERE
+      c.moderation_status = 'pending'
+      AND c.is_active = true
+      AND (previous_cursor IS NULL OR c.id > previous_cursor)
+    ORDER BY c.id
+    LIMIT n_cards
+  ));
+$$
+LANGUAGE SQL;
+
+-- Pending and rejected cards are only included for their submitter (and admins)
+CREATE FUNCTION bb.get_visible_cards_by_ids(
+  card_ids INT[],
+  in_user_id INT,
+  include_unapproved BOOLEAN
+) RETURNS TABLE (
+  id INT,
+  format_text TEXT,
+  is_black BOOLEAN,
+  parent_set_id INT,
+  parent_set_name TEXT,
+  total_votes INT,
+  average_rating REAL,
+  submitted_by_user_id INT,
+  submitted_by_username TEXT,
+  parent_set_ids INT[],
+  parent_set_names TEXT[],
+  pick INT,
+  draw INT,
+  score REAL
+)
+AS $$
+  SELECT * FROM bb.get_cards_by_ids(ARRAY(
+    SELECT c.id
+    FROM bb.card AS c
+    WHERE
+      c.id = ANY(card_ids)
+      AND (
+        include_unapproved
+        OR c.moderation_status = 'approved'
+        OR c.submitted_by_user_id = in_user_id
+      )
+  ));
+$$
+LANGUAGE SQL;
+
+-- Time decks rank cards by their score
+CREATE OR REPLACE PROCEDURE bb.generate_daily_set(top_n_cards INT)
+AS $$
+BEGIN
+  with
+    inserted_set AS (
+      INSERT INTO bb.parent_set (name)
+        VALUES ('Daily Set: ' || TO_CHAR(NOW()::DATE, 'yyyy-mm-dd'))
+      RETURNING id)
+    , top_cards AS (
+      SELECT c.id
+      FROM bb.card AS c
+      WHERE DATE_PART('DAY', created_date - NOW()::timestamp) <= 1
+        AND c.submitted_by_user_id IS NOT NULL
+        AND c.moderation_status = 'approved'
+        AND c.is_active = true
+        AND c.total_votes > 0
+      ORDER BY c.score DESC, c.id
+      LIMIT top_n_cards
+    )
+  INSERT INTO bb.parent_set_card (parent_set_id, card_id)
+    SELECT
+      s.id,
+      c.id
+    FROM inserted_set AS s
+      CROSS JOIN top_cards as c;
+END;
+$$
+LANGUAGE 'plpgsql';
+
+CREATE OR REPLACE PROCEDURE bb.generate_weekly_set(top_n_cards INT)
+AS $$
+BEGIN
+  with
+    inserted_set AS (
+      INSERT INTO bb.parent_set (name)
+        VALUES ('Weekly Set: ' || TO_CHAR(NOW()::DATE, 'yyyy-mm-dd'))
+      RETURNING id)
+    , top_cards AS (
+      SELECT c.id
+      FROM bb.card AS c
+      WHERE DATE_PART('day', created_date - NOW()::timestamp) <= 7
+        AND c.submitted_by_user_id IS NOT NULL
+        AND c.moderation_status = 'approved'
+        AND c.is_active = true
+        AND c.total_votes > 0
+      ORDER BY c.score DESC, c.id
+      LIMIT top_n_cards
+    )
+  INSERT INTO bb.parent_set_card (parent_set_id, card_id)
+    SELECT
+      s.id,
+      c.id
+    FROM inserted_set AS s
+      CROSS JOIN top_cards as c;
+END;
+$$
+LANGUAGE 'plpgsql';
+
+CREATE OR REPLACE PROCEDURE bb.generate_monthly_set(top_n_cards INT)
+AS $$
+BEGIN
+  with
+    inserted_set AS (
+      INSERT INTO bb.parent_set (name)
+        VALUES ('Montly Set: ' || TO_CHAR(NOW()::DATE, 'yyyy-mm'))
+      RETURNING id)
+    , top_cards AS (
+      SELECT c.id
+      FROM bb.card AS c
+      WHERE DATE_PART('month', created_date - NOW()::timestamp) <= 1
+        AND c.submitted_by_user_id IS NOT NULL
+        AND c.moderation_status = 'approved'
+        AND c.is_active = true
+        AND c.total_votes > 0
+      ORDER BY c.score DESC, c.id
+      LIMIT top_n_cards
+    )
+  INSERT INTO bb.parent_set_card (parent_set_id, card_id)
+    SELECT
+      s.id,
+      c.id
+    FROM inserted_set AS s
+      CROSS JOIN top_cards as c;
+END;
+$$
+LANGUAGE 'plpgsql';
+
+CREATE OR REPLACE PROCEDURE bb.generate_yearly_set(top_n_cards INT)
+AS $$
+BEGIN
+  with
+    inserted_set AS (
+      INSERT INTO bb.parent_set (name)
+        VALUES ('Yearly Set: ' || TO_CHAR(NOW()::DATE, 'yyyy'))
+      RETURNING id)
+    , top_cards AS (
+      SELECT c.id
+      FROM bb.card AS c
+      WHERE DATE_PART('year', created_date - NOW()::timestamp) <= 1
+        AND c.submitted_by_user_id IS NOT NULL
+        AND c.moderation_status = 'approved'
+        AND c.is_active = true
+        AND c.total_votes > 0
+      ORDER BY c.score DESC, c.id
+      LIMIT top_n_cards
+    )
+  INSERT INTO bb.parent_set_card (parent_set_id, card_id)
+    SELECT
+      s.id,
+      c.id
+    FROM inserted_set AS s
+      CROSS JOIN top_cards as c;
+END;
+$$
+LANGUAGE 'plpgsql';
@@ -0,0 +1,142 @@
+-- Your SQL goes here
+DROP FUNCTION bb.get_cards(text,boolean,integer,integer,integer[],boolean,real,boolean,integer,text,double precision);
+
+CREATE OR REPLACE FUNCTION bb.get_cards(
+	search text,
+	filter_black boolean,
+	previous_cursor integer,
+	n_cards integer,
+	card_sets integer[],
+	get_random boolean,
+	random_seed real,
+  user_submitted boolean,
+  filter_pick integer,
+  sort_by text,
+  previous_sort_key double precision,
+  sort_descending boolean)
+    RETURNS TABLE(id integer, format_text text, is_black boolean, parent_set_id integer, parent_set_name text, total_votes integer, average_rating real, submitted_by_user_id integer, submitted_by_username text, parent_set_ids integer[], parent_set_names text[], pick integer, draw integer, score real, sort_key double precision)
+    LANGUAGE 'plpgsql'
+
+    COST 100
+    VOLATILE
+    ROWS 1000
+
+AS $BODY$
+BEGIN
+
+  IF get_random THEN
+
+    IF random_seed IS NOT NULL THEN
+      PERFORM setseed(random_seed);
+    END IF;
+
+  END IF;
+
+  -- The page is selected first, so that every set of a card is only
+  -- aggregated for the cards that are actually returned
+  RETURN QUERY SELECT
+      page.id,
+      page.format_text,
+      page.is_black,
+      page.parent_set_id,
+      page.parent_set_name,
+      page.total_votes,
+      page.average_rating,
+      u.id AS "submitted_by_user_id",
+      u.username AS "submitted_by_username",
+      COALESCE(sets.ids, '{}') AS "parent_set_ids",
+      COALESCE(sets.names, '{}') AS "parent_set_names",
+      page.pick,
+      page.draw,
+      page.score,
+      page.sort_value AS "sort_key"
+    FROM (
+      SELECT
+        m.*,
+        CASE
+          WHEN get_random THEN RANDOM()
+          WHEN sort_descending THEN -m.sort_value
+          ELSE m.sort_value
+        END AS "sort_key"
+      FROM (
+        -- A card is returned once, even when it belongs to several of the
+        -- matched sets. `parent_set_id` is the first of those sets.
+        SELECT DISTINCT ON (c.id)
+          c.id,
+          c.format_text,
+          c.is_black,
+          p.parent_set_id,
+          ps.name AS "parent_set_name",
+          c.total_votes,
+          c.average_rating,
+          c.submitted_by_user_id,
+          c.pick,
+          c.draw,
+          c.score,
+          k.sort_value
+        FROM bb.card AS c
+          -- Value the cards are sorted by. Ties are broken by the card ID.
+          -- Unrated cards are sorted below any rating.
+          CROSS JOIN LATERAL (
+            SELECT CASE sort_by
+              WHEN 'newest' THEN EXTRACT(EPOCH FROM c.created_date)::DOUBLE PRECISION
+              WHEN 'top_rated' THEN COALESCE(c.average_rating, -1)
+              WHEN 'most_voted' THEN c.total_votes
+              WHEN 'score' THEN c.score
+              WHEN 'relevance' THEN ts_rank(c.text_searchable_format_text, to_tsquery('english', search))
+              ELSE c.id
+            END::DOUBLE PRECISION AS sort_value
+          ) AS k
+          -- User-submitted cards are returned even when they have not been
+          -- added to a set
+          -- Decks are only joined when they are searched for
+          LEFT JOIN (
+            bb.parent_set_card AS p
+              INNER JOIN bb.parent_set AS ps ON ps.id = p.parent_set_id AND ps.is_active = true
+          ) ON p.card_id = c.id AND p.is_active = true
+            AND (ps.owner_user_id IS NULL OR ps.id = ANY(card_sets))
+        WHERE
+          (search IS NULL OR to_tsquery('english', search) @@ text_searchable_format_text)
+          AND (filter_black IS NULL OR c.is_black = filter_black)
+          AND (filter_pick IS NULL OR c.pick = filter_pick)
+          AND (card_sets IS NULL OR p.parent_set_id = ANY(card_sets))
+          AND c.is_active = true
+          AND (p.card_id IS NOT NULL OR c.submitted_by_user_id IS NOT NULL)
+          AND c.moderation_status = 'approved'
+          AND (
+            -- NULL == CardSource.All
+            user_submitted IS NULL OR
+            -- TRUE == CardSource.User
+            (user_submitted AND c.submitted_by_user_id IS NOT NULL) OR
+            -- FALSE == CardSource.Official
+            (NOT user_submitted AND c.submitted_by_user_id IS NULL)
+          )
+          -- Randomized results are paginated by offset, others by the sort key
+          -- and ID of the last card
+          -- (`previous_sort_key` is NULL when sorting by ID)
+          AND (
+            get_random OR previous_cursor IS NULL
+            OR (sort_descending AND k.sort_value < COALESCE(previous_sort_key, previous_cursor))
+            OR (NOT sort_descending AND k.sort_value > COALESCE(previous_sort_key, previous_cursor))
+            OR (k.sort_value = COALESCE(previous_sort_key, previous_cursor) AND c.id > previous_cursor)
+          )
+        ORDER BY c.id, p.parent_set_id
+      ) AS m
+      ORDER BY sort_key, m.id
+      LIMIT n_cards OFFSET CASE WHEN get_random THEN previous_cursor END
+    ) AS page
+      LEFT JOIN bb."user" AS u ON u.id = page.submitted_by_user_id
+      LEFT JOIN LATERAL (
+        SELECT
+          array_agg(s.id ORDER BY s.id) AS ids,
+          array_agg(s.name ORDER BY s.id) AS names
+        FROM bb.parent_set_card AS sc
+          INNER JOIN bb.parent_set AS s ON s.id = sc.parent_set_id
+        WHERE
+          sc.card_id = page.id AND sc.is_active = true AND s.is_active = true
+          AND s.owner_user_id IS NULL
+      ) AS sets ON true
+    ORDER BY page.sort_key, page.id;
+
+END;
+$BODY$;
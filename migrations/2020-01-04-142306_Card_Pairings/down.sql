@@ -0,0 +1,4 @@
+-- This file should undo anything in `up.sql`
+DROP FUNCTION bb.get_card_pairings;
+DROP INDEX bb.IX_user_card_combination_rating_white_card;
+DROP INDEX bb.IX_user_card_combination_rating_black_card;
@@ -0,0 +1,44 @@
+-- Your SQL goes here
+CREATE INDEX IX_user_card_combination_rating_black_card
+  ON bb.user_card_combination_rating (black_card_id, ordinal);
+CREATE INDEX IX_user_card_combination_rating_white_card
+  ON bb.user_card_combination_rating (white_card_id);
+
+-- Combination ratings of a black card (or of a white card) grouped by the card
+-- it was paired with and the blank it was played on. Exactly one of the card IDs
+-- is expected, and in_ordinal optionally restricts the results to one blank.
+-- Pairings are ranked like cards, by the Bayesian average of their ratings.
+CREATE OR REPLACE FUNCTION bb.get_card_pairings(
+  in_black_card_id INT,
+  in_white_card_id INT,
+  in_ordinal INT,
+  n_results INT
+) RETURNS TABLE (
+  black_card_id INT,
+  white_card_id INT,
+  ordinal INT,
+  total_votes INT,
+  average_rating REAL
+)
+AS $$
+  SELECT
+    r.black_card_id,
+    r.white_card_id,
+    r.ordinal,
+    COUNT(r.rating)::INT AS "total_votes",
+    AVG(r.rating)::REAL AS "average_rating"
+  FROM bb.user_card_combination_rating AS r
+    INNER JOIN bb.card AS b ON b.id = r.black_card_id
+    INNER JOIN bb.card AS w ON w.id = r.white_card_id
+  WHERE
+    (in_black_card_id IS NULL OR r.black_card_id = in_black_card_id)
+    AND (in_white_card_id IS NULL OR r.white_card_id = in_white_card_id)
+    AND (in_ordinal IS NULL OR r.ordinal = in_ordinal)
+    AND r.is_active = true
+    AND b.is_active = true
+    AND w.is_active = true
+  GROUP BY r.black_card_id, r.white_card_id, r.ordinal
+  ORDER BY bb.card_score(COUNT(r.rating)::INT, AVG(r.rating)::REAL) DESC, r.black_card_id, r.white_card_id, r.ordinal
+  LIMIT n_results;
+$$
+LANGUAGE SQL;
@@ -0,0 +1,7 @@
+-- This file should undo anything in `up.sql`
+DROP FUNCTION bb.get_rating_distribution;
+DROP FUNCTION bb.get_top_raters;
+DROP FUNCTION bb.get_rating_volume;
+DROP INDEX bb.IX_user_card_rating_created_date;
+DROP FUNCTION bb.get_active_set_count;
+DROP FUNCTION bb.get_card_counts;
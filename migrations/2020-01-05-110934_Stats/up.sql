@@ -0,0 +1,107 @@
+-- Your SQL goes here
+-- Active, approved cards by color and by source (submitted by a user, or official)
+CREATE OR REPLACE FUNCTION bb.get_card_counts()
+RETURNS TABLE (
+  is_black BOOLEAN,
+  user_submitted BOOLEAN,
+  n_cards INT
+)
+AS $$
+  SELECT
+    c.is_black,
+    c.submitted_by_user_id IS NOT NULL AS "user_submitted",
+    COUNT(*)::INT AS "n_cards"
+  FROM bb.card AS c
+  WHERE c.is_active = true AND c.moderation_status = 'approved'
+  GROUP BY 1, 2
+  ORDER BY 1, 2;
+$$
+LANGUAGE SQL;
+
+-- Active sets, not counting the decks of users
+CREATE OR REPLACE FUNCTION bb.get_active_set_count() RETURNS INT
+AS $$
+  SELECT COUNT(*)::INT
+  FROM bb.parent_set AS s
+  WHERE s.is_active = true AND s.owner_user_id IS NULL;
+$$
+LANGUAGE SQL;
+
+CREATE INDEX IX_user_card_rating_created_date ON bb.user_card_rating (created_date);
+
+-- Active ratings by the day, week, or month they were first given, for the
+-- last n_buckets buckets (oldest first). Buckets without ratings are returned
+-- with no ratings, so the series has no gaps.
+CREATE OR REPLACE FUNCTION bb.get_rating_volume(in_interval TEXT, n_buckets INT)
+RETURNS TABLE (
+  bucket_start TEXT,
+  n_ratings INT,
+  n_raters INT
+)
+AS $$
+  WITH buckets AS (
+    SELECT generate_series(
+      date_trunc(in_interval, NOW()::TIMESTAMP) - (n_buckets - 1) * ('1 ' || in_interval)::INTERVAL,
+      date_trunc(in_interval, NOW()::TIMESTAMP),
+      ('1 ' || in_interval)::INTERVAL
+    ) AS bucket_start
+  )
+  SELECT
+    TO_CHAR(timezone('UTC', b.bucket_start::TIMESTAMPTZ), 'YYYY-MM-DD"T"HH24:MI:SS"Z"') AS "bucket_start",
+    COUNT(r.rating)::INT AS "n_ratings",
+    COUNT(DISTINCT r.user_id)::INT AS "n_raters"
+  FROM buckets AS b
+    LEFT JOIN bb.user_card_rating AS r
+      ON date_trunc(in_interval, r.created_date) = b.bucket_start
+      AND r.created_date >= (SELECT MIN(bucket_start) FROM buckets)
+      AND r.is_active = true
+  GROUP BY b.bucket_start
+  ORDER BY b.bucket_start;
+$$
+LANGUAGE SQL;
+
+-- Users with the most active card ratings
+CREATE OR REPLACE FUNCTION bb.get_top_raters(n_results INT)
+RETURNS TABLE (
+  user_id INT,
+  username TEXT,
+  n_ratings INT,
+  last_rated_date TEXT
+)
+AS $$
+  SELECT
+    u.id,
+    u.username,
+    COUNT(r.rating)::INT AS "n_ratings",
+    TO_CHAR(timezone('UTC', MAX(r.last_modified)::TIMESTAMPTZ), 'YYYY-MM-DD"T"HH24:MI:SS"Z"') AS "last_rated_date"
+  FROM bb.user_card_rating AS r
+    INNER JOIN bb."user" AS u ON u.id = r.user_id
+  WHERE r.is_active = true
+  GROUP BY u.id, u.username
+  ORDER BY COUNT(r.rating) DESC, u.id
+  LIMIT n_results;
+$$
+LANGUAGE SQL;
+
+-- Histogram of the average rating of active, approved cards with votes, in n_buckets
+-- buckets of equal width. Every bucket is returned, numbered from 0; a perfect
+-- 1.0 falls in the last bucket.
+CREATE OR REPLACE FUNCTION bb.get_rating_distribution(n_buckets INT)
+RETURNS TABLE (
+  bucket INT,
+  n_cards INT
+)
+AS $$
+  SELECT
+    b.bucket,
+    COUNT(c.id)::INT AS "n_cards"
+  FROM generate_series(0, n_buckets - 1) AS b(bucket)
+    LEFT JOIN bb.card AS c
+      ON LEAST(width_bucket(c.average_rating, 0, 1, n_buckets), n_buckets) - 1 = b.bucket
+      AND c.is_active = true
+      AND c.moderation_status = 'approved'
+      AND c.total_votes > 0
+  GROUP BY b.bucket
+  ORDER BY b.bucket;
+$$
+LANGUAGE SQL;
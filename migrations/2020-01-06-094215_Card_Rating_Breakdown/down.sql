@@ -0,0 +1,15 @@
+-- This file should undo anything in `up.sql`
+DROP FUNCTION bb.get_card_rating_breakdowns;
+DROP INDEX bb.IX_user_card_rating_card;
+
+CREATE OR REPLACE VIEW bb.v_card_ratings AS
+  SELECT
+    r.card_id
+    , SUM(CASE WHEN rating < 0.5 THEN 1 ELSE 0 END) AS "downvotes"
+    , SUM(CASE WHEN rating > 0.5 THEN 1 ELSE 0 END) AS "upvotes"
+    , AVG(CASE WHEN rating < 0.5 THEN rating ELSE NULL END) AS "low_votes"
+    , AVG(CASE WHEN rating > 0.5 THEN rating ELSE NULL END) AS "high_votes"
+    , AVG(rating) AS "average_rating"
+    , COUNT(*) AS "total_votes"
+    FROM bb.user_card_rating as r
+   GROUP BY r.card_id;
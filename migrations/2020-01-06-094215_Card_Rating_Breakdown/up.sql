@@ -0,0 +1,50 @@
+-- Your SQL goes here
+-- Withdrawn ratings no longer count, like in the aggregates of bb.card
+CREATE OR REPLACE VIEW bb.v_card_ratings AS
+  SELECT
+    r.card_id
+    , SUM(CASE WHEN rating < 0.5 THEN 1 ELSE 0 END) AS "downvotes"
+    , SUM(CASE WHEN rating > 0.5 THEN 1 ELSE 0 END) AS "upvotes"
+    , AVG(CASE WHEN rating < 0.5 THEN rating ELSE NULL END) AS "low_votes"
+    , AVG(CASE WHEN rating > 0.5 THEN rating ELSE NULL END) AS "high_votes"
+    , AVG(rating) AS "average_rating"
+    , COUNT(*) AS "total_votes"
+    FROM bb.user_card_rating as r
+   WHERE r.is_active = true
+   GROUP BY r.card_id;
+
+CREATE INDEX IX_user_card_rating_card ON bb.user_card_rating (card_id);
+
+-- Rating breakdowns of several cards at once, from bb.v_card_ratings, with a
+-- histogram of the ratings in 10 buckets of 0.1 (a perfect 1.0 falls in the
+-- last bucket). Cards without ratings are not returned.
+CREATE OR REPLACE FUNCTION bb.get_card_rating_breakdowns(in_card_ids INT[])
+RETURNS TABLE (
+  card_id INT,
+  upvotes INT,
+  downvotes INT,
+  low_votes REAL,
+  high_votes REAL,
+  histogram INT[]
+)
+AS $$
+  SELECT
+    v.card_id,
+    v.upvotes::INT,
+    v.downvotes::INT,
+    v.low_votes::REAL,
+    v.high_votes::REAL,
+    ARRAY(
+      SELECT COUNT(r.rating)::INT
+      FROM generate_series(0, 9) AS b(bucket)
+        LEFT JOIN bb.user_card_rating AS r
+          ON r.card_id = v.card_id
+          AND r.is_active = true
+          AND LEAST(width_bucket(r.rating, 0, 1, 10), 10) - 1 = b.bucket
+      GROUP BY b.bucket
+      ORDER BY b.bucket
+    ) AS "histogram"
+  FROM bb.v_card_ratings AS v
+  WHERE v.card_id = ANY(in_card_ids);
+$$
+LANGUAGE SQL;
@@ -0,0 +1,121 @@
+-- This file should undo anything in `up.sql`
+DROP FUNCTION bb.run_time_deck_job;
+DROP TABLE bb.job_run;
+
+CREATE OR REPLACE PROCEDURE bb.generate_daily_set(top_n_cards INT)
+AS $$
+BEGIN
+  with
+    inserted_set AS (
+      INSERT INTO bb.parent_set (name)
+        VALUES ('Daily Set: ' || TO_CHAR(NOW()::DATE, 'yyyy-mm-dd'))
+      RETURNING id)
+    , top_cards AS (
+      SELECT c.id
+      FROM bb.card AS c
+      WHERE DATE_PART('DAY', created_date - NOW()::timestamp) <= 1
+        AND c.submitted_by_user_id IS NOT NULL
+        AND c.moderation_status = 'approved'
+        AND c.is_active = true
+        AND c.total_votes > 0
+      ORDER BY c.score DESC, c.id
+      LIMIT top_n_cards
+    )
+  INSERT INTO bb.parent_set_card (parent_set_id, card_id)
+    SELECT
+      s.id,
+      c.id
+    FROM inserted_set AS s
+      CROSS JOIN top_cards as c;
+END;
+$$
+LANGUAGE 'plpgsql';
+
+CREATE OR REPLACE PROCEDURE bb.generate_weekly_set(top_n_cards INT)
+AS $$
+BEGIN
+  with
+    inserted_set AS (
+      INSERT INTO bb.parent_set (name)
+        VALUES ('Weekly Set: ' || TO_CHAR(NOW()::DATE, 'yyyy-mm-dd'))
+      RETURNING id)
+    , top_cards AS (
+      SELECT c.id
+      FROM bb.card AS c
+      WHERE DATE_PART('day', created_date - NOW()::timestamp) <= 7
+        AND c.submitted_by_user_id IS NOT NULL
+        AND c.moderation_status = 'approved'
+        AND c.is_active = true
+        AND c.total_votes > 0
+      ORDER BY c.score DESC, c.id
+      LIMIT top_n_cards
+    )
+  INSERT INTO bb.parent_set_card (parent_set_id, card_id)
+    SELECT
+      s.id,
+      c.id
+    FROM inserted_set AS s
+      CROSS JOIN top_cards as c;
+END;
+$$
+LANGUAGE 'plpgsql';
+
+CREATE OR REPLACE PROCEDURE bb.generate_monthly_set(top_n_cards INT)
+AS $$
+BEGIN
+  with
+    inserted_set AS (
+      INSERT INTO bb.parent_set (name)
+        VALUES ('Montly Set: ' || TO_CHAR(NOW()::DATE, 'yyyy-mm'))
+      RETURNING id)
+    , top_cards AS (
+      SELECT c.id
+      FROM bb.card AS c
+      WHERE DATE_PART('month', created_date - NOW()::timestamp) <= 1
+        AND c.submitted_by_user_id IS NOT NULL
+        AND c.moderation_status = 'approved'
+        AND c.is_active = true
+        AND c.total_votes > 0
+      ORDER BY c.score DESC, c.id
+      LIMIT top_n_cards
+    )
+  INSERT INTO bb.parent_set_card (parent_set_id, card_id)
+    SELECT
+      s.id,
+      c.id
+    FROM inserted_set AS s
+      CROSS JOIN top_cards as c;
+END;
+$$
+LANGUAGE 'plpgsql';
+
+CREATE OR REPLACE PROCEDURE bb.generate_yearly_set(top_n_cards INT)
+AS $$
+BEGIN
+  with
+    inserted_set AS (
+      INSERT INTO bb.parent_set (name)
+        VALUES ('Yearly Set: ' || TO_CHAR(NOW()::DATE, 'yyyy'))
+      RETURNING id)
+    , top_cards AS (
+      SELECT c.id
+      FROM bb.card AS c
+      WHERE DATE_PART('year', created_date - NOW()::timestamp) <= 1
+        AND c.submitted_by_user_id IS NOT NULL
+        AND c.moderation_status = 'approved'
+        AND c.is_active = true
+        AND c.total_votes > 0
+      ORDER BY c.score DESC, c.id
+      LIMIT top_n_cards
+    )
+  INSERT INTO bb.parent_set_card (parent_set_id, card_id)
+    SELECT
+      s.id,
+      c.id
+    FROM inserted_set AS s
+      CROSS JOIN top_cards as c;
+END;
+$$
+LANGUAGE 'plpgsql';
+
+DROP PROCEDURE bb.generate_period_set;
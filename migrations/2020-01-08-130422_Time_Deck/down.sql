@@ -0,0 +1,116 @@
+-- This file should undo anything in `up.sql`
+DROP FUNCTION bb.get_time_deck;
+DROP FUNCTION bb.get_time_decks;
+
+-- Generates the time-deck of the period (day, week, month, or year) starting at
+-- period_start: the top scoring approved cards submitted during the period.
+-- Time-decks that already exist are left alone, so generating one twice does
+-- not fail (or create a second set).
+CREATE OR REPLACE PROCEDURE bb.generate_period_set(in_period TEXT, period_start TIMESTAMP, top_n_cards INT)
+AS $$
+BEGIN
+  with
+    inserted_set AS (
+      INSERT INTO bb.parent_set (name)
+        VALUES (CASE in_period
+          WHEN 'day' THEN 'Daily Set: ' || TO_CHAR(period_start, 'yyyy-mm-dd')
+          WHEN 'week' THEN 'Weekly Set: ' || TO_CHAR(period_start, 'yyyy-mm-dd')
+          WHEN 'month' THEN 'Montly Set: ' || TO_CHAR(period_start, 'yyyy-mm')
+          WHEN 'year' THEN 'Yearly Set: ' || TO_CHAR(period_start, 'yyyy')
+        END)
+        ON CONFLICT (name) WHERE owner_user_id IS NULL DO NOTHING
+      RETURNING id)
+    , top_cards AS (
+      SELECT c.id
+      FROM bb.card AS c
+      WHERE c.created_date >= period_start
+        AND c.created_date < period_start + ('1 ' || in_period)::INTERVAL
+        AND c.submitted_by_user_id IS NOT NULL
+        AND c.moderation_status = 'approved'
+        AND c.is_active = true
+        AND c.total_votes > 0
+      ORDER BY c.score DESC, c.id
+      LIMIT top_n_cards
+    )
+  INSERT INTO bb.parent_set_card (parent_set_id, card_id)
+    SELECT
+      s.id,
+      c.id
+    FROM inserted_set AS s
+      CROSS JOIN top_cards as c;
+END;
+$$
+LANGUAGE 'plpgsql';
+
+-- The procedures generate the time-deck of the period that just ended, with
+-- periods starting at midnight (UTC)
+CREATE OR REPLACE PROCEDURE bb.generate_daily_set(top_n_cards INT)
+AS $$
+BEGIN
+  CALL bb.generate_period_set('day', date_trunc('day', NOW()::TIMESTAMP) - INTERVAL '1 day', top_n_cards);
+END;
+$$
+LANGUAGE 'plpgsql';
+
+CREATE OR REPLACE PROCEDURE bb.generate_weekly_set(top_n_cards INT)
+AS $$
+BEGIN
+  CALL bb.generate_period_set('week', date_trunc('week', NOW()::TIMESTAMP) - INTERVAL '1 week', top_n_cards);
+END;
+$$
+LANGUAGE 'plpgsql';
+
+CREATE OR REPLACE PROCEDURE bb.generate_monthly_set(top_n_cards INT)
+AS $$
+BEGIN
+  CALL bb.generate_period_set('month', date_trunc('month', NOW()::TIMESTAMP) - INTERVAL '1 month', top_n_cards);
+END;
+$$
+LANGUAGE 'plpgsql';
+
+CREATE OR REPLACE PROCEDURE bb.generate_yearly_set(top_n_cards INT)
+AS $$
+BEGIN
+  CALL bb.generate_period_set('year', date_trunc('year', NOW()::TIMESTAMP) - INTERVAL '1 year', top_n_cards);
+END;
+$$
+LANGUAGE 'plpgsql';
+
+CREATE OR REPLACE FUNCTION bb.run_time_deck_job(in_period TEXT, offset_hours INT, top_n_cards INT)
+RETURNS BOOLEAN
+AS $$
+DECLARE
+  job_name TEXT := in_period || '_time_deck';
+  current_period_start DATE;
+BEGIN
+  IF NOT pg_try_advisory_xact_lock(hashtext('bb.run_time_deck_job'), hashtext(in_period)) THEN
+    RETURN false;
+  END IF;
+
+  current_period_start := date_trunc(in_period, NOW()::TIMESTAMP - make_interval(hours => offset_hours))::DATE;
+
+  INSERT INTO bb.job_run (job_name, period_start)
+    VALUES (job_name, current_period_start)
+    ON CONFLICT ON CONSTRAINT PK_job_run DO NOTHING;
+  IF NOT FOUND THEN
+    RETURN false;
+  END IF;
+
+  CALL bb.generate_period_set(
+    in_period,
+    current_period_start - ('1 ' || in_period)::INTERVAL + make_interval(hours => offset_hours),
+    top_n_cards
+  );
+  RETURN true;
+END;
+$$
+LANGUAGE 'plpgsql';
+
+DROP FUNCTION bb.generate_time_deck;
+DROP FUNCTION bb.time_deck_name;
+
+UPDATE bb.parent_set
+  SET name = 'Montly Set: ' || SUBSTRING(name FROM 14), last_modified = NOW()
+  WHERE id IN (SELECT parent_set_id FROM bb.time_deck WHERE period = 'month');
+
+DROP TABLE bb.time_deck;
@@ -0,0 +1,238 @@
+-- Your SQL goes here
+-- Time-decks were told apart from other sets by their name only. Each one now
+-- records the period (day, week, month, or year) it was generated for, from
+-- its first to its last day.
+CREATE TABLE IF NOT EXISTS bb.time_deck (
+  parent_set_id INT NOT NULL CONSTRAINT FK_time_deck_parent_set REFERENCES bb.parent_set(id),
+  period TEXT NOT NULL,
+  start_date DATE NOT NULL,
+  end_date DATE NOT NULL,
+  CONSTRAINT PK_time_deck PRIMARY KEY (parent_set_id),
+  CONSTRAINT UX_time_deck_period_start UNIQUE (period, start_date),
+  CONSTRAINT CK_time_deck_period CHECK (period IN ('day', 'week', 'month', 'year'))
+);
+
+CREATE INDEX IX_time_deck_start ON bb.time_deck (start_date DESC, parent_set_id DESC);
+
+UPDATE bb.parent_set
+  SET name = 'Monthly Set: ' || SUBSTRING(name FROM 13), last_modified = NOW()
+  WHERE name LIKE 'Montly Set: %' AND owner_user_id IS NULL;
+
+-- Existing time-decks, by their name. Weekly sets were named after the day they
+-- were generated, so the oldest set of a week is kept as its time-deck.
+INSERT INTO bb.time_deck (parent_set_id, period, start_date, end_date)
+  SELECT DISTINCT ON (t.period, t.start_date)
+    t.id,
+    t.period,
+    t.start_date,
+    (t.start_date + ('1 ' || t.period)::INTERVAL - INTERVAL '1 day')::DATE
+  FROM (
+    SELECT
+      s.id,
+      p.period,
+      date_trunc(p.period, TO_DATE(SUBSTRING(s.name FROM LENGTH(p.prefix) + 1), p.format))::DATE AS "start_date"
+    FROM bb.parent_set AS s
+      INNER JOIN (VALUES
+        ('day', 'Daily Set: ', 'YYYY-MM-DD', '^\d{4}-\d{2}-\d{2}$'),
+        ('week', 'Weekly Set: ', 'YYYY-MM-DD', '^\d{4}-\d{2}-\d{2}$'),
+        ('month', 'Monthly Set: ', 'YYYY-MM', '^\d{4}-\d{2}$'),
+        ('year', 'Yearly Set: ', 'YYYY', '^\d{4}$')
+      ) AS p(period, prefix, format, pattern)
+        ON s.name LIKE p.prefix || '%'
+        AND SUBSTRING(s.name FROM LENGTH(p.prefix) + 1) ~ p.pattern
+    WHERE s.owner_user_id IS NULL
+  ) AS t
+  ORDER BY t.period, t.start_date, t.id;
+
+-- Name of the time-deck of a period, e.g. 'Weekly Set: 2020-01-06'
+CREATE OR REPLACE FUNCTION bb.time_deck_name(in_period TEXT, in_start_date DATE) RETURNS TEXT
+AS $$
+  SELECT CASE in_period
+    WHEN 'day' THEN 'Daily Set: ' || TO_CHAR(in_start_date, 'yyyy-mm-dd')
+    WHEN 'week' THEN 'Weekly Set: ' || TO_CHAR(in_start_date, 'yyyy-mm-dd')
+    WHEN 'month' THEN 'Monthly Set: ' || TO_CHAR(in_start_date, 'yyyy-mm')
+    WHEN 'year' THEN 'Yearly Set: ' || TO_CHAR(in_start_date, 'yyyy')
+  END;
+$$
+LANGUAGE SQL
+IMMUTABLE;
+
+-- Generates the time-deck of the period containing in_date: the top scoring
+-- approved cards submitted during the period. Periods start offset_hours after
+-- midnight (UTC), e.g. with an offset of 6 hours the time-deck of a day is made
+-- of the cards submitted from 06:00 that day until 06:00 the next day. Returns
+-- the ID of the new set, or NULL when the period already has a time-deck.
+CREATE OR REPLACE FUNCTION bb.generate_time_deck(
+  in_period TEXT,
+  in_date DATE,
+  offset_hours INT,
+  top_n_cards INT
+) RETURNS INT
+AS $$
+DECLARE
+  period_start DATE := date_trunc(in_period, in_date)::DATE;
+  period_end DATE := (date_trunc(in_period, in_date) + ('1 ' || in_period)::INTERVAL - INTERVAL '1 day')::DATE;
+  window_start TIMESTAMP := period_start + make_interval(hours => offset_hours);
+  window_end TIMESTAMP := period_end + 1 + make_interval(hours => offset_hours);
+  new_set_id INT;
+BEGIN
+  IF EXISTS (SELECT 1 FROM bb.time_deck AS t WHERE t.period = in_period AND t.start_date = period_start) THEN
+    RETURN NULL;
+  END IF;
+
+  INSERT INTO bb.parent_set (name)
+    VALUES (bb.time_deck_name(in_period, period_start))
+    ON CONFLICT (name) WHERE owner_user_id IS NULL DO NOTHING
+    RETURNING id INTO new_set_id;
+  IF new_set_id IS NULL THEN
+    RETURN NULL;
+  END IF;
+
+  INSERT INTO bb.time_deck (parent_set_id, period, start_date, end_date)
+    VALUES (new_set_id, in_period, period_start, period_end);
+
+  INSERT INTO bb.parent_set_card (parent_set_id, card_id)
+    SELECT new_set_id, c.id
+    FROM bb.card AS c
+    WHERE c.created_date >= window_start
+      AND c.created_date < window_end
+      AND c.submitted_by_user_id IS NOT NULL
+      AND c.moderation_status = 'approved'
+      AND c.is_active = true
+      AND c.total_votes > 0
+    ORDER BY c.score DESC, c.id
+    LIMIT top_n_cards;
+
+  RETURN new_set_id;
+END;
+$$
+LANGUAGE 'plpgsql';
+
+-- The procedures generate the time-deck of the period that just ended, with
+-- periods starting at midnight (UTC)
+CREATE OR REPLACE PROCEDURE bb.generate_daily_set(top_n_cards INT)
+AS $$
+BEGIN
+  PERFORM bb.generate_time_deck('day', (NOW() - INTERVAL '1 day')::DATE, 0, top_n_cards);
+END;
+$$
+LANGUAGE 'plpgsql';
+
+CREATE OR REPLACE PROCEDURE bb.generate_weekly_set(top_n_cards INT)
+AS $$
+BEGIN
+  PERFORM bb.generate_time_deck('week', (NOW() - INTERVAL '1 week')::DATE, 0, top_n_cards);
+END;
+$$
+LANGUAGE 'plpgsql';
+
+CREATE OR REPLACE PROCEDURE bb.generate_monthly_set(top_n_cards INT)
+AS $$
+BEGIN
+  PERFORM bb.generate_time_deck('month', (NOW() - INTERVAL '1 month')::DATE, 0, top_n_cards);
+END;
+$$
+LANGUAGE 'plpgsql';
+
+CREATE OR REPLACE PROCEDURE bb.generate_yearly_set(top_n_cards INT)
+AS $$
+BEGIN
+  PERFORM bb.generate_time_deck('year', (NOW() - INTERVAL '1 year')::DATE, 0, top_n_cards);
+END;
+$$
+LANGUAGE 'plpgsql';
+
+-- Once a period has ended (offset_hours after midnight, UTC), generates its
+-- time-deck, unless it was already generated. The advisory lock is held until
+-- the end of the transaction, so only one replica runs the job at a time; the
+-- others return false right away.
+CREATE OR REPLACE FUNCTION bb.run_time_deck_job(in_period TEXT, offset_hours INT, top_n_cards INT)
+RETURNS BOOLEAN
+AS $$
+DECLARE
+  job_name TEXT := in_period || '_time_deck';
+  current_period_start DATE;
+BEGIN
+  IF NOT pg_try_advisory_xact_lock(hashtext('bb.run_time_deck_job'), hashtext(in_period)) THEN
+    RETURN false;
+  END IF;
+
+  current_period_start := date_trunc(in_period, NOW()::TIMESTAMP - make_interval(hours => offset_hours))::DATE;
+
+  INSERT INTO bb.job_run (job_name, period_start)
+    VALUES (job_name, current_period_start)
+    ON CONFLICT ON CONSTRAINT PK_job_run DO NOTHING;
+  IF NOT FOUND THEN
+    RETURN false;
+  END IF;
+
+  RETURN bb.generate_time_deck(in_period, current_period_start - 1, offset_hours, top_n_cards) IS NOT NULL;
+END;
+$$
+LANGUAGE 'plpgsql';
+
+DROP PROCEDURE bb.generate_period_set;
+
+-- Time-decks of a period (or of every period), most recent first. The cursor is
+-- made up of the start day (days since 1970-01-01) and set ID of the last result.
+CREATE OR REPLACE FUNCTION bb.get_time_decks(
+  in_period TEXT,
+  previous_start_day INT,
+  previous_id INT,
+  n_results INT
+) RETURNS TABLE (
+  id INT,
+  name TEXT,
+  period TEXT,
+  start_date TEXT,
+  end_date TEXT,
+  start_day INT
+)
+AS $$
+  SELECT
+    s.id,
+    s.name,
+    t.period,
+    TO_CHAR(t.start_date, 'YYYY-MM-DD'),
+    TO_CHAR(t.end_date, 'YYYY-MM-DD'),
+    t.start_date - DATE '1970-01-01' AS "start_day"
+  FROM bb.time_deck AS t
+    INNER JOIN bb.parent_set AS s ON s.id = t.parent_set_id
+  WHERE
+    s.is_active = true
+    AND (in_period IS NULL OR t.period = in_period)
+    AND (
+      previous_start_day IS NULL
+      OR (t.start_date - DATE '1970-01-01', t.parent_set_id) < (previous_start_day, previous_id)
+    )
+  ORDER BY t.start_date DESC, t.parent_set_id DESC
+  LIMIT n_results;
+$$
+LANGUAGE SQL;
+
+-- The time-deck of the period containing in_date
+CREATE OR REPLACE FUNCTION bb.get_time_deck(in_period TEXT, in_date DATE)
+RETURNS TABLE (
+  id INT,
+  name TEXT,
+  period TEXT,
+  start_date TEXT,
+  end_date TEXT,
+  start_day INT
+)
+AS $$
+  SELECT
+    s.id,
+    s.name,
+    t.period,
+    TO_CHAR(t.start_date, 'YYYY-MM-DD'),
+    TO_CHAR(t.end_date, 'YYYY-MM-DD'),
+    t.start_date - DATE '1970-01-01' AS "start_day"
+  FROM bb.time_deck AS t
+    INNER JOIN bb.parent_set AS s ON s.id = t.parent_set_id
+  WHERE
+    s.is_active = true
+    AND t.period = in_period
+    AND t.start_date = date_trunc(in_period, in_date)::DATE;
+$$
+LANGUAGE SQL;
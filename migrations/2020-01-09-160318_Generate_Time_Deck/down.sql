@@ -0,0 +1,54 @@
+-- This file should undo anything in `up.sql`
+DROP FUNCTION bb.generate_time_deck(TEXT, DATE, INT, INT, BOOLEAN);
+DROP FUNCTION bb.time_deck_period_ended;
+
+-- Generates the time-deck of the period containing in_date: the top scoring
+-- approved cards submitted during the period. Periods start offset_hours after
+-- midnight (UTC), e.g. with an offset of 6 hours the time-deck of a day is made
+-- of the cards submitted from 06:00 that day until 06:00 the next day. Returns
+-- the ID of the new set, or NULL when the period already has a time-deck.
+CREATE OR REPLACE FUNCTION bb.generate_time_deck(
+  in_period TEXT,
+  in_date DATE,
+  offset_hours INT,
+  top_n_cards INT
+) RETURNS INT
+AS $$
+DECLARE
+  period_start DATE := date_trunc(in_period, in_date)::DATE;
+  period_end DATE := (date_trunc(in_period, in_date) + ('1 ' || in_period)::INTERVAL - INTERVAL '1 day')::DATE;
+  window_start TIMESTAMP := period_start + make_interval(hours => offset_hours);
+  window_end TIMESTAMP := period_end + 1 + make_interval(hours => offset_hours);
+  new_set_id INT;
+BEGIN
+  IF EXISTS (SELECT 1 FROM bb.time_deck AS t WHERE t.period = in_period AND t.start_date = period_start) THEN
+    RETURN NULL;
+  END IF;
+
+  INSERT INTO bb.parent_set (name)
+    VALUES (bb.time_deck_name(in_period, period_start))
+    ON CONFLICT (name) WHERE owner_user_id IS NULL DO NOTHING
+    RETURNING id INTO new_set_id;
+  IF new_set_id IS NULL THEN
+    RETURN NULL;
+  END IF;
+
+  INSERT INTO bb.time_deck (parent_set_id, period, start_date, end_date)
+    VALUES (new_set_id, in_period, period_start, period_end);
+
+  INSERT INTO bb.parent_set_card (parent_set_id, card_id)
+    SELECT new_set_id, c.id
+    FROM bb.card AS c
+    WHERE c.created_date >= window_start
+      AND c.created_date < window_end
+      AND c.submitted_by_user_id IS NOT NULL
+      AND c.moderation_status = 'approved'
+      AND c.is_active = true
+      AND c.total_votes > 0
+    ORDER BY c.score DESC, c.id
+    LIMIT top_n_cards;
+
+  RETURN new_set_id;
+END;
+$$
+LANGUAGE 'plpgsql';
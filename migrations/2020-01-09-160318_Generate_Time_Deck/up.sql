@@ -0,0 +1,97 @@
+-- Your SQL goes here
+DROP FUNCTION bb.generate_time_deck(TEXT, DATE, INT, INT);
+
+-- Whether the period containing in_date, starting offset_hours after midnight
+-- (UTC), has ended.
+CREATE OR REPLACE FUNCTION bb.time_deck_period_ended(
+  in_period TEXT,
+  in_date DATE,
+  offset_hours INT
+) RETURNS BOOLEAN
+AS $$
+  SELECT date_trunc(in_period, in_date) + ('1 ' || in_period)::INTERVAL
+    + make_interval(hours => offset_hours) <= NOW()::TIMESTAMP;
+$$
+LANGUAGE SQL STABLE;
+
+-- Generates the time-deck of the period containing in_date: the top approved
+-- cards submitted during the period, so past periods can be backfilled. Periods
+-- start offset_hours after midnight (UTC), and must have ended. Ratings are not
+-- versioned (re-rating a card overwrites the rating, unrating it deactivates
+-- it), so cards are ranked by their current ratings: a backfilled time-deck
+-- can differ from the one generated when the period ended. Returns the ID of
+-- the set, or NULL when the period already has a time-deck (unless
+-- replace_existing, which replaces the cards of the existing time-deck).
+CREATE OR REPLACE FUNCTION bb.generate_time_deck(
+  in_period TEXT,
+  in_date DATE,
+  offset_hours INT,
+  top_n_cards INT,
+  replace_existing BOOLEAN DEFAULT false
+) RETURNS INT
+AS $$
+DECLARE
+  period_start DATE := date_trunc(in_period, in_date)::DATE;
+  period_end DATE := (date_trunc(in_period, in_date) + ('1 ' || in_period)::INTERVAL - INTERVAL '1 day')::DATE;
+  window_start TIMESTAMP := period_start + make_interval(hours => offset_hours);
+  window_end TIMESTAMP := period_end + 1 + make_interval(hours => offset_hours);
+  deck_set_id INT;
+BEGIN
+  IF NOT bb.time_deck_period_ended(in_period, in_date, offset_hours) THEN
+    RAISE EXCEPTION 'The % period of % has not ended', in_period, in_date;
+  END IF;
+
+  SELECT t.parent_set_id INTO deck_set_id
+    FROM bb.time_deck AS t
+    WHERE t.period = in_period AND t.start_date = period_start;
+
+  IF deck_set_id IS NOT NULL THEN
+    IF NOT replace_existing THEN
+      RETURN NULL;
+    END IF;
+
+    UPDATE ONLY bb.parent_set
+      SET is_active = true, last_modified = NOW()
+      WHERE id = deck_set_id AND is_active = false;
+    UPDATE ONLY bb.parent_set_card
+      SET is_active = false, last_modified = NOW()
+      WHERE parent_set_id = deck_set_id AND is_active = true;
+  ELSE
+    INSERT INTO bb.parent_set (name)
+      VALUES (bb.time_deck_name(in_period, period_start))
+      ON CONFLICT (name) WHERE owner_user_id IS NULL DO NOTHING
+      RETURNING id INTO deck_set_id;
+    IF deck_set_id IS NULL THEN
+      RETURN NULL;
+    END IF;
+
+    INSERT INTO bb.time_deck (parent_set_id, period, start_date, end_date)
+      VALUES (deck_set_id, in_period, period_start, period_end);
+  END IF;
+
+  INSERT INTO bb.parent_set_card (parent_set_id, card_id)
+    SELECT deck_set_id, c.id
+    FROM bb.card AS c
+      CROSS JOIN LATERAL (
+        SELECT
+          COUNT(r.rating)::INT AS "total_votes",
+          AVG(r.rating)::REAL AS "average_rating"
+        FROM bb.user_card_rating AS r
+        WHERE r.card_id = c.id
+          AND r.is_active = true
+      ) AS r
+    WHERE c.created_date >= window_start
+      AND c.created_date < window_end
+      AND c.submitted_by_user_id IS NOT NULL
+      AND c.moderation_status = 'approved'
+      AND c.is_active = true
+      AND r.total_votes > 0
+    ORDER BY bb.card_score(r.total_votes, r.average_rating) DESC, c.id
+    LIMIT top_n_cards
+  ON CONFLICT (parent_set_id, card_id)
+    DO UPDATE SET is_active = true, last_modified = NOW();
+
+  RETURN deck_set_id;
+END;
+$$
+LANGUAGE 'plpgsql';
@@ -0,0 +1,37 @@
+-- This file should undo anything in `up.sql`
+DROP FUNCTION bb.get_game_hand;
+DROP FUNCTION bb.get_game_players;
+DROP FUNCTION bb.get_game;
+DROP FUNCTION bb.next_game_round;
+DROP FUNCTION bb.pick_game_winner;
+DROP FUNCTION bb.play_game_cards;
+DROP FUNCTION bb.get_game_plays;
+DROP FUNCTION bb.deal_game_hands;
+DROP FUNCTION bb.join_game;
+DROP FUNCTION bb.create_game;
+DROP FUNCTION bb.start_game_round;
+DROP FUNCTION bb.draw_game_cards;
+
+DELETE FROM bb.draw_card;
+DROP INDEX bb.IX_draw_card_game_player;
+DROP INDEX bb.UX_draw_card_game_card;
+ALTER TABLE bb.draw_card DROP ordinal;
+ALTER TABLE bb.draw_card DROP played_round;
+ALTER TABLE bb.draw_card DROP round;
+ALTER TABLE bb.draw_card DROP game_player_id;
+ALTER TABLE bb.draw_card DROP game_id;
+ALTER TABLE bb.draw_card ALTER draw_date DROP DEFAULT;
+ALTER TABLE bb.draw_card ADD sessionkey TEXT;
+ALTER TABLE bb.draw_card RENAME last_modified TO lastmodified;
+ALTER TABLE bb.draw_card RENAME is_active TO isactive;
+ALTER TABLE bb.draw_card RENAME draw_date TO drawdate;
+ALTER TABLE bb.draw_card RENAME card_id TO cardid;
+
+DROP TABLE bb.game_round;
+DROP TABLE bb.game_player;
+DROP TABLE bb.game;
+
+UPDATE bb.api_key SET scopes = array_remove(scopes, 'play');
+ALTER TABLE bb.api_key DROP CONSTRAINT CK_api_key_scopes;
+ALTER TABLE bb.api_key ADD CONSTRAINT CK_api_key_scopes
+  CHECK (scopes <@ ARRAY['read', 'rate', 'submit', 'admin']);
@@ -0,0 +1,633 @@
+-- Your SQL goes here
+ALTER TABLE bb.api_key DROP CONSTRAINT CK_api_key_scopes;
+ALTER TABLE bb.api_key ADD CONSTRAINT CK_api_key_scopes
+  CHECK (scopes <@ ARRAY['read', 'rate', 'submit', 'play', 'admin']);
+
+-- Games played on the server. The cards of the sets of a game are shuffled with
+-- its seed, the same way as `bb.get_cards`, and drawn in that order: the cursors
+-- are the offsets of the next black and white cards in the shuffle.
+CREATE TABLE IF NOT EXISTS bb.game (
+  id SERIAL NOT NULL CONSTRAINT PK_game PRIMARY KEY,
+  owner_user_id INT NOT NULL CONSTRAINT FK_game_owner_user REFERENCES bb."user"(id),
+  card_sets INT[] NOT NULL,
+  random_seed REAL NOT NULL
+    CONSTRAINT CK_game_random_seed CHECK (random_seed BETWEEN -1 AND 1),
+  hand_size INT NOT NULL
+    CONSTRAINT CK_game_hand_size CHECK (hand_size BETWEEN 1 AND 50),
+  status TEXT NOT NULL DEFAULT 'lobby'
+    CONSTRAINT CK_game_status CHECK (status IN ('lobby', 'playing', 'judging', 'round_over', 'finished')),
+  round INT NOT NULL DEFAULT 0,
+  black_cursor INT NOT NULL DEFAULT 0,
+  white_cursor INT NOT NULL DEFAULT 0,
+  created_date TIMESTAMP NOT NULL DEFAULT NOW(),
+  last_modified TIMESTAMP NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX IX_game_owner_user ON bb.game (owner_user_id);
+
+CREATE TABLE IF NOT EXISTS bb.game_player (
+  id SERIAL NOT NULL CONSTRAINT PK_game_player PRIMARY KEY,
+  game_id INT NOT NULL CONSTRAINT FK_game_player_game REFERENCES bb.game(id),
+  user_id INT NOT NULL CONSTRAINT FK_game_player_user REFERENCES bb."user"(id),
+  seat INT NOT NULL,
+  score INT NOT NULL DEFAULT 0,
+  joined_date TIMESTAMP NOT NULL DEFAULT NOW(),
+  last_modified TIMESTAMP NOT NULL DEFAULT NOW(),
+  CONSTRAINT UX_game_player_user UNIQUE (game_id, user_id),
+  CONSTRAINT UX_game_player_seat UNIQUE (game_id, seat)
+);
+
+CREATE INDEX IX_game_player_user ON bb.game_player (user_id);
+
+CREATE TABLE IF NOT EXISTS bb.game_round (
+  game_id INT NOT NULL CONSTRAINT FK_game_round_game REFERENCES bb.game(id),
+  round INT NOT NULL,
+  czar_player_id INT NOT NULL CONSTRAINT FK_game_round_czar_player REFERENCES bb.game_player(id),
+  black_card_id INT NOT NULL CONSTRAINT FK_game_round_black_card REFERENCES bb.card(id),
+  winner_player_id INT CONSTRAINT FK_game_round_winner_player REFERENCES bb.game_player(id),
+  started_date TIMESTAMP NOT NULL DEFAULT NOW(),
+  last_modified TIMESTAMP NOT NULL DEFAULT NOW(),
+  CONSTRAINT PK_game_round PRIMARY KEY (game_id, round)
+);
+
+-- Cards drawn in a game, which replace the session key of the original table.
+-- White cards are held by a player until they are played (`played_round`), in
+-- the position of the blank they fill (`ordinal`). Black cards are not held.
+ALTER TABLE bb.draw_card RENAME cardid TO card_id;
+ALTER TABLE bb.draw_card RENAME drawdate TO draw_date;
+ALTER TABLE bb.draw_card RENAME isactive TO is_active;
+ALTER TABLE bb.draw_card RENAME lastmodified TO last_modified;
+ALTER TABLE bb.draw_card DROP sessionkey;
+ALTER TABLE bb.draw_card ALTER draw_date SET DEFAULT NOW();
+ALTER TABLE bb.draw_card ADD game_id INT NOT NULL CONSTRAINT FK_draw_card_game REFERENCES bb.game(id);
+ALTER TABLE bb.draw_card ADD game_player_id INT CONSTRAINT FK_draw_card_game_player REFERENCES bb.game_player(id);
+ALTER TABLE bb.draw_card ADD round INT NOT NULL;
+ALTER TABLE bb.draw_card ADD played_round INT;
+ALTER TABLE bb.draw_card ADD ordinal INT;
+
+-- A card is only drawn once per game
+CREATE UNIQUE INDEX UX_draw_card_game_card ON bb.draw_card (game_id, card_id);
+CREATE INDEX IX_draw_card_game_player ON bb.draw_card (game_player_id);
+
+-- Draws up to n_cards from the black or white pile of a game, held by the player
+-- (NULL for black cards), and returns the number of cards drawn. Fewer cards
+-- are drawn once the pile has run out. Cards that have already been drawn in
+-- the game (when cards were added to its sets since) are skipped.
+CREATE OR REPLACE FUNCTION bb.draw_game_cards(
+  in_game_id INT,
+  in_round INT,
+  in_player_id INT,
+  in_is_black BOOLEAN,
+  n_cards INT
+) RETURNS INT
+AS $$
+DECLARE
+  g bb.game;
+  pile_cursor INT;
+  n_requested INT;
+  n_fetched INT;
+  n_inserted INT;
+  n_drawn INT := 0;
+BEGIN
+  SELECT * INTO g FROM bb.game AS gm WHERE gm.id = in_game_id;
+  pile_cursor := CASE WHEN in_is_black THEN g.black_cursor ELSE g.white_cursor END;
+
+  WHILE n_drawn < n_cards LOOP
+    n_requested := n_cards - n_drawn;
+
+    WITH fetched AS (
+      SELECT c.id, row_number() OVER () AS position
+      FROM bb.get_cards(
+        NULL, in_is_black, pile_cursor, n_requested, g.card_sets,
+        true, g.random_seed, NULL, NULL, 'id', NULL, false
+      ) AS c
+    ), inserted AS (
+      INSERT INTO bb.draw_card (card_id, game_id, game_player_id, round)
+        SELECT f.id, in_game_id, in_player_id, in_round
+        FROM fetched AS f
+        ORDER BY f.position
+      ON CONFLICT (game_id, card_id) DO NOTHING
+      RETURNING 1
+    )
+    SELECT (SELECT COUNT(*) FROM fetched), (SELECT COUNT(*) FROM inserted)
+      INTO n_fetched, n_inserted;
+
+    pile_cursor := pile_cursor + n_fetched;
+    n_drawn := n_drawn + n_inserted;
+
+    EXIT WHEN n_fetched < n_requested;
+  END LOOP;
+
+  UPDATE ONLY bb.game AS gm
+    SET
+      black_cursor = CASE WHEN in_is_black THEN pile_cursor ELSE gm.black_cursor END,
+      white_cursor = CASE WHEN in_is_black THEN gm.white_cursor ELSE pile_cursor END
+    WHERE gm.id = in_game_id;
+
+  RETURN n_drawn;
+END;
+$$
+LANGUAGE 'plpgsql';
+
+-- Starts the next round of a game: the Card Czar passes to the next seat and
+-- draws a black card, and every player draws back up to the hand size, along
+-- with the extra cards of the black card for everyone but the Card Czar.
+-- Returns false, finishing the game, when the black pile has run out, or when
+-- the white pile has run out and no player but the Card Czar holds enough
+-- cards to play.
+CREATE OR REPLACE FUNCTION bb.start_game_round(in_game_id INT) RETURNS BOOLEAN
+AS $$
+DECLARE
+  g bb.game;
+  next_round INT;
+  previous_czar_seat INT;
+  czar_id INT;
+  black_id INT;
+  extra_cards INT;
+  black_pick INT;
+  p RECORD;
+BEGIN
+  SELECT * INTO g FROM bb.game AS gm WHERE gm.id = in_game_id;
+  next_round := g.round + 1;
+
+  IF bb.draw_game_cards(in_game_id, next_round, NULL, true, 1) = 0 THEN
+    UPDATE ONLY bb.game
+      SET
+        status = 'finished',
+        last_modified = NOW()
+      WHERE id = in_game_id;
+
+    RETURN false;
+  END IF;
+
+  SELECT d.card_id, c.draw, c.pick INTO black_id, extra_cards, black_pick
+    FROM bb.draw_card AS d
+      INNER JOIN bb.card AS c ON c.id = d.card_id
+    WHERE d.game_id = in_game_id AND d.round = next_round AND d.game_player_id IS NULL;
+
+  SELECT gp.seat INTO previous_czar_seat
+    FROM bb.game_round AS r
+      INNER JOIN bb.game_player AS gp ON gp.id = r.czar_player_id
+    WHERE r.game_id = in_game_id AND r.round = g.round;
+
+  SELECT gp.id INTO czar_id
+    FROM bb.game_player AS gp
+    WHERE gp.game_id = in_game_id
+    ORDER BY gp.seat <= COALESCE(previous_czar_seat, -1), gp.seat
+    LIMIT 1;
+
+  FOR p IN
+    SELECT
+      gp.id,
+      (
+        SELECT COUNT(*)
+        FROM bb.draw_card AS d
+        WHERE d.game_player_id = gp.id AND d.played_round IS NULL
+      )::INT AS n_held
+    FROM bb.game_player AS gp
+    WHERE gp.game_id = in_game_id
+    ORDER BY gp.seat
+  LOOP
+    PERFORM bb.draw_game_cards(
+      in_game_id, next_round, p.id, false,
+      GREATEST(0, g.hand_size - p.n_held) + CASE WHEN p.id = czar_id THEN 0 ELSE extra_cards END
+    );
+  END LOOP;
+
+  IF NOT EXISTS (
+    SELECT 1
+    FROM bb.game_player AS gp
+    WHERE
+      gp.game_id = in_game_id
+      AND gp.id <> czar_id
+      AND (
+        SELECT COUNT(*)
+        FROM bb.draw_card AS d
+        WHERE d.game_player_id = gp.id AND d.played_round IS NULL
+      ) >= black_pick
+  ) THEN
+    UPDATE ONLY bb.game
+      SET
+        status = 'finished',
+        last_modified = NOW()
+      WHERE id = in_game_id;
+
+    RETURN false;
+  END IF;
+
+  INSERT INTO bb.game_round (game_id, round, czar_player_id, black_card_id)
+    VALUES (in_game_id, next_round, czar_id, black_id);
+
+  UPDATE ONLY bb.game
+    SET
+      round = next_round,
+      status = 'playing',
+      last_modified = NOW()
+    WHERE id = in_game_id;
+
+  RETURN true;
+END;
+$$
+LANGUAGE 'plpgsql';
+
+-- The owner takes the first seat
+CREATE OR REPLACE FUNCTION bb.create_game(
+  in_owner_user_id INT,
+  in_card_sets INT[],
+  in_random_seed REAL,
+  in_hand_size INT
+) RETURNS INTEGER
+AS $$
+DECLARE
+  new_game_id INT;
+BEGIN
+  INSERT INTO bb.game (owner_user_id, card_sets, random_seed, hand_size)
+    VALUES (in_owner_user_id, in_card_sets, in_random_seed, in_hand_size)
+    RETURNING id INTO new_game_id;
+
+  INSERT INTO bb.game_player (game_id, user_id, seat)
+    VALUES (new_game_id, in_owner_user_id, 0);
+
+  RETURN new_game_id;
+END;
+$$
+LANGUAGE 'plpgsql';
+
+-- The game functions below lock the game while they are making a move, and
+-- return the reason the move was refused, or NULL once it has been made.
+
+-- Players can join until the game has finished, and are dealt a hand at the
+-- start of the next round. Joining a game twice has no effect.
+CREATE OR REPLACE FUNCTION bb.join_game(in_game_id INT, in_user_id INT) RETURNS TEXT
+AS $$
+DECLARE
+  g bb.game;
+BEGIN
+  SELECT * INTO g FROM bb.game AS gm WHERE gm.id = in_game_id FOR UPDATE;
+  IF NOT FOUND THEN
+    RETURN 'not_found';
+  END IF;
+
+  IF EXISTS (
+    SELECT 1
+    FROM bb.game_player AS gp
+    WHERE gp.game_id = in_game_id AND gp.user_id = in_user_id
+  ) THEN
+    RETURN NULL;
+  END IF;
+
+  IF g.status = 'finished' THEN
+    RETURN 'status';
+  END IF;
+
+  INSERT INTO bb.game_player (game_id, user_id, seat)
+    SELECT in_game_id, in_user_id, COALESCE(MAX(gp.seat) + 1, 0)
+    FROM bb.game_player AS gp
+    WHERE gp.game_id = in_game_id;
+
+  UPDATE ONLY bb.game SET last_modified = NOW() WHERE id = in_game_id;
+
+  RETURN NULL;
+END;
+$$
+LANGUAGE 'plpgsql';
+
+-- Starts the first round of a game. Only the owner can deal, once at least
+-- 3 players have joined.
+CREATE OR REPLACE FUNCTION bb.deal_game_hands(in_game_id INT, in_user_id INT) RETURNS TEXT
+AS $$
+DECLARE
+  g bb.game;
+BEGIN
+  SELECT * INTO g FROM bb.game AS gm WHERE gm.id = in_game_id FOR UPDATE;
+  IF NOT FOUND THEN
+    RETURN 'not_found';
+  END IF;
+
+  IF g.owner_user_id <> in_user_id THEN
+    RETURN 'forbidden';
+  END IF;
+
+  IF g.status <> 'lobby' THEN
+    RETURN 'status';
+  END IF;
+
+  IF (SELECT COUNT(*) FROM bb.game_player AS gp WHERE gp.game_id = in_game_id) < 3 THEN
+    RETURN 'not_enough_players';
+  END IF;
+
+  IF NOT EXISTS (
+    SELECT 1
+    FROM bb.get_cards(NULL, true, NULL, 1, g.card_sets, false, NULL, NULL, NULL, 'id', NULL, false)
+  ) OR NOT EXISTS (
+    SELECT 1
+    FROM bb.get_cards(NULL, false, NULL, 1, g.card_sets, false, NULL, NULL, NULL, 'id', NULL, false)
+  ) THEN
+    RETURN 'empty_deck';
+  END IF;
+
+  PERFORM bb.start_game_round(in_game_id);
+
+  RETURN NULL;
+END;
+$$
+LANGUAGE 'plpgsql';
+
+-- The plays of the current round, in an order shuffled with the seed of the
+-- game, so that the order does not give away the players
+CREATE OR REPLACE FUNCTION bb.get_game_plays(in_game_id INT)
+RETURNS TABLE (
+  player_id INT,
+  card_ids INT[]
+)
+AS $$
+  SELECT
+    d.game_player_id AS "player_id",
+    array_agg(d.card_id ORDER BY d.ordinal) AS "card_ids"
+  FROM bb.game AS g
+    INNER JOIN bb.draw_card AS d ON d.game_id = g.id AND d.played_round = g.round
+  WHERE g.id = in_game_id
+  GROUP BY g.random_seed, g.round, d.game_player_id
+  ORDER BY md5(g.random_seed::TEXT || ':' || g.round || ':' || d.game_player_id);
+$$
+LANGUAGE SQL;
+
+-- Plays as many cards from the hand as the black card picks, in the order of
+-- its blanks. Once every player (but the Card Czar) who holds enough cards
+-- has played, the Card Czar judges the plays.
+CREATE OR REPLACE FUNCTION bb.play_game_cards(
+  in_game_id INT,
+  in_user_id INT,
+  in_card_ids INT[]
+) RETURNS TEXT
+AS $$
+DECLARE
+  g bb.game;
+  r bb.game_round;
+  caller_player_id INT;
+  black_pick INT;
+BEGIN
+  SELECT * INTO g FROM bb.game AS gm WHERE gm.id = in_game_id FOR UPDATE;
+  IF NOT FOUND THEN
+    RETURN 'not_found';
+  END IF;
+
+  SELECT gp.id INTO caller_player_id
+    FROM bb.game_player AS gp
+    WHERE gp.game_id = in_game_id AND gp.user_id = in_user_id;
+  IF caller_player_id IS NULL THEN
+    RETURN 'not_player';
+  END IF;
+
+  IF g.status <> 'playing' THEN
+    RETURN 'status';
+  END IF;
+
+  SELECT * INTO r FROM bb.game_round AS gr WHERE gr.game_id = in_game_id AND gr.round = g.round;
+  IF r.czar_player_id = caller_player_id THEN
+    RETURN 'czar';
+  END IF;
+
+  IF EXISTS (
+    SELECT 1
+    FROM bb.draw_card AS d
+    WHERE d.game_player_id = caller_player_id AND d.played_round = g.round
+  ) THEN
+    RETURN 'already_played';
+  END IF;
+
+  SELECT c.pick INTO black_pick FROM bb.card AS c WHERE c.id = r.black_card_id;
+  IF cardinality(in_card_ids) <> black_pick THEN
+    RETURN 'pick';
+  END IF;
+
+  IF (
+    SELECT COUNT(DISTINCT d.card_id)
+    FROM bb.draw_card AS d
+    WHERE
+      d.game_player_id = caller_player_id
+      AND d.played_round IS NULL
+      AND d.card_id = ANY(in_card_ids)
+  ) <> black_pick THEN
+    RETURN 'not_in_hand';
+  END IF;
+
+  UPDATE ONLY bb.draw_card AS d
+    SET
+      played_round = g.round,
+      ordinal = array_position(in_card_ids, d.card_id) - 1,
+      last_modified = NOW()
+    WHERE d.game_player_id = caller_player_id AND d.card_id = ANY(in_card_ids);
+
+  UPDATE ONLY bb.game AS gm
+    SET
+      status = CASE WHEN NOT EXISTS (
+        SELECT 1
+        FROM bb.game_player AS gp
+        WHERE
+          gp.game_id = in_game_id
+          AND gp.id <> r.czar_player_id
+          AND NOT EXISTS (
+            SELECT 1
+            FROM bb.draw_card AS d
+            WHERE d.game_player_id = gp.id AND d.played_round = g.round
+          )
+          AND (
+            SELECT COUNT(*)
+            FROM bb.draw_card AS d
+            WHERE d.game_player_id = gp.id AND d.played_round IS NULL
+          ) >= black_pick
+      ) THEN 'judging' ELSE gm.status END,
+      last_modified = NOW()
+    WHERE gm.id = in_game_id;
+
+  RETURN NULL;
+END;
+$$
+LANGUAGE 'plpgsql';
+
+-- The Card Czar picks the winning play by its position in `bb.get_game_plays`,
+-- and its player scores a point
+CREATE OR REPLACE FUNCTION bb.pick_game_winner(
+  in_game_id INT,
+  in_user_id INT,
+  in_play INT
+) RETURNS TEXT
+AS $$
+DECLARE
+  g bb.game;
+  r bb.game_round;
+  caller_player_id INT;
+  winner_id INT;
+BEGIN
+  SELECT * INTO g FROM bb.game AS gm WHERE gm.id = in_game_id FOR UPDATE;
+  IF NOT FOUND THEN
+    RETURN 'not_found';
+  END IF;
+
+  SELECT gp.id INTO caller_player_id
+    FROM bb.game_player AS gp
+    WHERE gp.game_id = in_game_id AND gp.user_id = in_user_id;
+  IF caller_player_id IS NULL THEN
+    RETURN 'not_player';
+  END IF;
+
+  IF g.status <> 'judging' THEN
+    RETURN 'status';
+  END IF;
+
+  SELECT * INTO r FROM bb.game_round AS gr WHERE gr.game_id = in_game_id AND gr.round = g.round;
+  IF r.czar_player_id <> caller_player_id THEN
+    RETURN 'not_czar';
+  END IF;
+
+  IF in_play >= 0 THEN
+    SELECT p.player_id INTO winner_id
+      FROM bb.get_game_plays(in_game_id) AS p
+      OFFSET in_play
+      LIMIT 1;
+  END IF;
+  IF winner_id IS NULL THEN
+    RETURN 'play_not_found';
+  END IF;
+
+  UPDATE ONLY bb.game_player
+    SET
+      score = score + 1,
+      last_modified = NOW()
+    WHERE id = winner_id;
+
+  UPDATE ONLY bb.game_round
+    SET
+      winner_player_id = winner_id,
+      last_modified = NOW()
+    WHERE game_id = in_game_id AND round = g.round;
+
+  UPDATE ONLY bb.game
+    SET
+      status = 'round_over',
+      last_modified = NOW()
+    WHERE id = in_game_id;
+
+  RETURN NULL;
+END;
+$$
+LANGUAGE 'plpgsql';
+
+-- Starts the next round once the winner has been picked. Only the Card Czar
+-- and the owner can start it.
+CREATE OR REPLACE FUNCTION bb.next_game_round(in_game_id INT, in_user_id INT) RETURNS TEXT
+AS $$
+DECLARE
+  g bb.game;
+  caller_player_id INT;
+BEGIN
+  SELECT * INTO g FROM bb.game AS gm WHERE gm.id = in_game_id FOR UPDATE;
+  IF NOT FOUND THEN
+    RETURN 'not_found';
+  END IF;
+
+  SELECT gp.id INTO caller_player_id
+    FROM bb.game_player AS gp
+    WHERE gp.game_id = in_game_id AND gp.user_id = in_user_id;
+  IF caller_player_id IS NULL THEN
+    RETURN 'not_player';
+  END IF;
+
+  IF g.status <> 'round_over' THEN
+    RETURN 'status';
+  END IF;
+
+  IF g.owner_user_id <> in_user_id AND NOT EXISTS (
+    SELECT 1
+    FROM bb.game_round AS r
+    WHERE r.game_id = in_game_id AND r.round = g.round AND r.czar_player_id = caller_player_id
+  ) THEN
+    RETURN 'forbidden';
+  END IF;
+
+  PERFORM bb.start_game_round(in_game_id);
+
+  RETURN NULL;
+END;
+$$
+LANGUAGE 'plpgsql';
+
+-- The current round of a game. The Card Czar, black card, and winner are NULL
+-- before the first round.
+CREATE OR REPLACE FUNCTION bb.get_game(in_game_id INT)
+RETURNS TABLE (
+  id INT,
+  owner_user_id INT,
+  owner_username TEXT,
+  card_sets INT[],
+  random_seed REAL,
+  hand_size INT,
+  status TEXT,
+  round INT,
+  czar_player_id INT,
+  black_card_id INT,
+  winner_player_id INT
+)
+AS $$
+  SELECT
+    g.id,
+    u.id AS "owner_user_id",
+    u.username AS "owner_username",
+    g.card_sets,
+    g.random_seed,
+    g.hand_size,
+    g.status,
+    g.round,
+    r.czar_player_id,
+    r.black_card_id,
+    r.winner_player_id
+  FROM bb.game AS g
+    INNER JOIN bb."user" AS u ON u.id = g.owner_user_id
+    LEFT JOIN bb.game_round AS r ON r.game_id = g.id AND r.round = g.round
+  WHERE g.id = in_game_id;
+$$
+LANGUAGE SQL;
+
+-- Players by seat, with the number of cards they hold, and whether they have
+-- played in the current round
+CREATE OR REPLACE FUNCTION bb.get_game_players(in_game_id INT)
+RETURNS TABLE (
+  id INT,
+  user_id INT,
+  username TEXT,
+  score INT,
+  n_cards INT,
+  has_played BOOLEAN
+)
+AS $$
+  SELECT
+    gp.id,
+    u.id AS "user_id",
+    u.username,
+    gp.score,
+    (COUNT(d.card_id) FILTER (WHERE d.played_round IS NULL))::INT AS "n_cards",
+    COALESCE(bool_or(d.played_round = g.round), false) AS "has_played"
+  FROM bb.game_player AS gp
+    INNER JOIN bb.game AS g ON g.id = gp.game_id
+    INNER JOIN bb."user" AS u ON u.id = gp.user_id
+    LEFT JOIN bb.draw_card AS d ON d.game_player_id = gp.id
+  WHERE gp.game_id = in_game_id
+  GROUP BY gp.id, u.id, g.round
+  ORDER BY gp.seat;
+$$
+LANGUAGE SQL;
+
+-- Cards held by a player, in the order they were drawn
+CREATE OR REPLACE FUNCTION bb.get_game_hand(in_game_id INT, in_user_id INT)
+RETURNS TABLE (
+  card_id INT
+)
+AS $$
+  SELECT d.card_id
+  FROM bb.draw_card AS d
+    INNER JOIN bb.game_player AS gp ON gp.id = d.game_player_id
+  WHERE gp.game_id = in_game_id AND gp.user_id = in_user_id AND d.played_round IS NULL
+  ORDER BY d.id;
+$$
+LANGUAGE SQL;
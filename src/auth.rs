@@ -0,0 +1,164 @@
+use crate::db::{self, Pool};
+use actix_web::{http::header::AUTHORIZATION, HttpMessage, HttpRequest};
+use base64::{encode_config, URL_SAFE_NO_PAD};
+use rand::random;
+use std::fmt;
+
+/// Cookie used to carry the token for browser (session) clients
+pub const SESSION_COOKIE: &str = "session";
+
+/// Number of days an issued token is valid for
+pub const TOKEN_LIFETIME_DAYS: i32 = 30;
+
+/// Permissions granted to a caller. Sessions (tokens issued through `/login`
+/// or `registerUser`) are granted every scope but `Admin`, which is only granted
+/// to admin users. API keys are granted the scopes they were created with.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Scope {
+  Read,
+  Rate,
+  Submit,
+  Play,
+  Admin,
+}
+
+impl Scope {
+  /// Name of the scope as stored in `bb.api_key.scopes`
+  pub fn name(self) -> &'static str {
+    match self {
+      Scope::Read => "read",
+      Scope::Rate => "rate",
+      Scope::Submit => "submit",
+      Scope::Play => "play",
+      Scope::Admin => "admin",
+    }
+  }
+
+  pub fn from_name(name: &str) -> Option<Scope> {
+    match name {
+      "read" => Some(Scope::Read),
+      "rate" => Some(Scope::Rate),
+      "submit" => Some(Scope::Submit),
+      "play" => Some(Scope::Play),
+      "admin" => Some(Scope::Admin),
+      _ => None,
+    }
+  }
+}
+
+/// Credentials sent along with a request
+pub enum Credentials {
+  /// Session token, issued through `/login` or `registerUser`
+  Token(String),
+  /// API key, issued through `createApiKey` for machine clients
+  ApiKey(String),
+}
+
+/// The authenticated caller of a request
+pub struct Caller {
+  pub user_id: i32,
+  pub scopes: Vec<Scope>,
+  /// Set when the caller authenticated with an API key rather than a session
+  pub api_key_id: Option<i32>,
+}
+
+impl Caller {
+  /// `Admin` implies every other scope
+  pub fn has_scope(&self, scope: Scope) -> bool {
+    self.scopes.contains(&Scope::Admin) || self.scopes.contains(&scope)
+  }
+}
+
+#[derive(Debug)]
+pub enum AuthError {
+  InvalidToken,
+  UnexpectedError,
+}
+
+impl fmt::Display for AuthError {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    match self {
+      AuthError::InvalidToken => write!(f, "Invalid or expired token or API key"),
+      AuthError::UnexpectedError => write!(f, "Unable to authenticate request"),
+    }
+  }
+}
+
+/// Reads the caller's credentials from the `Authorization` header, either
+/// `Bearer {token}` or `ApiKey {key}`. Browser clients without control over
+/// their headers may instead send the token in the session cookie. The header
+/// takes precedence when both are sent.
+pub fn credentials_from_request(req: &HttpRequest) -> Option<Credentials> {
+  let header = req
+    .headers()
+    .get(AUTHORIZATION)
+    .and_then(|v| v.to_str().ok())
+    .and_then(|v| {
+      let mut parts = v.splitn(2, ' ');
+      match (parts.next(), parts.next()) {
+        (Some(scheme), Some(token)) if scheme.eq_ignore_ascii_case("bearer") => {
+          Some(Credentials::Token(token.trim().to_owned()))
+        }
+        (Some(scheme), Some(key)) if scheme.eq_ignore_ascii_case("apikey") => {
+          Some(Credentials::ApiKey(key.trim().to_owned()))
+        }
+        _ => None,
+      }
+    });
+
+  header.or_else(|| {
+    req
+      .cookie(SESSION_COOKIE)
+      .map(|c| Credentials::Token(c.value().to_owned()))
+  })
+}
+
+/// Resolves the `bb.user` the credentials were issued to, along with the
+/// scopes they grant. Anonymous callers (no credentials) resolve to `None`,
+/// while credentials that are present but cannot be resolved are an error,
+/// rather than silently downgrading the caller.
+///
+/// This is a blocking call, and should be run on the `web::block` threadpool.
+pub fn authenticate(
+  pool: &Pool,
+  credentials: Option<Credentials>,
+) -> Result<Option<Caller>, AuthError> {
+  match credentials {
+    None => Ok(None),
+    Some(Credentials::Token(token)) => match db::get_user_by_token(pool, &token) {
+      Ok(Some(user)) => {
+        let mut scopes = vec![Scope::Read, Scope::Rate, Scope::Submit, Scope::Play];
+        if user.is_admin {
+          scopes.push(Scope::Admin);
+        }
+        Ok(Some(Caller {
+          user_id: user.user_id,
+          scopes,
+          api_key_id: None,
+        }))
+      }
+      Ok(None) => Err(AuthError::InvalidToken),
+      Err(_) => Err(AuthError::UnexpectedError),
+    },
+    Some(Credentials::ApiKey(key)) => match db::get_api_key(pool, &key) {
+      Ok(Some(api_key)) => Ok(Some(Caller {
+        user_id: api_key.user_id,
+        scopes: api_key
+          .scopes
+          .iter()
+          .filter_map(|s| Scope::from_name(s))
+          .filter(|s| *s != Scope::Admin || api_key.is_admin)
+          .collect(),
+        api_key_id: Some(api_key.id),
+      })),
+      Ok(None) => Err(AuthError::InvalidToken),
+      Err(_) => Err(AuthError::UnexpectedError),
+    },
+  }
+}
+
+/// Generates a new random token. 256 bits of randomness, encoded to be safe
+/// for use in headers, cookies, and URLs.
+pub fn generate_token() -> String {
+  encode_config(&random::<[u8; 32]>(), URL_SAFE_NO_PAD)
+}
@@ -1,10 +1,21 @@
 use crate::models::{
   AddCard, AddCardRating, AddCardRatingCombination, AddCardRatingResult, AddCardResult,
-  GetCardResults, GetCards, GetSetResults, GetSets,
+  CreateApiKey, CreateDeck, CreateGame, CreateSet, CreateUserToken, EditCard, GameMove,
+  GenerateTimeDeck, GetApiKeyResults, GetCardCountResults, GetCardModerationResults,
+  GetCardPairingResults, GetCardPairings, GetCardRatingBreakdownResults, GetCardReportResults,
+  GetCardReports, GetCardResults, GetCardRevisionResults, GetCards, GetDeckResults,
+  GetGamePlayResults, GetGamePlayerResults, GetGameResults, GetModerationQueue,
+  GetRatingDistributionResults, GetRatingVolume, GetRatingVolumeResults, GetSetResults, GetSets,
+  GetTimeDeck, GetTimeDeckResults, GetTimeDecks, GetTopRaterResults, GetUserApiKeyResults,
+  GetUserByTokenResults, GetUserCardCombinationRatingResults, GetUserCardCombinationRatings,
+  GetUserCardRatingResults, GetUserDecks, GetUserHistory, GetUserResults, GetUserSubmittedCards,
+  GetVisibleCards, LoginUserIdentity, ModerateCard, PickGameWinner, PlayGameCards, RegisterUser,
+  RemoveCardRating, RenameSet, ReportCard, RevokeApiKey, RunTimeDeckJob, TimeDeckPeriodEnded,
+  UpdateDeck, UpdateSetCards, UpdateUsername,
 };
 use actix_web::{error::ErrorInternalServerError, Error as AWError};
 use fallible_iterator::FallibleIterator;
-use postgres::types::Type;
+use postgres::{error::SqlState, types::Type, Row};
 use r2d2_postgres::PostgresConnectionManager;
 
 pub type ConnectionManager = PostgresConnectionManager<postgres::NoTls>;
@@ -44,7 +55,9 @@ pub fn create_pool(pg_config: PgConfig, pool_config: &PoolConfiguration) -> Pool
 /// ensure that the preparation of these statements is cached, possibly configured
 /// in the `create_pool` method using the `r2d2::CustomizeConnection` trait.
 ///
-/// Uses the database function `bb.get_cards(search, filter_black, previous_cursor, n_cards, card_sets, get_random, random_seed, user_submitted)`
+/// Uses the database function `bb.get_cards(search, filter_black, previous_cursor, n_cards, card_sets, get_random, random_seed, user_submitted, filter_pick, sort_by, previous_sort_key, sort_descending)`
+/// A card is returned once, along with every set it belongs to, even when it
+/// belongs to several of the `card_sets`.
 ///
 /// `Row::get()` accepts "Column Name", or Ordinal_i32. To add minor performance,
 /// we are using the ordinal to access the value of the column, as well as choosing
@@ -58,7 +71,7 @@ pub fn get_cards(pool: &Pool, query: &GetCards) -> Result<Vec<GetCardResults>, A
     .map_err(|e| ErrorInternalServerError(e))?;
   let stmt = client
         .prepare_typed(
-            "SELECT id, format_text, is_black, parent_set_id, parent_set_name, total_votes, average_rating FROM bb.get_cards($1, $2, $3, $4, $5, $6, $7, $8)",
+            "SELECT id, format_text, is_black, parent_set_id, parent_set_name, total_votes, average_rating, submitted_by_user_id, submitted_by_username, parent_set_ids, parent_set_names, pick, draw, score, sort_key FROM bb.get_cards($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12)",
             &[
                 Type::TEXT,
                 Type::BOOL,
@@ -67,6 +80,10 @@ pub fn get_cards(pool: &Pool, query: &GetCards) -> Result<Vec<GetCardResults>, A
                 Type::INT4_ARRAY,
                 Type::BOOL,
                 Type::FLOAT4,
+                Type::BOOL,
+                Type::INT4,
+                Type::TEXT,
+                Type::FLOAT8,
                 Type::BOOL
             ],)
         .map_err(|e| ErrorInternalServerError(e))?;
@@ -83,6 +100,10 @@ pub fn get_cards(pool: &Pool, query: &GetCards) -> Result<Vec<GetCardResults>, A
         &query.get_random,
         &query.random_seed,
         &query.user_submitted,
+        &query.pick,
+        &query.sort_by,
+        &query.previous_sort_key,
+        &query.sort_descending,
       ],
     )
     .map_err(|e| ErrorInternalServerError(e))?;
@@ -90,13 +111,8 @@ pub fn get_cards(pool: &Pool, query: &GetCards) -> Result<Vec<GetCardResults>, A
     results
       .map(|r| {
         Ok(GetCardResults {
-          id: r.get::<_, i32>(0),
-          format_text: r.get::<_, String>(1),
-          is_black: r.get::<_, bool>(2),
-          parent_set_id: r.get::<_, i32>(3),
-          parent_set_name: r.get::<_, String>(4),
-          total_votes: r.get::<_, i32>(5),
-          average_rating: r.get::<_, Option<f32>>(6),
+          sort_key: r.get::<_, Option<f64>>(14),
+          ..card_result_from_row(&r)
         })
       })
       .map_err(|e| ErrorInternalServerError(e))
@@ -142,43 +158,14 @@ pub fn get_sets(pool: &Pool, query: &GetSets) -> Result<Vec<GetSetResults>, AWEr
   )
 }
 
-/// Get sets by ID database call. Calls the prepare_typed method to ensure our data
-/// types match the SQL types used in the statement. Future implementation should
-/// ensure that the preparation of these statements is cached, possibly configured
-/// in the `create_pool` method using the `r2d2::CustomizeConnection` trait.
-///
-/// `Row::get()` accepts "Column Name", or Ordinal_i32. To add minor performance,
-/// we are using the ordinal to access the value of the column, as well as choosing
-/// not to use the `SELECT *` syntax.
-/// This does replace maintainability for performance, but the goal is currently
-/// to focus on performance and control.
-pub fn get_set_by_id(pool: &Pool, query: i32) -> Result<GetSetResults, AWError> {
-  let client = pool.clone();
-  let mut client = client.get().map_err(|e| ErrorInternalServerError(e))?;
-  let stmt = client
-    .prepare_typed(
-      "SELECT id, name FROM bb.parent_set WHERE id = $1",
-      &[Type::INT4],
-    )
-    .map_err(|e| ErrorInternalServerError(e))?;
-  let result = &client
-    .query(&stmt, &[&query])
-    .map_err(|e| ErrorInternalServerError(e))?[0];
-
-  Ok(GetSetResults {
-    id: result.get::<_, i32>(0),
-    name: result.get::<_, String>(1),
-  })
-}
-
 /// Create card database call. Calls the prepare_typed method to ensure our data
 /// types match the SQL types used in the statement. Future implementation should
 /// ensure that the preparation of these statements is cached, possibly configured
 /// in the `create_pool` method using the `r2d2::CustomizeConnection` trait.
 ///
 /// Uses the `bb.create_card(format_text, is_black, created_by_user_id)` method.
-/// The database should return an error when submitting a card that has already
-/// been created.
+/// Submitting a card that has already been created does not insert a card, and
+/// instead returns the ID of the existing card with `is_duplicate` set.
 ///
 /// `Row::get()` accepts "Column Name", or Ordinal_i32. To add minor performance,
 /// we are using the ordinal to access the value of the column, as well as choosing
@@ -190,7 +177,7 @@ pub fn add_card(pool: &Pool, query: &AddCard) -> Result<AddCardResult, AWError>
   let mut client = client.get().map_err(|e| ErrorInternalServerError(e))?;
   let stmt = client
     .prepare_typed(
-      "select create_card from bb.create_card($1, $2, $3)",
+      "select id, is_duplicate from bb.create_card($1, $2, $3)",
       &[Type::TEXT, Type::BOOL, Type::INT4],
     )
     .map_err(|e| ErrorInternalServerError(e))?;
@@ -204,6 +191,7 @@ pub fn add_card(pool: &Pool, query: &AddCard) -> Result<AddCardResult, AWError>
 
   Ok(AddCardResult {
     id: result.get::<_, i32>(0),
+    is_duplicate: result.get::<_, bool>(1),
   })
 }
 
@@ -213,11 +201,10 @@ pub fn add_card(pool: &Pool, query: &AddCard) -> Result<AddCardResult, AWError>
 /// in the `create_pool` method using the `r2d2::CustomizeConnection` trait.
 ///
 /// Uses the `bb.user_rate_card(user_id, card_id, rating)` method.
-/// The database should ensure that an UPSERT is executed to change a user's rating
-/// of a card when a conflict occurs. This should additionally update in-place the
-/// card's total_votes and average_rating fields accordingly (Insertion of a new record/Updating an already-cast vote)
-/// This function also does not return a result set, but rather out parameters to
-/// ensure that only a single record is returned from the resulting query.
+/// The database executes an UPSERT to change a user's rating of a card when a
+/// conflict occurs, and recomputes the card's total_votes and average_rating
+/// fields from the active ratings (Insertion of a new record/Updating an already-cast vote).
+/// `None` is returned when the card does not exist (or is inactive).
 ///
 /// `Row::get()` accepts "Column Name", or Ordinal_i32. To add minor performance,
 /// we are using the ordinal to access the value of the column, as well as choosing
@@ -227,24 +214,55 @@ pub fn add_card(pool: &Pool, query: &AddCard) -> Result<AddCardResult, AWError>
 pub fn add_user_rating_to_card(
   pool: &Pool,
   query: &AddCardRating,
-) -> Result<AddCardRatingResult, AWError> {
+) -> Result<Option<AddCardRatingResult>, AWError> {
   let client = pool.clone();
   let mut client = client.get().map_err(|e| ErrorInternalServerError(e))?;
   let stmt = client
     .prepare_typed(
-      "SELECT out_total_votes, out_average_rating FROM bb.user_rate_card($1, $2, $3)",
+      "SELECT total_votes, average_rating, previous_rating FROM bb.user_rate_card($1, $2, $3)",
       &[Type::INT4, Type::INT4, Type::FLOAT4],
     )
     .map_err(|e| ErrorInternalServerError(e))?;
 
-  let result = &client
+  let results = client
     .query(&stmt, &[&query.user_id, &query.card_id, &query.rating])
-    .map_err(|e| ErrorInternalServerError(e))?[0];
+    .map_err(|e| ErrorInternalServerError(e))?;
 
-  Ok(AddCardRatingResult {
-    total_votes: result.get::<_, i32>(0),
-    average_rating: result.get::<_, f32>(1),
-  })
+  Ok(results.first().map(card_rating_result_from_row))
+}
+
+/// Remove User Rating from Card database call. Calls the prepare_typed method to ensure our data
+/// types match the SQL types used in the statement.
+///
+/// Uses the `bb.user_unrate_card(user_id, card_id)` method, deactivating the user's
+/// rating and recomputing the card's total_votes and average_rating fields.
+/// `None` is returned when the card does not exist (or is inactive).
+pub fn remove_user_rating_from_card(
+  pool: &Pool,
+  query: &RemoveCardRating,
+) -> Result<Option<AddCardRatingResult>, AWError> {
+  let client = pool.clone();
+  let mut client = client.get().map_err(|e| ErrorInternalServerError(e))?;
+  let stmt = client
+    .prepare_typed(
+      "SELECT total_votes, average_rating, previous_rating FROM bb.user_unrate_card($1, $2)",
+      &[Type::INT4, Type::INT4],
+    )
+    .map_err(|e| ErrorInternalServerError(e))?;
+
+  let results = client
+    .query(&stmt, &[&query.user_id, &query.card_id])
+    .map_err(|e| ErrorInternalServerError(e))?;
+
+  Ok(results.first().map(card_rating_result_from_row))
+}
+
+fn card_rating_result_from_row(r: &Row) -> AddCardRatingResult {
+  AddCardRatingResult {
+    total_votes: r.get::<_, i32>(0),
+    average_rating: r.get::<_, Option<f32>>(1),
+    previous_rating: r.get::<_, Option<f32>>(2),
+  }
 }
 
 /// Add User Rating to Card Combination database call. Calls the prepare_typed method to ensure our data
@@ -252,13 +270,13 @@ pub fn add_user_rating_to_card(
 /// ensure that the preparation of these statements is cached, possibly configured
 /// in the `create_pool` method using the `r2d2::CustomizeConnection` trait.
 ///
-/// Uses the `bb.user_rate_card_combination(user_id, black_card_id, white_card, rating, ordinal)` method.
+/// Uses the `bb.user_rate_card_combination(user_id, white_card_id, black_card_id, rating, ordinal)` method.
 /// The database should ensure that an UPSERT is executed to change a user's rating
 /// of a card when a conflict occurs. Unlike the `add_user_rate_card()` method,
 /// this does not need to update any currently maintained statistics. The Ordinal
-/// is needed for white cards with multiple prompts. There is currently no way
-/// to query this information from the GraphQL API, as it is intended for analytical
-/// use later.
+/// is needed for white cards with multiple prompts. The ratings of a card are
+/// aggregated by `get_card_pairings()`; see `get_user_card_combination_ratings()`
+/// for a user's own ratings.
 ///
 /// This database function has no return value, but can throw an error (hence the `Result<(), AWError>` type)
 pub fn add_user_rate_card_combination(
@@ -279,8 +297,8 @@ pub fn add_user_rate_card_combination(
       &stmt,
       &[
         &query.user_id,
-        &query.black_card_id,
         &query.white_card_id,
+        &query.black_card_id,
         &query.rating,
         &query.ordinal,
       ],
@@ -289,3 +307,1571 @@ pub fn add_user_rate_card_combination(
 
   Ok(())
 }
+
+/// Get user by token database call. Calls the prepare_typed method to ensure our data
+/// types match the SQL types used in the statement.
+///
+/// Uses the `bb.get_user_by_token(token)` method. Tokens are only stored hashed,
+/// so the raw token is passed along and hashed by the database for the lookup.
+/// Unknown, expired, or revoked tokens (as well as tokens belonging to an inactive
+/// user) resolve to `None`.
+pub fn get_user_by_token(
+  pool: &Pool,
+  token: &str,
+) -> Result<Option<GetUserByTokenResults>, AWError> {
+  let client = pool.clone();
+  let mut client = client.get().map_err(|e| ErrorInternalServerError(e))?;
+  let stmt = client
+    .prepare_typed(
+      "SELECT u.id, u.is_admin FROM bb.\"user\" AS u WHERE u.id = bb.get_user_by_token($1)",
+      &[Type::TEXT],
+    )
+    .map_err(|e| ErrorInternalServerError(e))?;
+
+  let results = client
+    .query(&stmt, &[&token])
+    .map_err(|e| ErrorInternalServerError(e))?;
+
+  Ok(results.first().map(|r| GetUserByTokenResults {
+    user_id: r.get::<_, i32>(0),
+    is_admin: r.get::<_, bool>(1),
+  }))
+}
+
+/// Login user identity database call. Calls the prepare_typed method to ensure our data
+/// types match the SQL types used in the statement.
+///
+/// Uses the `bb.login_user_identity(provider, subject, username, fallback_username, link_user_id)`
+/// method.
+/// The database creates (or links) the `bb.user` on the first login of an identity,
+/// and returns the same user for every subsequent login. `None` is returned when the
+/// identity belongs to a user that is no longer active.
+pub fn login_user_identity(pool: &Pool, query: &LoginUserIdentity) -> Result<Option<i32>, AWError> {
+  let client = pool.clone();
+  let mut client = client.get().map_err(|e| ErrorInternalServerError(e))?;
+  let stmt = client
+    .prepare_typed(
+      "SELECT bb.login_user_identity($1, $2, $3, $4, $5)",
+      &[Type::TEXT, Type::TEXT, Type::TEXT, Type::TEXT, Type::INT4],
+    )
+    .map_err(|e| ErrorInternalServerError(e))?;
+
+  let result = &client
+    .query(
+      &stmt,
+      &[
+        &query.provider,
+        &query.subject,
+        &query.username,
+        &query.fallback_username,
+        &query.link_user_id,
+      ],
+    )
+    .map_err(|e| ErrorInternalServerError(e))?[0];
+
+  Ok(result.get::<_, Option<i32>>(0))
+}
+
+/// Create user token database call. Calls the prepare_typed method to ensure our data
+/// types match the SQL types used in the statement.
+///
+/// Uses the `bb.create_user_token(token, user_id, lifetime_days)` method. Only the
+/// hash of the token is persisted, so the caller is responsible for handing the raw
+/// token back to the user.
+///
+/// This database procedure has no return value, but can throw an error (hence the `Result<(), AWError>` type)
+pub fn create_user_token(pool: &Pool, query: &CreateUserToken) -> Result<(), AWError> {
+  let client = pool.clone();
+  let mut client = client.get().map_err(|e| ErrorInternalServerError(e))?;
+  let stmt = client
+    .prepare_typed(
+      "CALL bb.create_user_token($1, $2, $3)",
+      &[Type::TEXT, Type::INT4, Type::INT4],
+    )
+    .map_err(|e| ErrorInternalServerError(e))?;
+
+  client
+    .execute(&stmt, &[&query.token, &query.user_id, &query.lifetime_days])
+    .map_err(|e| ErrorInternalServerError(e))?;
+
+  Ok(())
+}
+
+/// Get user by ID database call. Calls the prepare_typed method to ensure our data
+/// types match the SQL types used in the statement.
+///
+/// Only active users are returned; `None` is returned for unknown or inactive users.
+pub fn get_user_by_id(pool: &Pool, query: i32) -> Result<Option<GetUserResults>, AWError> {
+  let client = pool.clone();
+  let mut client = client.get().map_err(|e| ErrorInternalServerError(e))?;
+  let stmt = client
+    .prepare_typed(
+      "SELECT id, username FROM bb.\"user\" WHERE id = $1 AND is_active = true",
+      &[Type::INT4],
+    )
+    .map_err(|e| ErrorInternalServerError(e))?;
+
+  let results = client
+    .query(&stmt, &[&query])
+    .map_err(|e| ErrorInternalServerError(e))?;
+
+  Ok(results.first().map(|r| GetUserResults {
+    id: r.get::<_, i32>(0),
+    username: r.get::<_, String>(1),
+  }))
+}
+
+/// Register user database call. Calls the prepare_typed method to ensure our data
+/// types match the SQL types used in the statement.
+///
+/// Uses the `bb.register_user(username)` method, returning the ID of the new user.
+/// `None` is returned when the username has already been taken.
+pub fn register_user(pool: &Pool, query: &RegisterUser) -> Result<Option<i32>, AWError> {
+  let client = pool.clone();
+  let mut client = client.get().map_err(|e| ErrorInternalServerError(e))?;
+  let stmt = client
+    .prepare_typed("SELECT bb.register_user($1)", &[Type::TEXT])
+    .map_err(|e| ErrorInternalServerError(e))?;
+
+  let result = &client
+    .query(&stmt, &[&query.username])
+    .map_err(|e| ErrorInternalServerError(e))?[0];
+
+  Ok(result.get::<_, Option<i32>>(0))
+}
+
+/// Update username database call. Calls the prepare_typed method to ensure our data
+/// types match the SQL types used in the statement.
+///
+/// Uses the `bb.update_username(user_id, username)` method. Returns `false` when
+/// the username has already been taken by another user, which the database reports
+/// as a unique violation so that concurrent updates cannot claim the same name.
+pub fn update_username(pool: &Pool, query: &UpdateUsername) -> Result<bool, AWError> {
+  let client = pool.clone();
+  let mut client = client.get().map_err(|e| ErrorInternalServerError(e))?;
+  let stmt = client
+    .prepare_typed(
+      "SELECT bb.update_username($1, $2)",
+      &[Type::INT4, Type::TEXT],
+    )
+    .map_err(|e| ErrorInternalServerError(e))?;
+
+  match client.query(&stmt, &[&query.user_id, &query.username]) {
+    Ok(result) => Ok(result[0].get::<_, Option<bool>>(0).unwrap_or(false)),
+    Err(e) if e.code() == Some(&SqlState::UNIQUE_VIOLATION) => Ok(false),
+    Err(e) => Err(ErrorInternalServerError(e)),
+  }
+}
+
+/// Maps the card columns shared by `bb.get_cards(...)`, `bb.get_cards_by_ids(card_ids)`,
+/// and the functions built on them
+fn card_result_from_row(r: &Row) -> GetCardResults {
+  GetCardResults {
+    id: r.get::<_, i32>(0),
+    format_text: r.get::<_, String>(1),
+    is_black: r.get::<_, bool>(2),
+    parent_set_id: r.get::<_, Option<i32>>(3),
+    parent_set_name: r.get::<_, Option<String>>(4),
+    total_votes: r.get::<_, i32>(5),
+    average_rating: r.get::<_, Option<f32>>(6),
+    submitted_by_user_id: r.get::<_, Option<i32>>(7),
+    submitted_by_username: r.get::<_, Option<String>>(8),
+    parent_set_ids: r.get::<_, Vec<i32>>(9),
+    parent_set_names: r.get::<_, Vec<String>>(10),
+    pick: r.get::<_, i32>(11),
+    draw: r.get::<_, i32>(12),
+    score: r.get::<_, f32>(13),
+    sort_key: None,
+  }
+}
+
+/// Get cards by IDs database call. Calls the prepare_typed method to ensure our data
+/// types match the SQL types used in the statement.
+///
+/// Uses the `bb.get_cards_by_ids(card_ids)` method. Inactive and unknown cards are
+/// left out of the results, and each card is returned once, along with the first
+/// set it belongs to (if any).
+pub fn get_cards_by_ids(pool: &Pool, query: &[i32]) -> Result<Vec<GetCardResults>, AWError> {
+  let client = pool.clone();
+  let mut client = client.get().map_err(|e| ErrorInternalServerError(e))?;
+  let stmt = client
+    .prepare_typed(
+      "SELECT id, format_text, is_black, parent_set_id, parent_set_name, total_votes, average_rating, submitted_by_user_id, submitted_by_username, parent_set_ids, parent_set_names, pick, draw, score FROM bb.get_cards_by_ids($1)",
+      &[Type::INT4_ARRAY],
+    )
+    .map_err(|e| ErrorInternalServerError(e))?;
+
+  let results = client
+    .query(&stmt, &[&query])
+    .map_err(|e| ErrorInternalServerError(e))?;
+
+  Ok(results.iter().map(card_result_from_row).collect())
+}
+
+/// Get visible cards by IDs database call. Calls the prepare_typed method to ensure our data
+/// types match the SQL types used in the statement.
+///
+/// Uses the `bb.get_visible_cards_by_ids(card_ids, user_id, include_unapproved)` method,
+/// which returns the cards of `bb.get_cards_by_ids(card_ids)` that have been approved,
+/// along with pending and rejected cards the user submitted.
+pub fn get_visible_cards_by_ids(
+  pool: &Pool,
+  query: &GetVisibleCards,
+) -> Result<Vec<GetCardResults>, AWError> {
+  let client = pool.clone();
+  let mut client = client.get().map_err(|e| ErrorInternalServerError(e))?;
+  let stmt = client
+    .prepare_typed(
+      "SELECT id, format_text, is_black, parent_set_id, parent_set_name, total_votes, average_rating, submitted_by_user_id, submitted_by_username, parent_set_ids, parent_set_names, pick, draw, score FROM bb.get_visible_cards_by_ids($1, $2, $3)",
+      &[Type::INT4_ARRAY, Type::INT4, Type::BOOL],
+    )
+    .map_err(|e| ErrorInternalServerError(e))?;
+
+  let results = client
+    .query(
+      &stmt,
+      &[&query.card_ids, &query.user_id, &query.include_unapproved],
+    )
+    .map_err(|e| ErrorInternalServerError(e))?;
+
+  Ok(results.iter().map(card_result_from_row).collect())
+}
+
+/// Get card rating breakdowns database call. Calls the prepare_typed method to ensure our data
+/// types match the SQL types used in the statement.
+///
+/// Uses the `bb.get_card_rating_breakdowns(card_ids)` method, which reads the
+/// `bb.v_card_ratings` view for all of the cards at once. Cards without ratings
+/// are not returned.
+pub fn get_card_rating_breakdowns(
+  pool: &Pool,
+  query: &[i32],
+) -> Result<Vec<GetCardRatingBreakdownResults>, AWError> {
+  let client = pool.clone();
+  let mut client = client.get().map_err(|e| ErrorInternalServerError(e))?;
+  let stmt = client
+    .prepare_typed(
+      "SELECT card_id, upvotes, downvotes, low_votes, high_votes, histogram FROM bb.get_card_rating_breakdowns($1)",
+      &[Type::INT4_ARRAY],
+    )
+    .map_err(|e| ErrorInternalServerError(e))?;
+
+  let results = client
+    .query(&stmt, &[&query])
+    .map_err(|e| ErrorInternalServerError(e))?;
+
+  Ok(
+    results
+      .iter()
+      .map(|r| GetCardRatingBreakdownResults {
+        card_id: r.get::<_, i32>(0),
+        upvotes: r.get::<_, i32>(1),
+        downvotes: r.get::<_, i32>(2),
+        low_votes: r.get::<_, Option<f32>>(3),
+        high_votes: r.get::<_, Option<f32>>(4),
+        histogram: r.get::<_, Vec<i32>>(5),
+      })
+      .collect(),
+  )
+}
+
+/// Get user submitted cards database call. Calls the prepare_typed method to ensure our data
+/// types match the SQL types used in the statement.
+///
+/// Uses the `bb.get_user_submitted_cards(user_id, previous_cursor, n_cards, include_unapproved)`
+/// method, paginated by card ID.
+pub fn get_user_submitted_cards(
+  pool: &Pool,
+  query: &GetUserSubmittedCards,
+) -> Result<Vec<GetCardResults>, AWError> {
+  let client = pool.clone();
+  let mut client = client.get().map_err(|e| ErrorInternalServerError(e))?;
+  let stmt = client
+    .prepare_typed(
+      "SELECT id, format_text, is_black, parent_set_id, parent_set_name, total_votes, average_rating, submitted_by_user_id, submitted_by_username, parent_set_ids, parent_set_names, pick, draw, score FROM bb.get_user_submitted_cards($1, $2, $3, $4)",
+      &[Type::INT4, Type::INT4, Type::INT4, Type::BOOL],
+    )
+    .map_err(|e| ErrorInternalServerError(e))?;
+
+  let results = client
+    .query(
+      &stmt,
+      &[
+        &query.user_id,
+        &query.cursor,
+        &query.n_results,
+        &query.include_unapproved,
+      ],
+    )
+    .map_err(|e| ErrorInternalServerError(e))?;
+
+  Ok(results.iter().map(card_result_from_row).collect())
+}
+
+/// Get user card ratings database call. Calls the prepare_typed method to ensure our data
+/// types match the SQL types used in the statement.
+///
+/// Uses the `bb.get_user_card_ratings(user_id, previous_cursor, n_ratings)` method,
+/// paginated by card ID. Only the IDs of the rated cards are returned; the cards
+/// themselves are loaded with `get_cards_by_ids()`.
+pub fn get_user_card_ratings(
+  pool: &Pool,
+  query: &GetUserHistory,
+) -> Result<Vec<GetUserCardRatingResults>, AWError> {
+  let client = pool.clone();
+  let mut client = client.get().map_err(|e| ErrorInternalServerError(e))?;
+  let stmt = client
+    .prepare_typed(
+      "SELECT card_id, rating FROM bb.get_user_card_ratings($1, $2, $3)",
+      &[Type::INT4, Type::INT4, Type::INT4],
+    )
+    .map_err(|e| ErrorInternalServerError(e))?;
+
+  let results = client
+    .query(&stmt, &[&query.user_id, &query.cursor, &query.n_results])
+    .map_err(|e| ErrorInternalServerError(e))?;
+
+  Ok(
+    results
+      .iter()
+      .map(|r| GetUserCardRatingResults {
+        card_id: r.get::<_, i32>(0),
+        rating: r.get::<_, f32>(1),
+      })
+      .collect(),
+  )
+}
+
+/// Get user card combination ratings database call. Calls the prepare_typed method to ensure our data
+/// types match the SQL types used in the statement.
+///
+/// Uses the `bb.get_user_card_combination_ratings(user_id, previous_black_card_id, previous_white_card_id, previous_ordinal, n_ratings)`
+/// method, paginated by `(black_card_id, white_card_id, ordinal)`. Only the IDs of the
+/// rated cards are returned; the cards themselves are loaded with `get_cards_by_ids()`.
+pub fn get_user_card_combination_ratings(
+  pool: &Pool,
+  query: &GetUserCardCombinationRatings,
+) -> Result<Vec<GetUserCardCombinationRatingResults>, AWError> {
+  let client = pool.clone();
+  let mut client = client.get().map_err(|e| ErrorInternalServerError(e))?;
+  let stmt = client
+    .prepare_typed(
+      "SELECT black_card_id, white_card_id, ordinal, rating FROM bb.get_user_card_combination_ratings($1, $2, $3, $4, $5)",
+      &[Type::INT4, Type::INT4, Type::INT4, Type::INT4, Type::INT4],
+    )
+    .map_err(|e| ErrorInternalServerError(e))?;
+
+  let results = client
+    .query(
+      &stmt,
+      &[
+        &query.user_id,
+        &query.cursor.map(|c| c.0),
+        &query.cursor.map(|c| c.1),
+        &query.cursor.map(|c| c.2),
+        &query.n_results,
+      ],
+    )
+    .map_err(|e| ErrorInternalServerError(e))?;
+
+  Ok(
+    results
+      .iter()
+      .map(|r| GetUserCardCombinationRatingResults {
+        black_card_id: r.get::<_, i32>(0),
+        white_card_id: r.get::<_, i32>(1),
+        ordinal: r.get::<_, i32>(2),
+        rating: r.get::<_, f32>(3),
+      })
+      .collect(),
+  )
+}
+
+/// Get API key database call. Calls the prepare_typed method to ensure our data
+/// types match the SQL types used in the statement.
+///
+/// Uses the `bb.get_api_key(key)` method. Like tokens, keys are only stored hashed.
+/// Unknown or revoked keys (as well as keys belonging to an inactive user) resolve
+/// to `None`.
+pub fn get_api_key(pool: &Pool, key: &str) -> Result<Option<GetApiKeyResults>, AWError> {
+  let client = pool.clone();
+  let mut client = client.get().map_err(|e| ErrorInternalServerError(e))?;
+  let stmt = client
+    .prepare_typed(
+      "SELECT id, user_id, scopes, is_admin FROM bb.get_api_key($1)",
+      &[Type::TEXT],
+    )
+    .map_err(|e| ErrorInternalServerError(e))?;
+
+  let results = client
+    .query(&stmt, &[&key])
+    .map_err(|e| ErrorInternalServerError(e))?;
+
+  Ok(results.first().map(|r| GetApiKeyResults {
+    id: r.get::<_, i32>(0),
+    user_id: r.get::<_, i32>(1),
+    scopes: r.get::<_, Vec<String>>(2),
+    is_admin: r.get::<_, bool>(3),
+  }))
+}
+
+/// Get user API keys database call. Calls the prepare_typed method to ensure our data
+/// types match the SQL types used in the statement.
+///
+/// Returns the active API keys of the user. The keys themselves are never returned,
+/// only what is needed to identify (and revoke) them.
+pub fn get_user_api_keys(pool: &Pool, user_id: i32) -> Result<Vec<GetUserApiKeyResults>, AWError> {
+  let client = pool.clone();
+  let mut client = client.get().map_err(|e| ErrorInternalServerError(e))?;
+  let stmt = client
+    .prepare_typed(
+      "SELECT id, name, scopes FROM bb.api_key WHERE user_id = $1 AND is_active = true ORDER BY id",
+      &[Type::INT4],
+    )
+    .map_err(|e| ErrorInternalServerError(e))?;
+
+  let results = client
+    .query(&stmt, &[&user_id])
+    .map_err(|e| ErrorInternalServerError(e))?;
+
+  Ok(
+    results
+      .iter()
+      .map(|r| GetUserApiKeyResults {
+        id: r.get::<_, i32>(0),
+        name: r.get::<_, String>(1),
+        scopes: r.get::<_, Vec<String>>(2),
+      })
+      .collect(),
+  )
+}
+
+/// Create API key database call. Calls the prepare_typed method to ensure our data
+/// types match the SQL types used in the statement.
+///
+/// Uses the `bb.create_api_key(key, user_id, name, scopes)` method, returning the ID
+/// of the new key. Only the hash of the key is persisted, so the caller is responsible
+/// for handing the raw key back to the user.
+pub fn create_api_key(pool: &Pool, query: &CreateApiKey) -> Result<i32, AWError> {
+  let client = pool.clone();
+  let mut client = client.get().map_err(|e| ErrorInternalServerError(e))?;
+  let stmt = client
+    .prepare_typed(
+      "SELECT bb.create_api_key($1, $2, $3, $4)",
+      &[Type::TEXT, Type::INT4, Type::TEXT, Type::TEXT_ARRAY],
+    )
+    .map_err(|e| ErrorInternalServerError(e))?;
+
+  let result = &client
+    .query(
+      &stmt,
+      &[&query.key, &query.user_id, &query.name, &query.scopes],
+    )
+    .map_err(|e| ErrorInternalServerError(e))?[0];
+
+  Ok(result.get::<_, i32>(0))
+}
+
+/// Revoke API key database call. Calls the prepare_typed method to ensure our data
+/// types match the SQL types used in the statement.
+///
+/// Uses the `bb.revoke_api_key(id, user_id)` method. Returns `false` when there was
+/// no active key (owned by the user) to revoke.
+pub fn revoke_api_key(pool: &Pool, query: &RevokeApiKey) -> Result<bool, AWError> {
+  let client = pool.clone();
+  let mut client = client.get().map_err(|e| ErrorInternalServerError(e))?;
+  let stmt = client
+    .prepare_typed(
+      "SELECT bb.revoke_api_key($1, $2)",
+      &[Type::INT4, Type::INT4],
+    )
+    .map_err(|e| ErrorInternalServerError(e))?;
+
+  let result = &client
+    .query(&stmt, &[&query.id, &query.user_id])
+    .map_err(|e| ErrorInternalServerError(e))?[0];
+
+  Ok(result.get::<_, bool>(0))
+}
+
+/// Get moderation queue database call. Calls the prepare_typed method to ensure our data
+/// types match the SQL types used in the statement.
+///
+/// Uses the `bb.get_moderation_queue(previous_cursor, n_cards)` method, returning
+/// the pending cards in the order they were submitted.
+pub fn get_moderation_queue(
+  pool: &Pool,
+  query: &GetModerationQueue,
+) -> Result<Vec<GetCardResults>, AWError> {
+  let client = pool.clone();
+  let mut client = client.get().map_err(|e| ErrorInternalServerError(e))?;
+  let stmt = client
+    .prepare_typed(
+      "SELECT id, format_text, is_black, parent_set_id, parent_set_name, total_votes, average_rating, submitted_by_user_id, submitted_by_username, parent_set_ids, parent_set_names, pick, draw, score FROM bb.get_moderation_queue($1, $2)",
+      &[Type::INT4, Type::INT4],
+    )
+    .map_err(|e| ErrorInternalServerError(e))?;
+
+  let results = client
+    .query(&stmt, &[&query.cursor, &query.n_results])
+    .map_err(|e| ErrorInternalServerError(e))?;
+
+  Ok(results.iter().map(card_result_from_row).collect())
+}
+
+/// Moderate card database call. Calls the prepare_typed method to ensure our data
+/// types match the SQL types used in the statement.
+///
+/// Uses the `bb.moderate_card(card_id, moderation_status, moderated_by_user_id, rejection_reason)`
+/// method, recording who moderated the card and when. Returns `false` when the card
+/// does not exist (or is inactive).
+pub fn moderate_card(pool: &Pool, query: &ModerateCard) -> Result<bool, AWError> {
+  let client = pool.clone();
+  let mut client = client.get().map_err(|e| ErrorInternalServerError(e))?;
+  let stmt = client
+    .prepare_typed(
+      "SELECT bb.moderate_card($1, $2, $3, $4)",
+      &[Type::INT4, Type::TEXT, Type::INT4, Type::TEXT],
+    )
+    .map_err(|e| ErrorInternalServerError(e))?;
+
+  let result = &client
+    .query(
+      &stmt,
+      &[
+        &query.card_id,
+        &query.moderation_status,
+        &query.moderated_by_user_id,
+        &query.rejection_reason,
+      ],
+    )
+    .map_err(|e| ErrorInternalServerError(e))?[0];
+
+  Ok(result.get::<_, bool>(0))
+}
+
+/// Get card moderations database call. Calls the prepare_typed method to ensure our data
+/// types match the SQL types used in the statement.
+///
+/// Uses the `bb.get_card_moderations(card_ids)` method. Unknown and inactive cards are
+/// left out of the results.
+pub fn get_card_moderations(
+  pool: &Pool,
+  query: &[i32],
+) -> Result<Vec<GetCardModerationResults>, AWError> {
+  let client = pool.clone();
+  let mut client = client.get().map_err(|e| ErrorInternalServerError(e))?;
+  let stmt = client
+    .prepare_typed(
+      "SELECT card_id, moderation_status, moderated_by_user_id, moderated_by_username, moderated_date, rejection_reason FROM bb.get_card_moderations($1)",
+      &[Type::INT4_ARRAY],
+    )
+    .map_err(|e| ErrorInternalServerError(e))?;
+
+  let results = client
+    .query(&stmt, &[&query])
+    .map_err(|e| ErrorInternalServerError(e))?;
+
+  Ok(
+    results
+      .iter()
+      .map(|r| GetCardModerationResults {
+        card_id: r.get::<_, i32>(0),
+        moderation_status: r.get::<_, String>(1),
+        moderated_by_user_id: r.get::<_, Option<i32>>(2),
+        moderated_by_username: r.get::<_, Option<String>>(3),
+        moderated_date: r.get::<_, Option<String>>(4),
+        rejection_reason: r.get::<_, Option<String>>(5),
+      })
+      .collect(),
+  )
+}
+
+/// Edit card database call. Calls the prepare_typed method to ensure our data
+/// types match the SQL types used in the statement.
+///
+/// Uses the `bb.edit_card(card_id, format_text, edited_by_user_id, resubmit)` method,
+/// recording the new wording as a revision of the card. When another card already
+/// uses the wording, nothing is changed and the ID of that card is returned.
+pub fn edit_card(pool: &Pool, query: &EditCard) -> Result<Option<i32>, AWError> {
+  let client = pool.clone();
+  let mut client = client.get().map_err(|e| ErrorInternalServerError(e))?;
+  let stmt = client
+    .prepare_typed(
+      "SELECT bb.edit_card($1, $2, $3, $4)",
+      &[Type::INT4, Type::TEXT, Type::INT4, Type::BOOL],
+    )
+    .map_err(|e| ErrorInternalServerError(e))?;
+
+  let result = &client
+    .query(
+      &stmt,
+      &[
+        &query.card_id,
+        &query.format_text,
+        &query.edited_by_user_id,
+        &query.resubmit,
+      ],
+    )
+    .map_err(|e| ErrorInternalServerError(e))?[0];
+
+  Ok(result.get::<_, Option<i32>>(0))
+}
+
+/// Deactivate card database call. Calls the prepare_typed method to ensure our data
+/// types match the SQL types used in the statement.
+///
+/// Uses the `bb.deactivate_card(card_id)` method. The card and its revisions and
+/// ratings are kept. Returns `false` when the card does not exist (or is inactive).
+pub fn deactivate_card(pool: &Pool, query: i32) -> Result<bool, AWError> {
+  let client = pool.clone();
+  let mut client = client.get().map_err(|e| ErrorInternalServerError(e))?;
+  let stmt = client
+    .prepare_typed("SELECT bb.deactivate_card($1)", &[Type::INT4])
+    .map_err(|e| ErrorInternalServerError(e))?;
+
+  let result = &client
+    .query(&stmt, &[&query])
+    .map_err(|e| ErrorInternalServerError(e))?[0];
+
+  Ok(result.get::<_, bool>(0))
+}
+
+/// Get card pairings database call. Calls the prepare_typed method to ensure our data
+/// types match the SQL types used in the statement.
+///
+/// Uses the `bb.get_card_pairings(black_card_id, white_card_id, ordinal, n_results)` method,
+/// which aggregates the combination ratings of a card by the card it was paired
+/// with and the blank it was played on, best pairings first.
+pub fn get_card_pairings(
+  pool: &Pool,
+  query: &GetCardPairings,
+) -> Result<Vec<GetCardPairingResults>, AWError> {
+  let client = pool.clone();
+  let mut client = client.get().map_err(|e| ErrorInternalServerError(e))?;
+  let stmt = client
+    .prepare_typed(
+      "SELECT black_card_id, white_card_id, ordinal, total_votes, average_rating FROM bb.get_card_pairings($1, $2, $3, $4)",
+      &[Type::INT4, Type::INT4, Type::INT4, Type::INT4],
+    )
+    .map_err(|e| ErrorInternalServerError(e))?;
+
+  let results = client
+    .query(
+      &stmt,
+      &[
+        &query.black_card_id,
+        &query.white_card_id,
+        &query.ordinal,
+        &query.n_results,
+      ],
+    )
+    .map_err(|e| ErrorInternalServerError(e))?;
+
+  Ok(
+    results
+      .iter()
+      .map(|r| GetCardPairingResults {
+        black_card_id: r.get::<_, i32>(0),
+        white_card_id: r.get::<_, i32>(1),
+        ordinal: r.get::<_, i32>(2),
+        total_votes: r.get::<_, i32>(3),
+        average_rating: r.get::<_, f32>(4),
+      })
+      .collect(),
+  )
+}
+
+/// Get card revisions database call. Calls the prepare_typed method to ensure our data
+/// types match the SQL types used in the statement.
+///
+/// Uses the `bb.get_card_revisions(card_id)` method, returning the revisions of an
+/// active card from oldest to newest.
+pub fn get_card_revisions(pool: &Pool, query: i32) -> Result<Vec<GetCardRevisionResults>, AWError> {
+  let client = pool.clone();
+  let mut client = client.get().map_err(|e| ErrorInternalServerError(e))?;
+  let stmt = client
+    .prepare_typed(
+      "SELECT revision, format_text, edited_by_user_id, edited_by_username, created_date, total_votes, average_rating FROM bb.get_card_revisions($1)",
+      &[Type::INT4],
+    )
+    .map_err(|e| ErrorInternalServerError(e))?;
+
+  let results = client
+    .query(&stmt, &[&query])
+    .map_err(|e| ErrorInternalServerError(e))?;
+
+  Ok(
+    results
+      .iter()
+      .map(|r| GetCardRevisionResults {
+        revision: r.get::<_, i32>(0),
+        format_text: r.get::<_, String>(1),
+        edited_by_user_id: r.get::<_, Option<i32>>(2),
+        edited_by_username: r.get::<_, Option<String>>(3),
+        created_date: r.get::<_, String>(4),
+        total_votes: r.get::<_, i32>(5),
+        average_rating: r.get::<_, Option<f32>>(6),
+      })
+      .collect(),
+  )
+}
+
+/// Report card database call. Calls the prepare_typed method to ensure our data
+/// types match the SQL types used in the statement.
+///
+/// Uses the `bb.report_card(card_id, user_id, reason, report_threshold)` method.
+/// The database deactivates the card once `report_threshold` distinct users have
+/// reported it. Returns `false` when the card does not exist (or is inactive).
+pub fn report_card(pool: &Pool, query: &ReportCard) -> Result<bool, AWError> {
+  let client = pool.clone();
+  let mut client = client.get().map_err(|e| ErrorInternalServerError(e))?;
+  let stmt = client
+    .prepare_typed(
+      "SELECT bb.report_card($1, $2, $3, $4)",
+      &[Type::INT4, Type::INT4, Type::TEXT, Type::INT4],
+    )
+    .map_err(|e| ErrorInternalServerError(e))?;
+
+  let result = &client
+    .query(
+      &stmt,
+      &[
+        &query.card_id,
+        &query.user_id,
+        &query.reason,
+        &query.report_threshold,
+      ],
+    )
+    .map_err(|e| ErrorInternalServerError(e))?[0];
+
+  Ok(result.get::<_, bool>(0))
+}
+
+/// Get card reports database call. Calls the prepare_typed method to ensure our data
+/// types match the SQL types used in the statement.
+///
+/// Uses the `bb.get_card_reports(previous_cursor, n_cards)` method, returning the
+/// reports grouped by card, paginated by card ID.
+pub fn get_card_reports(
+  pool: &Pool,
+  query: &GetCardReports,
+) -> Result<Vec<GetCardReportResults>, AWError> {
+  let client = pool.clone();
+  let mut client = client.get().map_err(|e| ErrorInternalServerError(e))?;
+  let stmt = client
+    .prepare_typed(
+      "SELECT card_id, format_text, is_black, is_active, reported_by_user_ids, reported_by_usernames, reasons, reported_dates FROM bb.get_card_reports($1, $2)",
+      &[Type::INT4, Type::INT4],
+    )
+    .map_err(|e| ErrorInternalServerError(e))?;
+
+  let results = client
+    .query(&stmt, &[&query.cursor, &query.n_results])
+    .map_err(|e| ErrorInternalServerError(e))?;
+
+  Ok(
+    results
+      .iter()
+      .map(|r| GetCardReportResults {
+        card_id: r.get::<_, i32>(0),
+        format_text: r.get::<_, String>(1),
+        is_black: r.get::<_, bool>(2),
+        is_active: r.get::<_, bool>(3),
+        reported_by_user_ids: r.get::<_, Vec<i32>>(4),
+        reported_by_usernames: r.get::<_, Vec<String>>(5),
+        reasons: r.get::<_, Vec<String>>(6),
+        reported_dates: r.get::<_, Vec<String>>(7),
+      })
+      .collect(),
+  )
+}
+
+/// Get active set database call. Calls the prepare_typed method to ensure our data
+/// types match the SQL types used in the statement.
+///
+/// `None` is returned for unknown or inactive sets, as well as for decks.
+pub fn get_active_set(pool: &Pool, query: i32) -> Result<Option<GetSetResults>, AWError> {
+  let client = pool.clone();
+  let mut client = client.get().map_err(|e| ErrorInternalServerError(e))?;
+  let stmt = client
+    .prepare_typed(
+      "SELECT id, name FROM bb.parent_set WHERE id = $1 AND is_active = true AND owner_user_id IS NULL",
+      &[Type::INT4],
+    )
+    .map_err(|e| ErrorInternalServerError(e))?;
+
+  let results = client
+    .query(&stmt, &[&query])
+    .map_err(|e| ErrorInternalServerError(e))?;
+
+  Ok(results.first().map(|r| GetSetResults {
+    id: r.get::<_, i32>(0),
+    name: r.get::<_, String>(1),
+  }))
+}
+
+/// Create set database call. Calls the prepare_typed method to ensure our data
+/// types match the SQL types used in the statement.
+///
+/// Uses the `bb.create_set(name)` method, returning the ID of the new set.
+/// `None` is returned when the name has already been taken.
+pub fn create_set(pool: &Pool, query: &CreateSet) -> Result<Option<i32>, AWError> {
+  let client = pool.clone();
+  let mut client = client.get().map_err(|e| ErrorInternalServerError(e))?;
+  let stmt = client
+    .prepare_typed("SELECT bb.create_set($1)", &[Type::TEXT])
+    .map_err(|e| ErrorInternalServerError(e))?;
+
+  let result = &client
+    .query(&stmt, &[&query.name])
+    .map_err(|e| ErrorInternalServerError(e))?[0];
+
+  Ok(result.get::<_, Option<i32>>(0))
+}
+
+/// Rename set database call. Calls the prepare_typed method to ensure our data
+/// types match the SQL types used in the statement.
+///
+/// Uses the `bb.rename_set(set_id, name)` method. Returns `false` when the name
+/// has already been taken by another set.
+pub fn rename_set(pool: &Pool, query: &RenameSet) -> Result<bool, AWError> {
+  let client = pool.clone();
+  let mut client = client.get().map_err(|e| ErrorInternalServerError(e))?;
+  let stmt = client
+    .prepare_typed("SELECT bb.rename_set($1, $2)", &[Type::INT4, Type::TEXT])
+    .map_err(|e| ErrorInternalServerError(e))?;
+
+  let result = &client
+    .query(&stmt, &[&query.set_id, &query.name])
+    .map_err(|e| ErrorInternalServerError(e))?[0];
+
+  Ok(result.get::<_, bool>(0))
+}
+
+/// Deactivate set database call. Calls the prepare_typed method to ensure our data
+/// types match the SQL types used in the statement.
+///
+/// Uses the `bb.deactivate_set(set_id)` method. Returns `false` when there was no
+/// active set to deactivate.
+pub fn deactivate_set(pool: &Pool, query: i32) -> Result<bool, AWError> {
+  let client = pool.clone();
+  let mut client = client.get().map_err(|e| ErrorInternalServerError(e))?;
+  let stmt = client
+    .prepare_typed("SELECT bb.deactivate_set($1)", &[Type::INT4])
+    .map_err(|e| ErrorInternalServerError(e))?;
+
+  let result = &client
+    .query(&stmt, &[&query])
+    .map_err(|e| ErrorInternalServerError(e))?[0];
+
+  Ok(result.get::<_, bool>(0))
+}
+
+/// Add cards to set database call. Calls the prepare_typed method to ensure our data
+/// types match the SQL types used in the statement.
+///
+/// Uses the `bb.add_cards_to_set(set_id, card_ids)` method. Cards that had been
+/// removed from the set are added back.
+pub fn add_cards_to_set(pool: &Pool, query: &UpdateSetCards) -> Result<(), AWError> {
+  let client = pool.clone();
+  let mut client = client.get().map_err(|e| ErrorInternalServerError(e))?;
+  let stmt = client
+    .prepare_typed(
+      "SELECT bb.add_cards_to_set($1, $2)",
+      &[Type::INT4, Type::INT4_ARRAY],
+    )
+    .map_err(|e| ErrorInternalServerError(e))?;
+
+  client
+    .execute(&stmt, &[&query.set_id, &query.card_ids])
+    .map_err(|e| ErrorInternalServerError(e))?;
+
+  Ok(())
+}
+
+/// Remove cards from set database call. Calls the prepare_typed method to ensure our data
+/// types match the SQL types used in the statement.
+///
+/// Uses the `bb.remove_cards_from_set(set_id, card_ids)` method. The cards are only
+/// deactivated in the set, not deleted.
+pub fn remove_cards_from_set(pool: &Pool, query: &UpdateSetCards) -> Result<(), AWError> {
+  let client = pool.clone();
+  let mut client = client.get().map_err(|e| ErrorInternalServerError(e))?;
+  let stmt = client
+    .prepare_typed(
+      "SELECT bb.remove_cards_from_set($1, $2)",
+      &[Type::INT4, Type::INT4_ARRAY],
+    )
+    .map_err(|e| ErrorInternalServerError(e))?;
+
+  client
+    .execute(&stmt, &[&query.set_id, &query.card_ids])
+    .map_err(|e| ErrorInternalServerError(e))?;
+
+  Ok(())
+}
+
+fn deck_result_from_row(r: &Row) -> GetDeckResults {
+  GetDeckResults {
+    id: r.get::<_, i32>(0),
+    name: r.get::<_, String>(1),
+    visibility: r.get::<_, String>(2),
+    owner_user_id: r.get::<_, i32>(3),
+    owner_username: r.get::<_, String>(4),
+  }
+}
+
+/// Get decks by IDs database call. Calls the prepare_typed method to ensure our data
+/// types match the SQL types used in the statement.
+///
+/// Uses the `bb.get_decks_by_ids(deck_ids)` method. Only active decks are returned,
+/// ordered by ID; IDs of official sets are ignored.
+pub fn get_decks_by_ids(pool: &Pool, query: &[i32]) -> Result<Vec<GetDeckResults>, AWError> {
+  let client = pool.clone();
+  let mut client = client.get().map_err(|e| ErrorInternalServerError(e))?;
+  let stmt = client
+    .prepare_typed(
+      "SELECT id, name, visibility, owner_user_id, owner_username FROM bb.get_decks_by_ids($1)",
+      &[Type::INT4_ARRAY],
+    )
+    .map_err(|e| ErrorInternalServerError(e))?;
+
+  let results = client
+    .query(&stmt, &[&query])
+    .map_err(|e| ErrorInternalServerError(e))?;
+
+  Ok(results.iter().map(deck_result_from_row).collect())
+}
+
+/// Get user decks database call. Calls the prepare_typed method to ensure our data
+/// types match the SQL types used in the statement.
+///
+/// Uses the `bb.get_user_decks(user_id, previous_cursor, n_decks, include_unlisted)`
+/// method, paginated by deck ID.
+pub fn get_user_decks(pool: &Pool, query: &GetUserDecks) -> Result<Vec<GetDeckResults>, AWError> {
+  let client = pool.clone();
+  let mut client = client.get().map_err(|e| ErrorInternalServerError(e))?;
+  let stmt = client
+    .prepare_typed(
+      "SELECT id, name, visibility, owner_user_id, owner_username FROM bb.get_user_decks($1, $2, $3, $4)",
+      &[Type::INT4, Type::INT4, Type::INT4, Type::BOOL],
+    )
+    .map_err(|e| ErrorInternalServerError(e))?;
+
+  let results = client
+    .query(
+      &stmt,
+      &[
+        &query.user_id,
+        &query.cursor,
+        &query.n_results,
+        &query.include_unlisted,
+      ],
+    )
+    .map_err(|e| ErrorInternalServerError(e))?;
+
+  Ok(results.iter().map(deck_result_from_row).collect())
+}
+
+/// Create deck database call. Calls the prepare_typed method to ensure our data
+/// types match the SQL types used in the statement.
+///
+/// Uses the `bb.create_deck(owner_user_id, name, visibility)` method, returning
+/// the ID of the new deck.
+pub fn create_deck(pool: &Pool, query: &CreateDeck) -> Result<i32, AWError> {
+  let client = pool.clone();
+  let mut client = client.get().map_err(|e| ErrorInternalServerError(e))?;
+  let stmt = client
+    .prepare_typed(
+      "SELECT bb.create_deck($1, $2, $3)",
+      &[Type::INT4, Type::TEXT, Type::TEXT],
+    )
+    .map_err(|e| ErrorInternalServerError(e))?;
+
+  let result = &client
+    .query(&stmt, &[&query.user_id, &query.name, &query.visibility])
+    .map_err(|e| ErrorInternalServerError(e))?[0];
+
+  Ok(result.get::<_, i32>(0))
+}
+
+/// Update deck database call. Calls the prepare_typed method to ensure our data
+/// types match the SQL types used in the statement.
+///
+/// Uses the `bb.update_deck(deck_id, name, visibility)` method.
+pub fn update_deck(pool: &Pool, query: &UpdateDeck) -> Result<(), AWError> {
+  let client = pool.clone();
+  let mut client = client.get().map_err(|e| ErrorInternalServerError(e))?;
+  let stmt = client
+    .prepare_typed(
+      "SELECT bb.update_deck($1, $2, $3)",
+      &[Type::INT4, Type::TEXT, Type::TEXT],
+    )
+    .map_err(|e| ErrorInternalServerError(e))?;
+
+  client
+    .execute(&stmt, &[&query.deck_id, &query.name, &query.visibility])
+    .map_err(|e| ErrorInternalServerError(e))?;
+
+  Ok(())
+}
+
+/// Get card counts database call. Calls the prepare_typed method to ensure our data
+/// types match the SQL types used in the statement.
+///
+/// Uses the `bb.get_card_counts()` method, counting the active, approved cards
+/// by color and by whether they were submitted by a user.
+pub fn get_card_counts(pool: &Pool) -> Result<Vec<GetCardCountResults>, AWError> {
+  let client = pool.clone();
+  let mut client = client.get().map_err(|e| ErrorInternalServerError(e))?;
+  let stmt = client
+    .prepare_typed(
+      "SELECT is_black, user_submitted, n_cards FROM bb.get_card_counts()",
+      &[],
+    )
+    .map_err(|e| ErrorInternalServerError(e))?;
+
+  let results = client
+    .query(&stmt, &[])
+    .map_err(|e| ErrorInternalServerError(e))?;
+
+  Ok(
+    results
+      .iter()
+      .map(|r| GetCardCountResults {
+        is_black: r.get::<_, bool>(0),
+        user_submitted: r.get::<_, bool>(1),
+        n_cards: r.get::<_, i32>(2),
+      })
+      .collect(),
+  )
+}
+
+/// Get active set count database call. Calls the prepare_typed method to ensure our data
+/// types match the SQL types used in the statement.
+///
+/// Uses the `bb.get_active_set_count()` method. Decks owned by users are not counted.
+pub fn get_active_set_count(pool: &Pool) -> Result<i32, AWError> {
+  let client = pool.clone();
+  let mut client = client.get().map_err(|e| ErrorInternalServerError(e))?;
+  let stmt = client
+    .prepare_typed("SELECT bb.get_active_set_count()", &[])
+    .map_err(|e| ErrorInternalServerError(e))?;
+
+  let result = &client
+    .query(&stmt, &[])
+    .map_err(|e| ErrorInternalServerError(e))?[0];
+
+  Ok(result.get::<_, i32>(0))
+}
+
+/// Get rating volume database call. Calls the prepare_typed method to ensure our data
+/// types match the SQL types used in the statement.
+///
+/// Uses the `bb.get_rating_volume(interval, n_buckets)` method, returning the number
+/// of active ratings (and raters) of each of the last `n_buckets` days, weeks, or
+/// months, oldest first.
+pub fn get_rating_volume(
+  pool: &Pool,
+  query: &GetRatingVolume,
+) -> Result<Vec<GetRatingVolumeResults>, AWError> {
+  let client = pool.clone();
+  let mut client = client.get().map_err(|e| ErrorInternalServerError(e))?;
+  let stmt = client
+    .prepare_typed(
+      "SELECT bucket_start, n_ratings, n_raters FROM bb.get_rating_volume($1, $2)",
+      &[Type::TEXT, Type::INT4],
+    )
+    .map_err(|e| ErrorInternalServerError(e))?;
+
+  let results = client
+    .query(&stmt, &[&query.interval, &query.n_buckets])
+    .map_err(|e| ErrorInternalServerError(e))?;
+
+  Ok(
+    results
+      .iter()
+      .map(|r| GetRatingVolumeResults {
+        bucket_start: r.get::<_, String>(0),
+        n_ratings: r.get::<_, i32>(1),
+        n_raters: r.get::<_, i32>(2),
+      })
+      .collect(),
+  )
+}
+
+/// Get top raters database call. Calls the prepare_typed method to ensure our data
+/// types match the SQL types used in the statement.
+///
+/// Uses the `bb.get_top_raters(n_results)` method, returning the users with the
+/// most active card ratings.
+pub fn get_top_raters(pool: &Pool, query: i32) -> Result<Vec<GetTopRaterResults>, AWError> {
+  let client = pool.clone();
+  let mut client = client.get().map_err(|e| ErrorInternalServerError(e))?;
+  let stmt = client
+    .prepare_typed(
+      "SELECT user_id, username, n_ratings, last_rated_date FROM bb.get_top_raters($1)",
+      &[Type::INT4],
+    )
+    .map_err(|e| ErrorInternalServerError(e))?;
+
+  let results = client
+    .query(&stmt, &[&query])
+    .map_err(|e| ErrorInternalServerError(e))?;
+
+  Ok(
+    results
+      .iter()
+      .map(|r| GetTopRaterResults {
+        user_id: r.get::<_, i32>(0),
+        username: r.get::<_, String>(1),
+        n_ratings: r.get::<_, i32>(2),
+        last_rated_date: r.get::<_, String>(3),
+      })
+      .collect(),
+  )
+}
+
+/// Get rating distribution database call. Calls the prepare_typed method to ensure our data
+/// types match the SQL types used in the statement.
+///
+/// Uses the `bb.get_rating_distribution(n_buckets)` method, a histogram of the
+/// average rating of the active cards with votes. Every bucket is returned,
+/// including empty ones.
+pub fn get_rating_distribution(
+  pool: &Pool,
+  query: i32,
+) -> Result<Vec<GetRatingDistributionResults>, AWError> {
+  let client = pool.clone();
+  let mut client = client.get().map_err(|e| ErrorInternalServerError(e))?;
+  let stmt = client
+    .prepare_typed(
+      "SELECT bucket, n_cards FROM bb.get_rating_distribution($1)",
+      &[Type::INT4],
+    )
+    .map_err(|e| ErrorInternalServerError(e))?;
+
+  let results = client
+    .query(&stmt, &[&query])
+    .map_err(|e| ErrorInternalServerError(e))?;
+
+  Ok(
+    results
+      .iter()
+      .map(|r| GetRatingDistributionResults {
+        bucket: r.get::<_, i32>(0),
+        n_cards: r.get::<_, i32>(1),
+      })
+      .collect(),
+  )
+}
+
+/// Run time-deck job database call. Calls the prepare_typed method to ensure our data
+/// types match the SQL types used in the statement.
+///
+/// Uses the `bb.run_time_deck_job(period, offset_hours, top_n_cards)` method, which
+/// generates the time-deck of the current period under an advisory lock. Returns
+/// `false` when the time-deck was already generated, or another replica is
+/// generating it.
+pub fn run_time_deck_job(pool: &Pool, query: &RunTimeDeckJob) -> Result<bool, AWError> {
+  let client = pool.clone();
+  let mut client = client.get().map_err(|e| ErrorInternalServerError(e))?;
+  let stmt = client
+    .prepare_typed(
+      "SELECT bb.run_time_deck_job($1, $2, $3)",
+      &[Type::TEXT, Type::INT4, Type::INT4],
+    )
+    .map_err(|e| ErrorInternalServerError(e))?;
+
+  let result = &client
+    .query(
+      &stmt,
+      &[&query.period, &query.offset_hours, &query.top_n_cards],
+    )
+    .map_err(|e| ErrorInternalServerError(e))?[0];
+
+  Ok(result.get::<_, bool>(0))
+}
+
+fn time_deck_result_from_row(r: &Row) -> GetTimeDeckResults {
+  GetTimeDeckResults {
+    id: r.get::<_, i32>(0),
+    name: r.get::<_, String>(1),
+    period: r.get::<_, String>(2),
+    start_date: r.get::<_, String>(3),
+    end_date: r.get::<_, String>(4),
+    start_day: r.get::<_, i32>(5),
+  }
+}
+
+/// Get time-decks database call. Calls the prepare_typed method to ensure our data
+/// types match the SQL types used in the statement.
+///
+/// Uses the `bb.get_time_decks(period, previous_start_day, previous_id, n_results)`
+/// method, returning the active time-decks of a period (or of every period when
+/// `None`), most recent first.
+pub fn get_time_decks(
+  pool: &Pool,
+  query: &GetTimeDecks,
+) -> Result<Vec<GetTimeDeckResults>, AWError> {
+  let client = pool.clone();
+  let mut client = client.get().map_err(|e| ErrorInternalServerError(e))?;
+  let stmt = client
+    .prepare_typed(
+      "SELECT id, name, period, start_date, end_date, start_day FROM bb.get_time_decks($1, $2, $3, $4)",
+      &[Type::TEXT, Type::INT4, Type::INT4, Type::INT4],
+    )
+    .map_err(|e| ErrorInternalServerError(e))?;
+
+  let results = client
+    .query(
+      &stmt,
+      &[
+        &query.period,
+        &query.cursor.map(|c| c.0),
+        &query.cursor.map(|c| c.1),
+        &query.n_results,
+      ],
+    )
+    .map_err(|e| ErrorInternalServerError(e))?;
+
+  Ok(results.iter().map(time_deck_result_from_row).collect())
+}
+
+/// Get time-deck database call. Calls the prepare_typed method to ensure our data
+/// types match the SQL types used in the statement.
+///
+/// Uses the `bb.get_time_deck(period, date)` method, returning the active time-deck
+/// of the period containing the date, if it was generated. The date must be valid.
+pub fn get_time_deck(
+  pool: &Pool,
+  query: &GetTimeDeck,
+) -> Result<Option<GetTimeDeckResults>, AWError> {
+  let client = pool.clone();
+  let mut client = client.get().map_err(|e| ErrorInternalServerError(e))?;
+  let stmt = client
+    .prepare_typed(
+      "SELECT id, name, period, start_date, end_date, start_day FROM bb.get_time_deck($1, $2::DATE)",
+      &[Type::TEXT, Type::TEXT],
+    )
+    .map_err(|e| ErrorInternalServerError(e))?;
+
+  let results = client
+    .query(&stmt, &[&query.period, &query.date])
+    .map_err(|e| ErrorInternalServerError(e))?;
+
+  Ok(results.first().map(time_deck_result_from_row))
+}
+
+/// Generate time-deck database call. Calls the prepare_typed method to ensure our data
+/// types match the SQL types used in the statement.
+///
+/// Uses the `bb.generate_time_deck(period, date, offset_hours, top_n_cards, replace_existing)` method,
+/// returning the ID of the set of the time-deck. `None` is returned when the period
+/// already has a time-deck and `replace` is `false`, or when another set already
+/// has the name of the time-deck. The date must be valid, and its period must have
+/// ended (see `time_deck_period_ended`). Cards are ranked by their current ratings.
+pub fn generate_time_deck(pool: &Pool, query: &GenerateTimeDeck) -> Result<Option<i32>, AWError> {
+  let client = pool.clone();
+  let mut client = client.get().map_err(|e| ErrorInternalServerError(e))?;
+  let stmt = client
+    .prepare_typed(
+      "SELECT bb.generate_time_deck($1, $2::DATE, $3, $4, $5)",
+      &[Type::TEXT, Type::TEXT, Type::INT4, Type::INT4, Type::BOOL],
+    )
+    .map_err(|e| ErrorInternalServerError(e))?;
+
+  let result = &client
+    .query(
+      &stmt,
+      &[
+        &query.period,
+        &query.date,
+        &query.offset_hours,
+        &query.top_n_cards,
+        &query.replace,
+      ],
+    )
+    .map_err(|e| ErrorInternalServerError(e))?[0];
+
+  Ok(result.get::<_, Option<i32>>(0))
+}
+
+/// Time-deck period ended database call. Calls the prepare_typed method to ensure
+/// our data types match the SQL types used in the statement.
+///
+/// Uses the `bb.time_deck_period_ended(period, date, offset_hours)` method, returning
+/// whether the period containing the date, starting `offset_hours` after midnight
+/// (UTC), has ended. The date must be valid.
+pub fn time_deck_period_ended(pool: &Pool, query: &TimeDeckPeriodEnded) -> Result<bool, AWError> {
+  let client = pool.clone();
+  let mut client = client.get().map_err(|e| ErrorInternalServerError(e))?;
+  let stmt = client
+    .prepare_typed(
+      "SELECT bb.time_deck_period_ended($1, $2::DATE, $3)",
+      &[Type::TEXT, Type::TEXT, Type::INT4],
+    )
+    .map_err(|e| ErrorInternalServerError(e))?;
+
+  let result = &client
+    .query(&stmt, &[&query.period, &query.date, &query.offset_hours])
+    .map_err(|e| ErrorInternalServerError(e))?[0];
+
+  Ok(result.get::<_, bool>(0))
+}
+
+/// Create game database call. Calls the prepare_typed method to ensure our data
+/// types match the SQL types used in the statement.
+///
+/// Uses the `bb.create_game(owner_user_id, card_sets, random_seed, hand_size)` method,
+/// returning the ID of the new game. The owner joins the game.
+pub fn create_game(pool: &Pool, query: &CreateGame) -> Result<i32, AWError> {
+  let client = pool.clone();
+  let mut client = client.get().map_err(|e| ErrorInternalServerError(e))?;
+  let stmt = client
+    .prepare_typed(
+      "SELECT bb.create_game($1, $2, $3, $4)",
+      &[Type::INT4, Type::INT4_ARRAY, Type::FLOAT4, Type::INT4],
+    )
+    .map_err(|e| ErrorInternalServerError(e))?;
+
+  let result = &client
+    .query(
+      &stmt,
+      &[
+        &query.user_id,
+        &query.card_sets,
+        &query.random_seed,
+        &query.hand_size,
+      ],
+    )
+    .map_err(|e| ErrorInternalServerError(e))?[0];
+
+  Ok(result.get::<_, i32>(0))
+}
+
+/// Join game database call. Calls the prepare_typed method to ensure our data
+/// types match the SQL types used in the statement.
+///
+/// Uses the `bb.join_game(game_id, user_id)` method, returning the reason the
+/// user could not join, or `None` once the user has joined.
+pub fn join_game(pool: &Pool, query: &GameMove) -> Result<Option<String>, AWError> {
+  let client = pool.clone();
+  let mut client = client.get().map_err(|e| ErrorInternalServerError(e))?;
+  let stmt = client
+    .prepare_typed("SELECT bb.join_game($1, $2)", &[Type::INT4, Type::INT4])
+    .map_err(|e| ErrorInternalServerError(e))?;
+
+  let result = &client
+    .query(&stmt, &[&query.game_id, &query.user_id])
+    .map_err(|e| ErrorInternalServerError(e))?[0];
+
+  Ok(result.get::<_, Option<String>>(0))
+}
+
+/// Deal game hands database call. Calls the prepare_typed method to ensure our data
+/// types match the SQL types used in the statement.
+///
+/// Uses the `bb.deal_game_hands(game_id, user_id)` method, starting the first
+/// round. Returns the reason the hands could not be dealt, or `None` once dealt.
+pub fn deal_game_hands(pool: &Pool, query: &GameMove) -> Result<Option<String>, AWError> {
+  let client = pool.clone();
+  let mut client = client.get().map_err(|e| ErrorInternalServerError(e))?;
+  let stmt = client
+    .prepare_typed(
+      "SELECT bb.deal_game_hands($1, $2)",
+      &[Type::INT4, Type::INT4],
+    )
+    .map_err(|e| ErrorInternalServerError(e))?;
+
+  let result = &client
+    .query(&stmt, &[&query.game_id, &query.user_id])
+    .map_err(|e| ErrorInternalServerError(e))?[0];
+
+  Ok(result.get::<_, Option<String>>(0))
+}
+
+/// Play game cards database call. Calls the prepare_typed method to ensure our data
+/// types match the SQL types used in the statement.
+///
+/// Uses the `bb.play_game_cards(game_id, user_id, card_ids)` method, returning the
+/// reason the cards could not be played, or `None` once played.
+pub fn play_game_cards(pool: &Pool, query: &PlayGameCards) -> Result<Option<String>, AWError> {
+  let client = pool.clone();
+  let mut client = client.get().map_err(|e| ErrorInternalServerError(e))?;
+  let stmt = client
+    .prepare_typed(
+      "SELECT bb.play_game_cards($1, $2, $3)",
+      &[Type::INT4, Type::INT4, Type::INT4_ARRAY],
+    )
+    .map_err(|e| ErrorInternalServerError(e))?;
+
+  let result = &client
+    .query(&stmt, &[&query.game_id, &query.user_id, &query.card_ids])
+    .map_err(|e| ErrorInternalServerError(e))?[0];
+
+  Ok(result.get::<_, Option<String>>(0))
+}
+
+/// Pick game winner database call. Calls the prepare_typed method to ensure our data
+/// types match the SQL types used in the statement.
+///
+/// Uses the `bb.pick_game_winner(game_id, user_id, play)` method, returning the
+/// reason the winner could not be picked, or `None` once picked.
+pub fn pick_game_winner(pool: &Pool, query: &PickGameWinner) -> Result<Option<String>, AWError> {
+  let client = pool.clone();
+  let mut client = client.get().map_err(|e| ErrorInternalServerError(e))?;
+  let stmt = client
+    .prepare_typed(
+      "SELECT bb.pick_game_winner($1, $2, $3)",
+      &[Type::INT4, Type::INT4, Type::INT4],
+    )
+    .map_err(|e| ErrorInternalServerError(e))?;
+
+  let result = &client
+    .query(&stmt, &[&query.game_id, &query.user_id, &query.play])
+    .map_err(|e| ErrorInternalServerError(e))?[0];
+
+  Ok(result.get::<_, Option<String>>(0))
+}
+
+/// Next game round database call. Calls the prepare_typed method to ensure our data
+/// types match the SQL types used in the statement.
+///
+/// Uses the `bb.next_game_round(game_id, user_id)` method, returning the reason
+/// the round could not be started, or `None` once started (or the game finished).
+pub fn next_game_round(pool: &Pool, query: &GameMove) -> Result<Option<String>, AWError> {
+  let client = pool.clone();
+  let mut client = client.get().map_err(|e| ErrorInternalServerError(e))?;
+  let stmt = client
+    .prepare_typed(
+      "SELECT bb.next_game_round($1, $2)",
+      &[Type::INT4, Type::INT4],
+    )
+    .map_err(|e| ErrorInternalServerError(e))?;
+
+  let result = &client
+    .query(&stmt, &[&query.game_id, &query.user_id])
+    .map_err(|e| ErrorInternalServerError(e))?[0];
+
+  Ok(result.get::<_, Option<String>>(0))
+}
+
+/// Get game database call. Calls the prepare_typed method to ensure our data
+/// types match the SQL types used in the statement.
+///
+/// Uses the `bb.get_game(game_id)` method, along with its current round.
+pub fn get_game(pool: &Pool, query: i32) -> Result<Option<GetGameResults>, AWError> {
+  let client = pool.clone();
+  let mut client = client.get().map_err(|e| ErrorInternalServerError(e))?;
+  let stmt = client
+    .prepare_typed(
+      "SELECT id, owner_user_id, owner_username, card_sets, random_seed, hand_size, status, round, czar_player_id, black_card_id, winner_player_id FROM bb.get_game($1)",
+      &[Type::INT4],
+    )
+    .map_err(|e| ErrorInternalServerError(e))?;
+
+  let results = client
+    .query(&stmt, &[&query])
+    .map_err(|e| ErrorInternalServerError(e))?;
+
+  Ok(results.first().map(|r| GetGameResults {
+    id: r.get::<_, i32>(0),
+    owner_user_id: r.get::<_, i32>(1),
+    owner_username: r.get::<_, String>(2),
+    card_sets: r.get::<_, Vec<i32>>(3),
+    random_seed: r.get::<_, f32>(4),
+    hand_size: r.get::<_, i32>(5),
+    status: r.get::<_, String>(6),
+    round: r.get::<_, i32>(7),
+    czar_player_id: r.get::<_, Option<i32>>(8),
+    black_card_id: r.get::<_, Option<i32>>(9),
+    winner_player_id: r.get::<_, Option<i32>>(10),
+  }))
+}
+
+/// Get game players database call. Calls the prepare_typed method to ensure our data
+/// types match the SQL types used in the statement.
+///
+/// Uses the `bb.get_game_players(game_id)` method, ordered by seat.
+pub fn get_game_players(pool: &Pool, query: i32) -> Result<Vec<GetGamePlayerResults>, AWError> {
+  let client = pool.clone();
+  let mut client = client.get().map_err(|e| ErrorInternalServerError(e))?;
+  let stmt = client
+    .prepare_typed(
+      "SELECT id, user_id, username, score, n_cards, has_played FROM bb.get_game_players($1)",
+      &[Type::INT4],
+    )
+    .map_err(|e| ErrorInternalServerError(e))?;
+
+  let results = client
+    .query(&stmt, &[&query])
+    .map_err(|e| ErrorInternalServerError(e))?;
+
+  Ok(
+    results
+      .iter()
+      .map(|r| GetGamePlayerResults {
+        id: r.get::<_, i32>(0),
+        user_id: r.get::<_, i32>(1),
+        username: r.get::<_, String>(2),
+        score: r.get::<_, i32>(3),
+        n_cards: r.get::<_, i32>(4),
+        has_played: r.get::<_, bool>(5),
+      })
+      .collect(),
+  )
+}
+
+/// Get game plays database call. Calls the prepare_typed method to ensure our data
+/// types match the SQL types used in the statement.
+///
+/// Uses the `bb.get_game_plays(game_id)` method, returning the plays of the
+/// current round in shuffled order.
+pub fn get_game_plays(pool: &Pool, query: i32) -> Result<Vec<GetGamePlayResults>, AWError> {
+  let client = pool.clone();
+  let mut client = client.get().map_err(|e| ErrorInternalServerError(e))?;
+  let stmt = client
+    .prepare_typed(
+      "SELECT player_id, card_ids FROM bb.get_game_plays($1)",
+      &[Type::INT4],
+    )
+    .map_err(|e| ErrorInternalServerError(e))?;
+
+  let results = client
+    .query(&stmt, &[&query])
+    .map_err(|e| ErrorInternalServerError(e))?;
+
+  Ok(
+    results
+      .iter()
+      .map(|r| GetGamePlayResults {
+        player_id: r.get::<_, i32>(0),
+        card_ids: r.get::<_, Vec<i32>>(1),
+      })
+      .collect(),
+  )
+}
+
+/// Get game hand database call. Calls the prepare_typed method to ensure our data
+/// types match the SQL types used in the statement.
+///
+/// Uses the `bb.get_game_hand(game_id, user_id)` method, returning the IDs of the
+/// cards held by the user, in the order they were drawn.
+pub fn get_game_hand(pool: &Pool, query: &GameMove) -> Result<Vec<i32>, AWError> {
+  let client = pool.clone();
+  let mut client = client.get().map_err(|e| ErrorInternalServerError(e))?;
+  let stmt = client
+    .prepare_typed(
+      "SELECT card_id FROM bb.get_game_hand($1, $2)",
+      &[Type::INT4, Type::INT4],
+    )
+    .map_err(|e| ErrorInternalServerError(e))?;
+
+  let results = client
+    .query(&stmt, &[&query.game_id, &query.user_id])
+    .map_err(|e| ErrorInternalServerError(e))?;
+
+  Ok(results.iter().map(|r| r.get::<_, i32>(0)).collect())
+}
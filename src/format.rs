@@ -0,0 +1,420 @@
+use unicode_normalization::UnicodeNormalization;
+
+/// A piece of a card's `format_text`
+#[derive(Debug, Clone, PartialEq)]
+pub enum Segment {
+  Text(String),
+  /// A `<prompt/>`, numbered from 0 in the order they appear
+  Blank(i32),
+  Emphasis(String),
+}
+
+/// Placeholder used for blanks in plain text
+const BLANK: &str = "_____";
+
+/// The only accepted spelling of a blank in submitted cards
+const PROMPT: &str = "<prompt/>";
+
+/// Normalizes submitted format text. Unicode is composed (NFC), control
+/// characters are dropped, and runs of whitespace are collapsed into a single
+/// space. Line breaks are written as `<br>`, so whitespace is never meaningful.
+pub fn normalize(format_text: &str) -> String {
+  format_text
+    .nfc()
+    .filter(|c| c.is_whitespace() || !c.is_control())
+    .collect::<String>()
+    .split_whitespace()
+    .collect::<Vec<_>>()
+    .join(" ")
+}
+
+/// Counts the blanks of format text. Returns `None` when a prompt tag is not
+/// written exactly as `<prompt/>` (`<prompt>`, `</prompt>`, `<Prompt />`, or a
+/// `<prompt` that is never closed).
+pub fn count_prompts(format_text: &str) -> Option<usize> {
+  let mut prompts = 0;
+
+  for (i, _) in format_text.match_indices('<') {
+    let rest = &format_text[i..];
+    let tag = match rest.find('>') {
+      Some(end) => &rest[..=end],
+      None => rest,
+    };
+    let name = tag
+      .trim_start_matches('<')
+      .trim_end_matches('>')
+      .trim()
+      .trim_matches('/')
+      .trim()
+      .to_lowercase();
+
+    if !name.starts_with("prompt") {
+      continue;
+    }
+    if tag != PROMPT {
+      return None;
+    }
+    prompts += 1;
+  }
+
+  Some(prompts)
+}
+
+/// Parses the format text of a card into segments.
+///
+/// Format text contains `<prompt/>` tags for blanks, `<br>` tags for line breaks
+/// (parsed as `'\n'`), and emphasis written as either `*text*` or with the
+/// `<i>`, `<em>`, `<b>`, and `<strong>` tags, which may be nested. Asterisks that
+/// do not wrap a word (`f**k`), and any other tags, are kept as text.
+pub fn parse(format_text: &str) -> Vec<Segment> {
+  let chars = format_text.chars().collect::<Vec<_>>();
+  let mut segments = Segments::default();
+  let mut blanks = 0;
+  let mut star_close: Option<usize> = None;
+  let mut tag_depth: usize = 0;
+  let mut i = 0;
+
+  while i < chars.len() {
+    match chars[i] {
+      '<' => {
+        let end = match chars[i..].iter().position(|&c| c == '>') {
+          Some(end) => i + end,
+          None => {
+            segments.push('<');
+            i += 1;
+            continue;
+          }
+        };
+        let tag = chars[i + 1..end]
+          .iter()
+          .collect::<String>()
+          .trim()
+          .trim_end_matches('/')
+          .trim()
+          .to_lowercase();
+
+        match tag.as_str() {
+          "prompt" => {
+            segments.blank(blanks);
+            blanks += 1;
+          }
+          "br" => segments.push('\n'),
+          "i" | "em" | "b" | "strong" => tag_depth += 1,
+          "/i" | "/em" | "/b" | "/strong" => tag_depth = tag_depth.saturating_sub(1),
+          _ => chars[i..=end].iter().for_each(|&c| segments.push(c)),
+        }
+        segments.emphasis = tag_depth > 0 || star_close.is_some();
+        i = end + 1;
+      }
+      '*' if star_close == Some(i) => {
+        segments.emphasis = tag_depth > 0;
+        star_close = None;
+        i += 1;
+      }
+      '*' if star_close.is_none() && !segments.emphasis => match closing_star(&chars, i) {
+        Some(close) => {
+          segments.emphasis = true;
+          star_close = Some(close);
+          i += 1;
+        }
+        None => {
+          segments.push('*');
+          i += 1;
+        }
+      },
+      '&' => {
+        let entity = ENTITIES
+          .iter()
+          .find(|(name, _)| chars[i..].starts_with(&name.chars().collect::<Vec<_>>()));
+        match entity {
+          Some((name, c)) => {
+            segments.push(*c);
+            i += name.chars().count();
+          }
+          None => {
+            segments.push('&');
+            i += 1;
+          }
+        }
+      }
+      c => {
+        segments.push(c);
+        i += 1;
+      }
+    }
+  }
+
+  segments.finish()
+}
+
+/// The card as plain text, with blanks written as `_____`
+pub fn plain_text(segments: &[Segment]) -> String {
+  segments
+    .iter()
+    .map(|s| match s {
+      Segment::Text(t) | Segment::Emphasis(t) => t.as_str(),
+      Segment::Blank(_) => BLANK,
+    })
+    .collect()
+}
+
+/// The card as plain text, with each blank filled in by the plain text of a
+/// white card. Black cards without blanks are answered at the end of the card.
+/// A trailing period of a fill is dropped, as the black card provides its own
+/// punctuation.
+pub fn render(segments: &[Segment], fills: &[String]) -> String {
+  let fills = fills
+    .iter()
+    .map(|f| f.trim().trim_end_matches('.'))
+    .collect::<Vec<_>>();
+
+  let has_blanks = segments.iter().any(|s| matches!(s, Segment::Blank(_)));
+
+  if !has_blanks {
+    let mut rendered = plain_text(segments);
+    for fill in fills {
+      rendered.push(' ');
+      rendered.push_str(fill);
+      rendered.push('.');
+    }
+    return rendered;
+  }
+
+  segments
+    .iter()
+    .map(|s| match s {
+      Segment::Text(t) | Segment::Emphasis(t) => t.as_str(),
+      Segment::Blank(n) => fills.get(*n as usize).copied().unwrap_or(BLANK),
+    })
+    .collect()
+}
+
+/// HTML entities decoded from format text
+const ENTITIES: [(&str, char); 6] = [
+  ("&amp;", '&'),
+  ("&lt;", '<'),
+  ("&gt;", '>'),
+  ("&quot;", '"'),
+  ("&#39;", '\''),
+  ("&nbsp;", ' '),
+];
+
+/// Finds the `*` closing the emphasis opened at `open`. Emphasis must wrap a word:
+/// the opening `*` may not follow a letter, and the text must neither start nor
+/// end with whitespace. Anything else (`f**k`, `2 * 3`) is not emphasis.
+fn closing_star(chars: &[char], open: usize) -> Option<usize> {
+  let wraps_word = |c: char| !c.is_whitespace() && c != '*';
+
+  if open > 0 && chars[open - 1].is_alphanumeric() {
+    return None;
+  }
+  match chars.get(open + 1) {
+    Some(&c) if wraps_word(c) => {}
+    _ => return None,
+  }
+
+  let close = open + 2 + chars[open + 2..].iter().position(|&c| c == '*')?;
+  match chars.get(close + 1) {
+    Some(c) if c.is_alphanumeric() => None,
+    _ if wraps_word(chars[close - 1]) => Some(close),
+    _ => None,
+  }
+}
+
+/// Builds the list of segments, merging adjacent text of the same kind
+#[derive(Default)]
+struct Segments {
+  segments: Vec<Segment>,
+  current: String,
+  emphasis: bool,
+  current_emphasis: bool,
+}
+
+impl Segments {
+  fn push(&mut self, c: char) {
+    if self.emphasis != self.current_emphasis {
+      self.flush();
+      self.current_emphasis = self.emphasis;
+    }
+    self.current.push(c);
+  }
+
+  fn blank(&mut self, n: i32) {
+    self.flush();
+    self.segments.push(Segment::Blank(n));
+  }
+
+  fn flush(&mut self) {
+    if self.current.is_empty() {
+      return;
+    }
+    let text = std::mem::take(&mut self.current);
+    self.segments.push(match self.current_emphasis {
+      true => Segment::Emphasis(text),
+      false => Segment::Text(text),
+    });
+  }
+
+  fn finish(mut self) -> Vec<Segment> {
+    self.flush();
+    self.segments
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn text(t: &str) -> Segment {
+    Segment::Text(t.to_owned())
+  }
+
+  fn emphasis(t: &str) -> Segment {
+    Segment::Emphasis(t.to_owned())
+  }
+
+  #[test]
+  fn normalize_whitespace_and_control_characters() {
+    assert_eq!(
+      normalize("  Why \t can't\n\nI  sleep?  "),
+      "Why can't I sleep?"
+    );
+    assert_eq!(normalize("a\u{0}b\u{7}c"), "abc");
+    // Decomposed "é" is composed
+    assert_eq!(normalize("cafe\u{301}"), "caf\u{e9}");
+    assert_eq!(normalize(" \r\n "), "");
+  }
+
+  #[test]
+  fn count_prompts_valid() {
+    assert_eq!(count_prompts("No blanks."), Some(0));
+    assert_eq!(count_prompts("<prompt/> and <prompt/>."), Some(2));
+    assert_eq!(
+      count_prompts("<b>Bold</b> <br> 1 < 2 > 0 <prompt/>"),
+      Some(1)
+    );
+  }
+
+  #[test]
+  fn count_prompts_malformed() {
+    for format_text in &[
+      "<prompt>",
+      "</prompt>",
+      "<Prompt />",
+      "<prompt />",
+      "<prompt",
+      "Two: <prompt/> <prompt>",
+    ] {
+      assert_eq!(count_prompts(format_text), None, "{}", format_text);
+    }
+  }
+
+  #[test]
+  fn parse_blanks_and_line_breaks() {
+    assert_eq!(
+      parse("<prompt/> meets <prompt/>.<br>Again!"),
+      vec![
+        Segment::Blank(0),
+        text(" meets "),
+        Segment::Blank(1),
+        text(".\nAgain!"),
+      ]
+    );
+    // Official cards are parsed leniently
+    assert_eq!(parse("<Prompt>"), vec![Segment::Blank(0)]);
+    assert_eq!(parse("<prompt"), vec![text("<prompt")]);
+  }
+
+  #[test]
+  fn parse_emphasis() {
+    assert_eq!(
+      parse("A *big* <i>deal</i>."),
+      vec![
+        text("A "),
+        emphasis("big"),
+        text(" "),
+        emphasis("deal"),
+        text(".")
+      ]
+    );
+    assert_eq!(
+      parse("<strong>Very</strong> <EM>much</EM>"),
+      vec![emphasis("Very"), text(" "), emphasis("much")]
+    );
+  }
+
+  #[test]
+  fn parse_nested_emphasis() {
+    assert_eq!(
+      parse("<b>a <i>b</i> c</b> d"),
+      vec![emphasis("a b c"), text(" d")]
+    );
+    assert_eq!(
+      parse("*a <b>b</b> c* d"),
+      vec![emphasis("a b c"), text(" d")]
+    );
+    assert_eq!(parse("<b>*a*</b>"), vec![emphasis("*a*")]);
+  }
+
+  #[test]
+  fn parse_asterisks_that_are_not_emphasis() {
+    assert_eq!(parse("f**k"), vec![text("f**k")]);
+    assert_eq!(parse("2 * 3 * 4"), vec![text("2 * 3 * 4")]);
+    assert_eq!(parse("*unclosed"), vec![text("*unclosed")]);
+    assert_eq!(parse("* a*"), vec![text("* a*")]);
+    assert_eq!(parse("*a*b"), vec![text("*a*b")]);
+  }
+
+  #[test]
+  fn parse_entities() {
+    assert_eq!(
+      parse("Tom &amp; Jerry &lt;3 &quot;hi&quot; it&#39;s&nbsp;ok"),
+      vec![text("Tom & Jerry <3 \"hi\" it's ok")]
+    );
+    // Unknown entities are kept, and entities are only decoded once
+    assert_eq!(parse("&foo; &amp;lt;"), vec![text("&foo; &lt;")]);
+    // A decoded `<` does not start a tag
+    assert_eq!(parse("&lt;prompt/&gt;"), vec![text("<prompt/>")]);
+  }
+
+  #[test]
+  fn parse_other_tags_are_text() {
+    assert_eq!(parse("<u>under</u>"), vec![text("<u>under</u>")]);
+  }
+
+  #[test]
+  fn plain_text_blanks() {
+    assert_eq!(
+      plain_text(&parse("*Why* <prompt/>?<br>Because <prompt/>.")),
+      "Why _____?\nBecause _____."
+    );
+  }
+
+  #[test]
+  fn render_fills_blanks() {
+    let segments = parse("<prompt/> + <prompt/> = <i>love</i>.");
+    let fills = vec!["Cats.".to_owned(), " Dogs ".to_owned()];
+    assert_eq!(render(&segments, &fills), "Cats + Dogs = love.");
+  }
+
+  #[test]
+  fn render_without_blanks_appends_fills() {
+    let segments = parse("What ended my last relationship?");
+    assert_eq!(
+      render(&segments, &["A cat.".to_owned()]),
+      "What ended my last relationship? A cat."
+    );
+    assert_eq!(render(&segments, &[]), "What ended my last relationship?");
+  }
+
+  #[test]
+  fn render_mismatched_fill_counts() {
+    let segments = parse("<prompt/> and <prompt/>.");
+    // Missing fills are left as blanks
+    assert_eq!(render(&segments, &["Cats".to_owned()]), "Cats and _____.");
+    assert_eq!(render(&segments, &[]), "_____ and _____.");
+    // Extra fills are ignored
+    let fills = vec!["A".to_owned(), "B".to_owned(), "C".to_owned()];
+    assert_eq!(render(&segments, &fills), "A and B.");
+  }
+}
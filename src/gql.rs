@@ -1,25 +1,383 @@
 use crate::{
+  auth::{self, AuthError, Scope, TOKEN_LIFETIME_DAYS},
   db::{self, Pool},
-  models::{AddCard, AddCardRating, AddCardRatingCombination, GetCards, GetSets},
+  format::{self, Segment},
+  models::{
+    AddCard, AddCardRating, AddCardRatingCombination, CreateApiKey, CreateDeck, CreateGame,
+    CreateSet, CreateUserToken, EditCard, GameMove, GenerateTimeDeck, GetCardPairingResults,
+    GetCardPairings, GetCardReports, GetCardResults, GetCards, GetDeckResults, GetGameResults,
+    GetModerationQueue, GetRatingVolume, GetSets, GetTimeDeck, GetTimeDeckResults, GetTimeDecks,
+    GetUserCardCombinationRatings, GetUserDecks, GetUserHistory, GetUserSubmittedCards,
+    GetVisibleCards, ModerateCard, PickGameWinner, PlayGameCards, RegisterUser, RemoveCardRating,
+    RenameSet, ReportCard, RevokeApiKey, TimeDeckPeriodEnded, UpdateDeck, UpdateSetCards,
+    UpdateUsername,
+  },
   Context,
 };
 use actix_web::{
+  error::{BlockingError, ErrorUnauthorized},
   web::{self, Data, Json, ServiceConfig},
-  Error as AWError, HttpResponse,
+  Error as AWError, HttpRequest, HttpResponse,
 };
 use base64::{decode, encode, DecodeError};
 use juniper::{
   http::{playground::playground_source, GraphQLRequest},
-  Context as JContext, Executor, FieldError, IntoFieldError, ID,
+  Context as JContext, Executor, FieldError, IntoFieldError, Value, ID,
 };
 use juniper_from_schema::graphql_schema_from_file;
 use rand::random;
 use serde_json::{error::Error as SError, to_string};
-use std::{num::ParseIntError, panic, sync::Arc};
+use std::{cell::RefCell, collections::HashMap, num::ParseIntError, panic, sync::Arc};
 use url::{ParseError, Url};
 
 impl JContext for Context {}
 
+/// Rating breakdowns of the cards of a request. Lists of cards load the
+/// breakdowns of all of their cards at once when they are requested, so that
+/// `Card.ratingBreakdown` does not query the database for every card.
+#[derive(Default)]
+pub struct RatingBreakdownLoader {
+  breakdowns: RefCell<HashMap<i32, CardRatingBreakdown>>,
+}
+
+impl RatingBreakdownLoader {
+  /// Loads the breakdowns of the cards that were not loaded yet, with a single
+  /// database call
+  fn load(&self, pool: &Pool, card_ids: &[i32]) -> Result<(), GqlError> {
+    let card_ids = {
+      let breakdowns = self.breakdowns.borrow();
+      card_ids
+        .iter()
+        .filter(|id| !breakdowns.contains_key(id))
+        .cloned()
+        .collect::<Vec<_>>()
+    };
+    if card_ids.is_empty() {
+      return Ok(());
+    }
+
+    let results = db::get_card_rating_breakdowns(pool, &card_ids)?;
+    let mut breakdowns = self.breakdowns.borrow_mut();
+    for id in card_ids {
+      breakdowns.insert(id, CardRatingBreakdown::default());
+    }
+    for r in results {
+      breakdowns.insert(
+        r.card_id,
+        CardRatingBreakdown {
+          upvotes: r.upvotes,
+          downvotes: r.downvotes,
+          low_votes: r.low_votes,
+          high_votes: r.high_votes,
+          histogram: r.histogram,
+        },
+      );
+    }
+    Ok(())
+  }
+
+  fn get(&self, pool: &Pool, card_id: i32) -> Result<CardRatingBreakdown, GqlError> {
+    self.load(pool, &[card_id])?;
+    Ok(self.breakdowns.borrow()[&card_id].clone())
+  }
+}
+
+/// Moderation of the cards of a request. Like the rating breakdowns, lists of
+/// cards load the moderation of their cards with a single query when
+/// `Card.moderation` is requested.
+#[derive(Default)]
+pub struct CardModerationLoader {
+  moderations: RefCell<HashMap<i32, Option<CardModeration>>>,
+}
+
+impl CardModerationLoader {
+  /// Loads the moderation of the cards that were not loaded yet. Inactive cards
+  /// are loaded as `None`.
+  fn load(&self, pool: &Pool, card_ids: &[i32]) -> Result<(), GqlError> {
+    let card_ids = {
+      let moderations = self.moderations.borrow();
+      card_ids
+        .iter()
+        .filter(|id| !moderations.contains_key(id))
+        .cloned()
+        .collect::<Vec<_>>()
+    };
+    if card_ids.is_empty() {
+      return Ok(());
+    }
+
+    let results = db::get_card_moderations(pool, &card_ids)?;
+
+    let mut moderations = self.moderations.borrow_mut();
+    for id in card_ids {
+      moderations.insert(id, None);
+    }
+    for r in results {
+      moderations.insert(
+        r.card_id,
+        Some(CardModeration {
+          status: ModerationStatus::from_name(&r.moderation_status)
+            .ok_or(GqlError::UnexpectedError)?,
+          moderated_by: match (r.moderated_by_user_id, r.moderated_by_username) {
+            (Some(id), Some(username)) => Some(User { id, username }),
+            _ => None,
+          },
+          moderated_date: r.moderated_date,
+          reason: r.rejection_reason,
+        }),
+      );
+    }
+    Ok(())
+  }
+
+  fn get(&self, pool: &Pool, card_id: i32) -> Result<Option<CardModeration>, GqlError> {
+    self.load(pool, &[card_id])?;
+    Ok(self.moderations.borrow()[&card_id].clone())
+  }
+}
+
+/// Settings of the GraphQL endpoint, built by `gql_config_from_env()`
+#[derive(Clone)]
+pub struct GqlConfig {
+  /// Number of distinct users reporting a card before it is deactivated
+  pub report_threshold: i32,
+  /// Hour (UTC) at which the periods of time-decks start, the same as the
+  /// scheduler's `JobsConfig::time_deck_hour`
+  pub time_deck_hour: i32,
+}
+
+impl Context {
+  /// The authenticated `bb.user` id, required for any operation that records
+  /// who performed it. Anonymous callers are rejected, as are callers that
+  /// were not granted the scope.
+  fn require(&self, scope: Scope) -> Result<i32, GqlError> {
+    match &self.caller {
+      Some(c) if c.has_scope(scope) => Ok(c.user_id),
+      Some(_) => Err(GqlError::Forbidden),
+      None => Err(GqlError::Unauthenticated),
+    }
+  }
+
+  /// The authenticated `bb.user` id for operations managing the account itself,
+  /// which cannot be performed with an API key
+  fn session_user_id(&self) -> Result<i32, GqlError> {
+    match &self.caller {
+      Some(c) if c.api_key_id.is_none() => Ok(c.user_id),
+      Some(_) => Err(GqlError::Forbidden),
+      None => Err(GqlError::Unauthenticated),
+    }
+  }
+
+  fn has_scope(&self, scope: Scope) -> bool {
+    self.caller.as_ref().is_some_and(|c| c.has_scope(scope))
+  }
+
+  /// Active cards by their IDs. Pending and rejected cards are left out, unless
+  /// the caller submitted them (or is an admin).
+  fn visible_cards_by_ids(&self, card_ids: &[i32]) -> Result<Vec<GetCardResults>, GqlError> {
+    Ok(db::get_visible_cards_by_ids(
+      &self.db,
+      &GetVisibleCards {
+        card_ids: card_ids.to_vec(),
+        user_id: self.caller.as_ref().map(|c| c.user_id),
+        include_unapproved: self.has_scope(Scope::Admin),
+      },
+    )?)
+  }
+}
+
+impl From<ApiScope> for Scope {
+  fn from(scope: ApiScope) -> Scope {
+    match scope {
+      ApiScope::Read => Scope::Read,
+      ApiScope::Rate => Scope::Rate,
+      ApiScope::Submit => Scope::Submit,
+      ApiScope::Play => Scope::Play,
+      ApiScope::Admin => Scope::Admin,
+    }
+  }
+}
+
+impl From<Scope> for ApiScope {
+  fn from(scope: Scope) -> ApiScope {
+    match scope {
+      Scope::Read => ApiScope::Read,
+      Scope::Rate => ApiScope::Rate,
+      Scope::Submit => ApiScope::Submit,
+      Scope::Play => ApiScope::Play,
+      Scope::Admin => ApiScope::Admin,
+    }
+  }
+}
+
+impl ModerationStatus {
+  /// Name of the status in `bb.card.moderation_status`
+  fn name(self) -> &'static str {
+    match self {
+      ModerationStatus::Pending => "pending",
+      ModerationStatus::Approved => "approved",
+      ModerationStatus::Rejected => "rejected",
+    }
+  }
+
+  fn from_name(name: &str) -> Option<ModerationStatus> {
+    match name {
+      "pending" => Some(ModerationStatus::Pending),
+      "approved" => Some(ModerationStatus::Approved),
+      "rejected" => Some(ModerationStatus::Rejected),
+      _ => None,
+    }
+  }
+}
+
+impl TimeDeckPeriod {
+  /// Name of the period in `bb.time_deck.period`, and in `bb.run_time_deck_job`
+  pub fn name(self) -> &'static str {
+    match self {
+      TimeDeckPeriod::Daily => "day",
+      TimeDeckPeriod::Weekly => "week",
+      TimeDeckPeriod::Monthly => "month",
+      TimeDeckPeriod::Yearly => "year",
+    }
+  }
+
+  pub fn from_name(name: &str) -> Option<TimeDeckPeriod> {
+    match name {
+      "day" => Some(TimeDeckPeriod::Daily),
+      "week" => Some(TimeDeckPeriod::Weekly),
+      "month" => Some(TimeDeckPeriod::Monthly),
+      "year" => Some(TimeDeckPeriod::Yearly),
+      _ => None,
+    }
+  }
+}
+
+/// Whether a date is a valid `YYYY-MM-DD` day
+fn is_valid_date(date: &str) -> bool {
+  let parts = date.split('-').collect::<Vec<_>>();
+  let (year, month, day) = match parts.as_slice() {
+    [y, m, d] if y.len() == 4 && m.len() == 2 && d.len() == 2 => {
+      match (y.parse::<u32>(), m.parse::<u32>(), d.parse::<u32>()) {
+        (Ok(y), Ok(m), Ok(d)) => (y, m, d),
+        _ => return false,
+      }
+    }
+    _ => return false,
+  };
+  let days_in_month = match month {
+    1 | 3 | 5 | 7 | 8 | 10 | 12 => 31,
+    4 | 6 | 9 | 11 => 30,
+    2 if year % 4 == 0 && (year % 100 != 0 || year % 400 == 0) => 29,
+    2 => 28,
+    _ => return false,
+  };
+  year > 0 && day > 0 && day <= days_in_month
+}
+
+impl DeckVisibility {
+  /// Name of the visibility in `bb.parent_set.visibility`
+  fn name(self) -> &'static str {
+    match self {
+      DeckVisibility::Private => "private",
+      DeckVisibility::Unlisted => "unlisted",
+      DeckVisibility::Public => "public",
+    }
+  }
+
+  fn from_name(name: &str) -> Option<DeckVisibility> {
+    match name {
+      "private" => Some(DeckVisibility::Private),
+      "unlisted" => Some(DeckVisibility::Unlisted),
+      "public" => Some(DeckVisibility::Public),
+      _ => None,
+    }
+  }
+}
+
+impl CardSort {
+  /// Name of the sort in `bb.get_cards`, `None` for the default (ID) order
+  fn name(self) -> Option<&'static str> {
+    match self {
+      CardSort::Id => None,
+      CardSort::Newest => Some("newest"),
+      CardSort::TopRated => Some("top_rated"),
+      CardSort::MostVoted => Some("most_voted"),
+      CardSort::Score => Some("score"),
+      CardSort::SearchRelevance => Some("relevance"),
+    }
+  }
+
+  /// Cards are sorted by ascending ID, and from the highest value down otherwise
+  fn default_direction(self) -> SortDirection {
+    match self {
+      CardSort::Id => SortDirection::Asc,
+      _ => SortDirection::Desc,
+    }
+  }
+}
+
+/// Usernames are trimmed, and must be between 3 and 32 characters made up of
+/// letters, digits, `_`, `-`, or `.`
+pub fn validate_username(username: &str) -> Result<String, GqlError> {
+  let username = username.trim();
+  let length = username.chars().count();
+
+  if !(3..=32).contains(&length)
+    || !username
+      .chars()
+      .all(|c| c.is_alphanumeric() || c == '_' || c == '-' || c == '.')
+  {
+    return Err(GqlError::InvalidUsername);
+  }
+
+  Ok(username.to_owned())
+}
+
+/// Maximum length of a card's format text, in characters
+const MAX_FORMAT_TEXT_LENGTH: usize = 256;
+
+/// Format text is normalized before it is validated. Black cards may have up to
+/// 3 `<prompt/>` blanks (without any, the card is answered with a single white
+/// card), and white cards cannot have any.
+fn validate_format_text(format_text: &str, color: CardColor) -> Result<String, GqlError> {
+  let format_text = format::normalize(format_text);
+
+  if format_text.is_empty() {
+    return Err(GqlError::EmptyFormatText);
+  }
+  if format_text.chars().count() > MAX_FORMAT_TEXT_LENGTH {
+    return Err(GqlError::FormatTextTooLong);
+  }
+
+  let prompts = format::count_prompts(&format_text).ok_or(GqlError::MalformedPrompt)?;
+  match color {
+    CardColor::Black if prompts > 3 => Err(GqlError::PromptOutOfBounds),
+    CardColor::White if prompts > 0 => Err(GqlError::WhiteCardPrompt),
+    _ => Ok(format_text),
+  }
+}
+
+/// Set names are trimmed, and must be between 1 and 100 characters
+fn validate_set_name(name: &str) -> Result<String, GqlError> {
+  let name = name.trim();
+
+  if !(1..=100).contains(&name.chars().count()) {
+    return Err(GqlError::InvalidSetName);
+  }
+
+  Ok(name.to_owned())
+}
+
+/// Splits the `limit + 1` rows fetched for a page into the page itself, whether
+/// more rows follow, and the cursor of the last row of the page (if any)
+fn page<T, C>(mut rows: Vec<T>, limit: i32, cursor: impl Fn(&T) -> C) -> (Vec<T>, bool, Option<C>) {
+  let limit = limit.max(0) as usize;
+  let has_more = rows.len() > limit;
+  rows.truncate(limit);
+  let last_cursor = rows.last().map(cursor);
+  (rows, has_more, last_cursor)
+}
+
 graphql_schema_from_file!("schema.graphql", error_type: GqlError);
 
 /// Helper trait for encoding a value into a JuniperID
@@ -61,13 +419,144 @@ impl ToEncodedJuniperID for i32 {
   }
 }
 
+impl ToEncodedJuniperID for (i32, i32, i32) {
+  fn to_encoded_id(&self) -> ID {
+    let mut encoding = self.0.to_be_bytes().to_vec();
+    encoding.extend_from_slice(&self.1.to_be_bytes());
+    encoding.extend_from_slice(&self.2.to_be_bytes());
+    ID::new(encode(&encoding))
+  }
+
+  fn from_encoded_id(id: ID) -> Result<(i32, i32, i32), DecodeError> {
+    let decoded = decode(&id.to_string())?;
+    if decoded.len() != 12 {
+      return Err(DecodeError::InvalidLength);
+    }
+    let mut values = decoded.chunks(4).map(|c| {
+      let mut bytes = [0; 4];
+      bytes.copy_from_slice(c);
+      i32::from_be_bytes(bytes)
+    });
+    Ok((
+      values.next().unwrap_or_default(),
+      values.next().unwrap_or_default(),
+      values.next().unwrap_or_default(),
+    ))
+  }
+}
+
+impl ToEncodedJuniperID for (i32, i32) {
+  fn to_encoded_id(&self) -> ID {
+    let mut encoding = self.0.to_be_bytes().to_vec();
+    encoding.extend_from_slice(&self.1.to_be_bytes());
+    ID::new(encode(&encoding))
+  }
+
+  fn from_encoded_id(id: ID) -> Result<(i32, i32), DecodeError> {
+    let decoded = decode(&id.to_string())?;
+    if decoded.len() != 8 {
+      return Err(DecodeError::InvalidLength);
+    }
+    let mut first = [0; 4];
+    let mut second = [0; 4];
+    first.copy_from_slice(&decoded[..4]);
+    second.copy_from_slice(&decoded[4..]);
+    Ok((i32::from_be_bytes(first), i32::from_be_bytes(second)))
+  }
+}
+
+/// Cursor of sorted results, made up of the sort key and ID of the last card
+impl ToEncodedJuniperID for (f64, i32) {
+  fn to_encoded_id(&self) -> ID {
+    let mut encoding = self.0.to_be_bytes().to_vec();
+    encoding.extend_from_slice(&self.1.to_be_bytes());
+    ID::new(encode(&encoding))
+  }
+
+  fn from_encoded_id(id: ID) -> Result<(f64, i32), DecodeError> {
+    let decoded = decode(&id.to_string())?;
+    if decoded.len() != 12 {
+      return Err(DecodeError::InvalidLength);
+    }
+    let mut sort_key = [0; 8];
+    let mut card_id = [0; 4];
+    sort_key.copy_from_slice(&decoded[..8]);
+    card_id.copy_from_slice(&decoded[8..]);
+    Ok((f64::from_be_bytes(sort_key), i32::from_be_bytes(card_id)))
+  }
+}
+
+#[derive(Clone)]
 pub struct Card {
   id: i32,
   format_text: String,
   color: CardColor,
   average_rating: Option<f32>,
   total_votes: i32,
-  set: SetInfo,
+  set: Option<SetInfo>,
+  sets: Vec<SetInfo>,
+  submitted_by: Option<User>,
+  pick: i32,
+  draw: i32,
+  score: f32,
+}
+
+impl From<&GetCardResults> for Card {
+  fn from(c: &GetCardResults) -> Card {
+    Card {
+      id: c.id,
+      color: match c.is_black {
+        true => CardColor::Black,
+        false => CardColor::White,
+      },
+      format_text: c.format_text.to_owned(),
+      set: match (c.parent_set_id, &c.parent_set_name) {
+        (Some(id), Some(name)) => Some(SetInfo {
+          id,
+          name: name.to_owned(),
+        }),
+        _ => None,
+      },
+      sets: c
+        .parent_set_ids
+        .iter()
+        .zip(c.parent_set_names.iter())
+        .map(|(&id, name)| SetInfo {
+          id,
+          name: name.to_owned(),
+        })
+        .collect(),
+      total_votes: c.total_votes,
+      average_rating: c.average_rating,
+      submitted_by: match (c.submitted_by_user_id, &c.submitted_by_username) {
+        (Some(id), Some(username)) => Some(User {
+          id,
+          username: username.to_owned(),
+        }),
+        _ => None,
+      },
+      pick: c.pick,
+      draw: c.draw,
+      score: c.score,
+    }
+  }
+}
+
+impl Card {
+  /// The moderation of user-submitted cards is only visible to the submitter
+  /// (and admins)
+  fn moderation_visible(&self, context: &Context) -> bool {
+    let submitted_by = match &self.submitted_by {
+      Some(u) => u.id,
+      None => return false,
+    };
+
+    match &context.caller {
+      Some(c) if c.has_scope(Scope::Admin) => true,
+      Some(c) => c.user_id == submitted_by && c.has_scope(Scope::Read),
+      None => false,
+    }
+  }
 }
 
 impl CardFields for Card {
@@ -89,14 +578,26 @@ impl CardFields for Card {
     Ok(self.color)
   }
 
+  /// The first set the card belongs to (of the sets that were searched),
+  /// `null` for user-submitted cards which have not been added to a set.
+  /// Deprecated in favor of `sets`.
   fn field_set(
     &self,
     _: &Executor<'_, Context>,
     _: &QueryTrail<'_, SetInfo, Walked>,
-  ) -> Result<&SetInfo, GqlError> {
+  ) -> Result<&Option<SetInfo>, GqlError> {
     Ok(&self.set)
   }
 
+  /// Every set the card belongs to, ordered by ID
+  fn field_sets(
+    &self,
+    _: &Executor<'_, Context>,
+    _: &QueryTrail<'_, SetInfo, Walked>,
+  ) -> Result<&Vec<SetInfo>, GqlError> {
+    Ok(&self.sets)
+  }
+
   fn field_total_votes(&self, _: &Executor<'_, Context>) -> Result<i32, GqlError> {
     Ok(self.total_votes)
   }
@@ -104,557 +605,3548 @@ impl CardFields for Card {
   fn field_average_rating(&self, _: &Executor<'_, Context>) -> Result<Option<f64>, GqlError> {
     Ok(self.average_rating.map(|v| v.into()))
   }
-}
-
-pub struct CardOperation {
-  id: i32,
-  format_text: String,
-  color: CardColor,
-}
 
-impl CardOperationFields for CardOperation {
-  fn field_id(&self, _: &Executor<'_, Context>) -> Result<ID, GqlError> {
-    Ok(ID::from(self.id.to_string()))
+  /// The user who submitted the card, `null` for official cards
+  fn field_submitted_by(
+    &self,
+    _: &Executor<'_, Context>,
+    _: &QueryTrail<'_, User, Walked>,
+  ) -> Result<&Option<User>, GqlError> {
+    Ok(&self.submitted_by)
   }
 
-  fn field_format_text(&self, _: &Executor<'_, Context>) -> Result<&String, GqlError> {
-    Ok(&self.format_text)
+  /// Number of white cards to play on a black card. Black cards without
+  /// a `<prompt/>` are answered with a single card. Always 0 for white cards.
+  fn field_pick(&self, _: &Executor<'_, Context>) -> Result<i32, GqlError> {
+    Ok(self.pick)
   }
 
-  fn field_color(&self, _: &Executor<'_, Context>) -> Result<CardColor, GqlError> {
-    Ok(self.color)
+  /// Number of extra white cards each player draws before playing a black
+  /// card ("Draw 2, Pick 3")
+  fn field_draw(&self, _: &Executor<'_, Context>) -> Result<i32, GqlError> {
+    Ok(self.draw)
   }
-}
 
-pub struct CardResult {
-  results: Vec<Card>,
-  last_cursor: Option<i32>,
-  has_next_page: bool,
-  random_seed: Option<f32>,
-}
+  /// Ranking score of the card, the Bayesian average of its ratings starting
+  /// from 10 votes of 0.5. Unlike `averageRating`, a handful of votes cannot
+  /// outrank a card with many votes.
+  fn field_score(&self, _: &Executor<'_, Context>) -> Result<f64, GqlError> {
+    Ok(self.score.into())
+  }
 
-impl CardResultFields for CardResult {
-  fn field_results(
+  /// The format text parsed into text, blanks, and emphasized text, so
+  /// clients don't need to parse the HTML themselves
+  fn field_segments(
     &self,
     _: &Executor<'_, Context>,
-    _: &QueryTrail<'_, Card, Walked>,
-  ) -> Result<&Vec<Card>, GqlError> {
-    Ok(&self.results)
+    _: &QueryTrail<'_, CardSegment, Walked>,
+  ) -> Result<Vec<CardSegment>, GqlError> {
+    Ok(
+      format::parse(&self.format_text)
+        .into_iter()
+        .map(CardSegment::from)
+        .collect(),
+    )
   }
 
-  fn field_last_cursor(&self, _: &Executor<'_, Context>) -> Result<Option<ID>, GqlError> {
-    Ok(self.last_cursor.map(|v| v.to_encoded_id()))
+  /// The format text without markup, with blanks written as `_____`
+  fn field_plain_text(&self, _: &Executor<'_, Context>) -> Result<String, GqlError> {
+    Ok(format::plain_text(&format::parse(&self.format_text)))
   }
 
-  fn field_has_next_page(&self, _: &Executor<'_, Context>) -> Result<bool, GqlError> {
-    Ok(self.has_next_page)
+  /// The black card with its blanks filled in by the given white cards, in
+  /// order. There must be exactly `pick` fills. Without fills, this is the
+  /// same as `plainText`.
+  fn field_render(
+    &self,
+    executor: &Executor<'_, Context>,
+    fills: Option<Vec<ID>>,
+  ) -> Result<String, GqlError> {
+    let segments = format::parse(&self.format_text);
+    let fills = match fills {
+      Some(fills) if !fills.is_empty() => fills,
+      _ => return Ok(format::plain_text(&segments)),
+    };
+
+    match self.color {
+      CardColor::Black => {}
+      CardColor::White => return Err(GqlError::NotBlackCard),
+    }
+    if fills.len() != self.pick as usize {
+      return Err(GqlError::InvalidFills);
+    }
+
+    let fill_ids = fills
+      .iter()
+      .map(|id| id.parse::<i32>())
+      .collect::<Result<Vec<_>, _>>()?;
+
+    let white_cards = executor
+      .context()
+      .visible_cards_by_ids(&fill_ids)?
+      .into_iter()
+      .map(|c| (c.id, c))
+      .collect::<HashMap<_, _>>();
+
+    let missing = fill_ids
+      .iter()
+      .filter(|id| !white_cards.contains_key(id))
+      .map(|id| id.to_string())
+      .collect::<Vec<_>>();
+
+    if !missing.is_empty() {
+      return Err(GqlError::CardNotFound(missing));
+    }
+
+    let fill_texts = fill_ids
+      .iter()
+      .map(|id| match &white_cards[id] {
+        c if c.is_black => Err(GqlError::InvalidFills),
+        c => Ok(format::plain_text(&format::parse(&c.format_text))),
+      })
+      .collect::<Result<Vec<_>, _>>()?;
+
+    Ok(format::render(&segments, &fill_texts))
   }
 
-  fn field_random_seed(&self, _: &Executor<'_, Context>) -> Result<Option<ID>, GqlError> {
-    Ok(self.random_seed.map(|c| c.to_encoded_id()))
+  /// Moderation of a user-submitted card, only visible to the submitter (and
+  /// admins). `null` for official cards, and for anyone else.
+  fn field_moderation(
+    &self,
+    executor: &Executor<'_, Context>,
+    _: &QueryTrail<'_, CardModeration, Walked>,
+  ) -> Result<Option<CardModeration>, GqlError> {
+    let context = executor.context();
+
+    match self.moderation_visible(context) {
+      true => context.moderations.get(&context.db, self.id),
+      false => Ok(None),
+    }
   }
-}
 
-pub struct Set {
-  id: i32,
-  name: String,
-}
+  /// Every wording the card has had, oldest first. Revision 1 is the card as it
+  /// was created, and the last revision is the current `formatText`.
+  fn field_revisions(
+    &self,
+    executor: &Executor<'_, Context>,
+    _: &QueryTrail<'_, CardRevision, Walked>,
+  ) -> Result<Vec<CardRevision>, GqlError> {
+    let revisions = db::get_card_revisions(&executor.context().db, self.id)?;
 
-impl SetFields for Set {
-  fn field_id(&self, _: &Executor<'_, Context>) -> Result<ID, GqlError> {
-    Ok(ID::from(self.id.to_string()))
+    Ok(
+      revisions
+        .into_iter()
+        .map(|r| CardRevision {
+          revision: r.revision,
+          format_text: r.format_text,
+          edited_by: match (r.edited_by_user_id, r.edited_by_username) {
+            (Some(id), Some(username)) => Some(User { id, username }),
+            _ => None,
+          },
+          edited_date: r.created_date,
+          total_votes: r.total_votes,
+          average_rating: r.average_rating,
+        })
+        .collect(),
+    )
   }
 
-  fn field_name(&self, _: &Executor<'_, Context>) -> Result<&String, GqlError> {
-    Ok(&self.name)
+  /// Up and down votes of the card, and how its ratings are spread out
+  fn field_rating_breakdown(
+    &self,
+    executor: &Executor<'_, Context>,
+    _: &QueryTrail<'_, CardRatingBreakdown, Walked>,
+  ) -> Result<CardRatingBreakdown, GqlError> {
+    let context = executor.context();
+    context.rating_breakdowns.get(&context.db, self.id)
   }
 
-  /// Most useful method. Can be used for "actual" gameplay. To *shuffle* the cards,
-  /// pass `randomized: true`, and use the resulting `randomSeed` to keep the
-  /// same card shuffle in subsequent results.
-  ///
-  /// Search field is a full-text-search implementation
-  fn field_cards(
+  /// The cards most liked in combination with this one, from the combination
+  /// ratings. White cards are returned for a black card, and black cards for a
+  /// white card. `ordinal` is the blank the white card was played on, and can be
+  /// used to only get the pairings of one blank of a multi-blank black card.
+  fn field_best_pairings(
     &self,
     executor: &Executor<'_, Context>,
-    _: &QueryTrail<'_, CardResult, Walked>,
-    search: Option<String>,
-    card_color: Option<CardColor>,
-    pagination: Pagination,
-    randomized: Option<bool>,
-  ) -> Result<Option<CardResult>, GqlError> {
-    if pagination.page_size > 1000 || pagination.page_size < 0 {
+    _: &QueryTrail<'_, CardPairing, Walked>,
+    first: i32,
+    ordinal: Option<i32>,
+  ) -> Result<Vec<CardPairing>, GqlError> {
+    if first > 1000 || first < 0 {
       return Err(GqlError::LimitOutOfBounds);
     }
 
-    let mut get_cards = GetCards::default();
-    get_cards.n_cards = Some(pagination.page_size + 1);
-    get_cards.search = search;
-    get_cards.card_sets = Some(vec![self.id]);
-
-    match pagination.cursor.map(|v| i32::from_encoded_id(v)) {
-      Some(Ok(v)) => get_cards.previous_cursor = Some(v),
-      Some(Err(e)) => {
-        return Err(e.into());
+    match ordinal {
+      Some(o) if o < 0 => return Err(GqlError::NegativeOrdinal),
+      Some(o) if self.color == CardColor::Black && o >= self.pick => {
+        return Err(GqlError::OrdinalOutOfBounds)
       }
-      _ => get_cards.previous_cursor = None,
+      _ => {}
     }
 
-    get_cards.filter_black = match card_color {
-      Some(CardColor::Black) => Some(true),
-      Some(CardColor::White) => Some(false),
-      None => None,
+    let con = &executor.context().db;
+    let pairings = db::get_card_pairings(
+      con,
+      &GetCardPairings {
+        black_card_id: Some(self.id).filter(|_| self.color == CardColor::Black),
+        white_card_id: Some(self.id).filter(|_| self.color == CardColor::White),
+        ordinal,
+        n_results: first,
+      },
+    )?;
+
+    let paired_card_id = |p: &GetCardPairingResults| match self.color {
+      CardColor::Black => p.white_card_id,
+      CardColor::White => p.black_card_id,
     };
+    let card_ids = pairings.iter().map(paired_card_id).collect::<Vec<_>>();
+    let cards = executor
+      .context()
+      .visible_cards_by_ids(&card_ids)?
+      .iter()
+      .map(|c| (c.id, Card::from(c)))
+      .collect::<HashMap<_, _>>();
 
-    if let Some(r) = randomized {
-      get_cards.get_random = Some(r);
+    Ok(
+      pairings
+        .iter()
+        .filter_map(|p| {
+          cards.get(&paired_card_id(p)).map(|card| CardPairing {
+            card: card.clone(),
+            ordinal: p.ordinal,
+            total_votes: p.total_votes,
+            average_rating: p.average_rating,
+          })
+        })
+        .collect(),
+    )
+  }
+}
 
-      match pagination.random_seed.map(|v| f32::from_encoded_id(v)) {
-        Some(Ok(v)) => get_cards.random_seed = Some(v),
-        Some(Err(e)) => {
-          return Err(e.into());
-        }
-        None => get_cards.random_seed = Some(random::<f32>()),
-      }
+/// Breakdown of the ratings of a card. Ratings of exactly 0.5 are neither up
+/// nor down votes.
+#[derive(Clone)]
+pub struct CardRatingBreakdown {
+  upvotes: i32,
+  downvotes: i32,
+  low_votes: Option<f32>,
+  high_votes: Option<f32>,
+  histogram: Vec<i32>,
+}
+
+impl Default for CardRatingBreakdown {
+  fn default() -> CardRatingBreakdown {
+    CardRatingBreakdown {
+      upvotes: 0,
+      downvotes: 0,
+      low_votes: None,
+      high_votes: None,
+      histogram: vec![0; 10],
     }
+  }
+}
+
+impl CardRatingBreakdownFields for CardRatingBreakdown {
+  fn field_upvotes(&self, _: &Executor<'_, Context>) -> Result<i32, GqlError> {
+    Ok(self.upvotes)
+  }
 
-    let db_cards = db::get_cards(&executor.context().db, &get_cards)?;
+  fn field_downvotes(&self, _: &Executor<'_, Context>) -> Result<i32, GqlError> {
+    Ok(self.downvotes)
+  }
 
-    let has_more = db_cards.iter().len() as i32 > pagination.page_size;
-    let last_cursor = match get_cards.get_random {
-      Some(true) => Some(get_cards.previous_cursor.unwrap_or(0) + pagination.page_size),
-      _ => db_cards
+  /// Average of the down votes
+  fn field_low_votes(&self, _: &Executor<'_, Context>) -> Result<Option<f64>, GqlError> {
+    Ok(self.low_votes.map(f64::from))
+  }
+
+  /// Average of the up votes
+  fn field_high_votes(&self, _: &Executor<'_, Context>) -> Result<Option<f64>, GqlError> {
+    Ok(self.high_votes.map(f64::from))
+  }
+
+  /// Number of ratings in each 0.1 wide bucket, from 0 to 1
+  fn field_histogram(
+    &self,
+    _: &Executor<'_, Context>,
+    _: &QueryTrail<'_, RatingBucket, Walked>,
+  ) -> Result<Vec<RatingBucket>, GqlError> {
+    let buckets = self.histogram.len() as i32;
+    Ok(
+      self
+        .histogram
         .iter()
-        .nth(pagination.page_size as usize - 1)
-        .map(|r| r.id),
-    };
+        .zip(0..)
+        .map(|(&votes, bucket)| RatingBucket {
+          min: f64::from(bucket) / f64::from(buckets),
+          max: f64::from(bucket + 1) / f64::from(buckets),
+          votes,
+        })
+        .collect(),
+    )
+  }
+}
 
-    let db_cards = db_cards
-      .iter()
-      .take(pagination.page_size as usize)
-      .map(|c| Card {
-        id: c.id,
-        color: match c.is_black {
-          true => CardColor::Black,
-          false => CardColor::White,
-        },
-        format_text: c.format_text.to_owned(),
-        set: SetInfo {
-          id: c.parent_set_id,
-          name: c.parent_set_name.to_owned(),
-        },
-        total_votes: c.total_votes,
-        average_rating: c.average_rating,
-      })
-      .collect::<Vec<_>>();
+pub struct RatingBucket {
+  min: f64,
+  max: f64,
+  votes: i32,
+}
 
-    Ok(Some(CardResult {
-      results: db_cards,
-      has_next_page: has_more,
-      last_cursor: last_cursor,
-      random_seed: get_cards.random_seed,
-    }))
+impl RatingBucketFields for RatingBucket {
+  fn field_min(&self, _: &Executor<'_, Context>) -> Result<f64, GqlError> {
+    Ok(self.min)
+  }
+
+  fn field_max(&self, _: &Executor<'_, Context>) -> Result<f64, GqlError> {
+    Ok(self.max)
+  }
+
+  fn field_votes(&self, _: &Executor<'_, Context>) -> Result<i32, GqlError> {
+    Ok(self.votes)
   }
 }
 
-pub struct SetInfo {
+pub struct CardPairing {
+  card: Card,
+  ordinal: i32,
+  total_votes: i32,
+  average_rating: f32,
+}
+
+impl CardPairingFields for CardPairing {
+  fn field_card(
+    &self,
+    _: &Executor<'_, Context>,
+    _: &QueryTrail<'_, Card, Walked>,
+  ) -> Result<&Card, GqlError> {
+    Ok(&self.card)
+  }
+
+  fn field_ordinal(&self, _: &Executor<'_, Context>) -> Result<i32, GqlError> {
+    Ok(self.ordinal)
+  }
+
+  fn field_total_votes(&self, _: &Executor<'_, Context>) -> Result<i32, GqlError> {
+    Ok(self.total_votes)
+  }
+
+  fn field_average_rating(&self, _: &Executor<'_, Context>) -> Result<f64, GqlError> {
+    Ok(self.average_rating.into())
+  }
+}
+
+pub struct CardRevision {
+  revision: i32,
+  format_text: String,
+  edited_by: Option<User>,
+  edited_date: String,
+  total_votes: i32,
+  average_rating: Option<f32>,
+}
+
+impl CardRevisionFields for CardRevision {
+  fn field_revision(&self, _: &Executor<'_, Context>) -> Result<i32, GqlError> {
+    Ok(self.revision)
+  }
+
+  fn field_format_text(&self, _: &Executor<'_, Context>) -> Result<&String, GqlError> {
+    Ok(&self.format_text)
+  }
+
+  /// The user who wrote the revision, `null` for official cards
+  fn field_edited_by(
+    &self,
+    _: &Executor<'_, Context>,
+    _: &QueryTrail<'_, User, Walked>,
+  ) -> Result<&Option<User>, GqlError> {
+    Ok(&self.edited_by)
+  }
+
+  /// When the revision was written (UTC, ISO 8601)
+  fn field_edited_date(&self, _: &Executor<'_, Context>) -> Result<&String, GqlError> {
+    Ok(&self.edited_date)
+  }
+
+  /// Active ratings given while this revision was the card's wording
+  fn field_total_votes(&self, _: &Executor<'_, Context>) -> Result<i32, GqlError> {
+    Ok(self.total_votes)
+  }
+
+  fn field_average_rating(&self, _: &Executor<'_, Context>) -> Result<Option<f64>, GqlError> {
+    Ok(self.average_rating.map(f64::from))
+  }
+}
+
+#[derive(Clone)]
+pub struct CardModeration {
+  status: ModerationStatus,
+  moderated_by: Option<User>,
+  moderated_date: Option<String>,
+  reason: Option<String>,
+}
+
+impl CardModerationFields for CardModeration {
+  fn field_status(&self, _: &Executor<'_, Context>) -> Result<ModerationStatus, GqlError> {
+    Ok(self.status)
+  }
+
+  /// The admin who approved or rejected the card, `null` while pending
+  fn field_moderated_by(
+    &self,
+    _: &Executor<'_, Context>,
+    _: &QueryTrail<'_, User, Walked>,
+  ) -> Result<&Option<User>, GqlError> {
+    Ok(&self.moderated_by)
+  }
+
+  /// When the card was approved or rejected (UTC, ISO 8601)
+  fn field_moderated_date(&self, _: &Executor<'_, Context>) -> Result<&Option<String>, GqlError> {
+    Ok(&self.moderated_date)
+  }
+
+  /// Why the card was rejected
+  fn field_reason(&self, _: &Executor<'_, Context>) -> Result<&Option<String>, GqlError> {
+    Ok(&self.reason)
+  }
+}
+
+impl From<Segment> for CardSegment {
+  fn from(segment: Segment) -> CardSegment {
+    match segment {
+      Segment::Text(text) => CardSegment::CardTextSegment(CardTextSegment { text }),
+      Segment::Blank(ordinal) => CardSegment::CardBlankSegment(CardBlankSegment { ordinal }),
+      Segment::Emphasis(text) => CardSegment::CardEmphasisSegment(CardEmphasisSegment { text }),
+    }
+  }
+}
+
+pub struct CardTextSegment {
+  text: String,
+}
+
+impl CardTextSegmentFields for CardTextSegment {
+  fn field_text(&self, _: &Executor<'_, Context>) -> Result<&String, GqlError> {
+    Ok(&self.text)
+  }
+}
+
+pub struct CardBlankSegment {
+  ordinal: i32,
+}
+
+impl CardBlankSegmentFields for CardBlankSegment {
+  /// Position of the blank on the card, starting from 0
+  fn field_ordinal(&self, _: &Executor<'_, Context>) -> Result<i32, GqlError> {
+    Ok(self.ordinal)
+  }
+}
+
+pub struct CardEmphasisSegment {
+  text: String,
+}
+
+impl CardEmphasisSegmentFields for CardEmphasisSegment {
+  fn field_text(&self, _: &Executor<'_, Context>) -> Result<&String, GqlError> {
+    Ok(&self.text)
+  }
+}
+
+pub struct CardOperation {
   id: i32,
-  name: String,
+  format_text: String,
+  color: CardColor,
 }
 
-impl SetInfoFields for SetInfo {
+impl CardOperationFields for CardOperation {
   fn field_id(&self, _: &Executor<'_, Context>) -> Result<ID, GqlError> {
     Ok(ID::from(self.id.to_string()))
   }
 
-  fn field_name(&self, _: &Executor<'_, Context>) -> Result<&String, GqlError> {
-    Ok(&self.name)
+  fn field_format_text(&self, _: &Executor<'_, Context>) -> Result<&String, GqlError> {
+    Ok(&self.format_text)
+  }
+
+  fn field_color(&self, _: &Executor<'_, Context>) -> Result<CardColor, GqlError> {
+    Ok(self.color)
   }
 }
 
-pub struct SetResult {
-  results: Vec<SetInfo>,
-  last_cursor: Option<i32>,
+pub struct CardResult {
+  results: Vec<Card>,
+  last_cursor: Option<ID>,
   has_next_page: bool,
+  random_seed: Option<f32>,
 }
 
-impl SetResultFields for SetResult {
+impl CardResultFields for CardResult {
   fn field_results(
     &self,
-    _: &Executor<'_, Context>,
-    _: &QueryTrail<'_, SetInfo, Walked>,
-  ) -> Result<&Vec<SetInfo>, GqlError> {
+    executor: &Executor<'_, Context>,
+    trail: &QueryTrail<'_, Card, Walked>,
+  ) -> Result<&Vec<Card>, GqlError> {
+    let context = executor.context();
+    if trail.rating_breakdown().walk().is_some() {
+      let card_ids = self.results.iter().map(|c| c.id).collect::<Vec<_>>();
+      context.rating_breakdowns.load(&context.db, &card_ids)?;
+    }
+    if trail.moderation().walk().is_some() {
+      let card_ids = self
+        .results
+        .iter()
+        .filter(|c| c.moderation_visible(context))
+        .map(|c| c.id)
+        .collect::<Vec<_>>();
+      context.moderations.load(&context.db, &card_ids)?;
+    }
     Ok(&self.results)
   }
 
   fn field_last_cursor(&self, _: &Executor<'_, Context>) -> Result<Option<ID>, GqlError> {
-    Ok(self.last_cursor.map(|c| c.to_encoded_id()))
+    Ok(self.last_cursor.clone())
   }
 
   fn field_has_next_page(&self, _: &Executor<'_, Context>) -> Result<bool, GqlError> {
     Ok(self.has_next_page)
   }
+
+  fn field_random_seed(&self, _: &Executor<'_, Context>) -> Result<Option<ID>, GqlError> {
+    Ok(self.random_seed.map(|c| c.to_encoded_id()))
+  }
 }
 
-/// Biggest Blackest API Schema documentation
-/// Primary (only) access to cards in the database
-pub struct Query {}
+pub struct Set {
+  id: i32,
+  name: String,
+}
 
-impl QueryFields for Query {
+impl SetFields for Set {
+  fn field_id(&self, _: &Executor<'_, Context>) -> Result<ID, GqlError> {
+    Ok(ID::from(self.id.to_string()))
+  }
+
+  fn field_name(&self, _: &Executor<'_, Context>) -> Result<&String, GqlError> {
+    Ok(&self.name)
+  }
+
+  /// Most useful method. Can be used for "actual" gameplay. To *shuffle* the cards,
+  /// pass `randomized: true`, and use the resulting `randomSeed` to keep the
+  /// same card shuffle in subsequent results.
+  ///
+  /// Search field is a full-text-search implementation
   fn field_cards(
     &self,
     executor: &Executor<'_, Context>,
     _: &QueryTrail<'_, CardResult, Walked>,
     search: Option<String>,
-    color: Option<CardColor>,
+    card_color: Option<CardColor>,
+    pick: Option<i32>,
     pagination: Pagination,
-    set_ids: Option<Vec<juniper::ID>>,
     randomized: Option<bool>,
-    card_source: CardSource,
-  ) -> Result<CardResult, GqlError> {
-    //AWError handling
-    let limit = pagination.page_size;
+  ) -> Result<Option<CardResult>, GqlError> {
+    let mut get_cards = GetCards::default();
+    get_cards.search = search;
+    get_cards.card_sets = Some(vec![self.id]);
+    get_cards.pick = pick;
 
-    if limit > 1000 || limit < 0 {
-      return Err(GqlError::LimitOutOfBounds);
-    }
+    set_cards(executor, get_cards, card_color, pagination, randomized).map(Some)
+  }
+}
 
-    let mut get_cards = GetCards::default();
+/// Cards of a set or deck, shared by `Set.cards` and `Deck.cards`. The `search`,
+/// `card_sets`, `pick`, and `user_submitted` fields of `get_cards` are set by the caller.
+fn set_cards(
+  executor: &Executor<'_, Context>,
+  mut get_cards: GetCards,
+  card_color: Option<CardColor>,
+  pagination: Pagination,
+  randomized: Option<bool>,
+) -> Result<CardResult, GqlError> {
+  if pagination.page_size > 1000 || pagination.page_size < 0 {
+    return Err(GqlError::LimitOutOfBounds);
+  }
 
-    get_cards.n_cards = Some(limit + 1);
+  get_cards.n_cards = Some(pagination.page_size + 1);
 
-    if let Some(v) = set_ids {
-      get_cards.card_sets = Some(
-        v.iter()
-          .map(|i| i.parse().expect("Values must be numerical"))
-          .collect(),
-      );
+  match pagination.cursor.map(|v| i32::from_encoded_id(v)) {
+    Some(Ok(v)) => get_cards.previous_cursor = Some(v),
+    Some(Err(e)) => {
+      return Err(e.into());
     }
+    _ => get_cards.previous_cursor = None,
+  }
 
-    match pagination.cursor.map(|v| i32::from_encoded_id(v)) {
-      Some(Ok(v)) => get_cards.previous_cursor = Some(v),
+  get_cards.filter_black = match card_color {
+    Some(CardColor::Black) => Some(true),
+    Some(CardColor::White) => Some(false),
+    None => None,
+  };
+
+  if let Some(r) = randomized {
+    get_cards.get_random = Some(r);
+
+    match pagination.random_seed.map(|v| f32::from_encoded_id(v)) {
+      Some(Ok(v)) => get_cards.random_seed = Some(v),
       Some(Err(e)) => {
         return Err(e.into());
       }
-      _ => get_cards.previous_cursor = None,
+      None => get_cards.random_seed = Some(random::<f32>()),
     }
-    get_cards.search = search;
+  }
 
-    get_cards.user_submitted = match card_source {
-      CardSource::All => None,
-      CardSource::User => Some(true),
-      CardSource::Official => Some(false),
-    };
+  let db_cards = db::get_cards(&executor.context().db, &get_cards)?;
 
-    match color {
-      Some(CardColor::Black) => {
-        get_cards.filter_black = Some(true);
-      }
-      Some(CardColor::White) => {
-        get_cards.filter_black = Some(false);
-      }
-      _ => {}
-    }
+  let (db_cards, has_more, last_cursor) = page(db_cards, pagination.page_size, |r| r.id);
+  let last_cursor = match get_cards.get_random {
+    Some(true) => Some(get_cards.previous_cursor.unwrap_or(0) + pagination.page_size),
+    _ => last_cursor,
+  }
+  .map(|c| c.to_encoded_id());
 
-    if let Some(r) = randomized {
-      get_cards.get_random = Some(r);
+  let db_cards = db_cards.iter().map(Card::from).collect::<Vec<_>>();
 
-      match pagination.random_seed.map(|v| f32::from_encoded_id(v)) {
-        Some(Ok(v)) => get_cards.random_seed = Some(v),
-        Some(Err(e)) => {
-          return Err(e.into());
-        }
-        None => get_cards.random_seed = Some(random::<f32>()),
-      }
-    }
+  Ok(CardResult {
+    results: db_cards,
+    has_next_page: has_more,
+    last_cursor: last_cursor,
+    random_seed: get_cards.random_seed,
+  })
+}
 
-    let con = &executor.context().db;
-    let db_cards = db::get_cards(con, &get_cards)?;
+#[derive(Clone)]
+pub struct SetInfo {
+  id: i32,
+  name: String,
+}
 
-    let has_more = db_cards.iter().len() as i32 > limit;
-    let last_cursor = match get_cards.get_random {
-      Some(true) => Some(get_cards.previous_cursor.unwrap_or(0) + limit),
-      _ => db_cards.iter().nth(limit as usize - 1).map(|r| r.id),
-    };
+impl SetInfoFields for SetInfo {
+  fn field_id(&self, _: &Executor<'_, Context>) -> Result<ID, GqlError> {
+    Ok(ID::from(self.id.to_string()))
+  }
 
-    let db_cards = db_cards
-      .iter()
-      .take(limit as usize)
-      .map(|c| Card {
-        id: c.id,
-        color: match c.is_black {
-          true => CardColor::Black,
-          false => CardColor::White,
-        },
-        format_text: c.format_text.to_owned(),
-        set: SetInfo {
-          id: c.parent_set_id,
-          name: c.parent_set_name.to_owned(),
-        },
-        total_votes: c.total_votes,
-        average_rating: c.average_rating,
-      })
-      .collect::<Vec<_>>();
+  fn field_name(&self, _: &Executor<'_, Context>) -> Result<&String, GqlError> {
+    Ok(&self.name)
+  }
+}
 
-    Ok(CardResult {
-      results: db_cards,
-      has_next_page: has_more,
-      last_cursor: last_cursor,
-      random_seed: get_cards.random_seed,
+pub struct SetResult {
+  results: Vec<SetInfo>,
+  last_cursor: Option<i32>,
+  has_next_page: bool,
+}
+
+impl SetResultFields for SetResult {
+  fn field_results(
+    &self,
+    _: &Executor<'_, Context>,
+    _: &QueryTrail<'_, SetInfo, Walked>,
+  ) -> Result<&Vec<SetInfo>, GqlError> {
+    Ok(&self.results)
+  }
+
+  fn field_last_cursor(&self, _: &Executor<'_, Context>) -> Result<Option<ID>, GqlError> {
+    Ok(self.last_cursor.map(|c| c.to_encoded_id()))
+  }
+
+  fn field_has_next_page(&self, _: &Executor<'_, Context>) -> Result<bool, GqlError> {
+    Ok(self.has_next_page)
+  }
+}
+
+/// Top cards of a day, week, month, or year. Time-decks are stored as official
+/// sets, so they can be passed to `cards(sets:)` like any other set.
+pub struct TimeDeck {
+  id: i32,
+  name: String,
+  period: TimeDeckPeriod,
+  start_date: String,
+  end_date: String,
+}
+
+impl TimeDeck {
+  fn from_result(t: GetTimeDeckResults) -> Result<TimeDeck, GqlError> {
+    Ok(TimeDeck {
+      id: t.id,
+      name: t.name,
+      period: TimeDeckPeriod::from_name(&t.period).ok_or(GqlError::UnexpectedError)?,
+      start_date: t.start_date,
+      end_date: t.end_date,
     })
   }
+}
 
-  /// To get cards belonging to a specific set
-  /// The return types on this are different than the subsequent
-  /// `sets` field to reduce nested query results, and nested pagination
-  ///
-  /// This allows finer control of where the cards are coming from,
-  /// as well as allowing cards to be queried in a *shuffled* order
-  fn field_set(
+impl TimeDeckFields for TimeDeck {
+  fn field_id(&self, _: &Executor<'_, Context>) -> Result<ID, GqlError> {
+    Ok(ID::from(self.id.to_string()))
+  }
+
+  fn field_name(&self, _: &Executor<'_, Context>) -> Result<&String, GqlError> {
+    Ok(&self.name)
+  }
+
+  fn field_period(&self, _: &Executor<'_, Context>) -> Result<TimeDeckPeriod, GqlError> {
+    Ok(self.period)
+  }
+
+  /// First day of the period, formatted as `YYYY-MM-DD`
+  fn field_start_date(&self, _: &Executor<'_, Context>) -> Result<&String, GqlError> {
+    Ok(&self.start_date)
+  }
+
+  /// Last day of the period, formatted as `YYYY-MM-DD`
+  fn field_end_date(&self, _: &Executor<'_, Context>) -> Result<&String, GqlError> {
+    Ok(&self.end_date)
+  }
+
+  /// The cards of the time-deck, best scoring first
+  fn field_cards(
     &self,
     executor: &Executor<'_, Context>,
-    _: &QueryTrail<'_, Set, Walked>,
-    id: ID,
-  ) -> Result<Set, GqlError> {
-    let set = db::get_set_by_id(
+    trail: &QueryTrail<'_, Card, Walked>,
+    card_color: Option<CardColor>,
+  ) -> Result<Vec<Card>, GqlError> {
+    let mut get_cards = GetCards::default();
+    get_cards.card_sets = Some(vec![self.id]);
+    get_cards.user_submitted = None;
+    get_cards.n_cards = Some(1000);
+    get_cards.sort_by = CardSort::Score.name().map(String::from);
+    get_cards.sort_descending = Some(true);
+    get_cards.filter_black = match card_color {
+      Some(CardColor::Black) => Some(true),
+      Some(CardColor::White) => Some(false),
+      None => None,
+    };
+
+    let context = executor.context();
+    let db_cards = db::get_cards(&context.db, &get_cards)?;
+
+    if trail.rating_breakdown().walk().is_some() {
+      let card_ids = db_cards.iter().map(|c| c.id).collect::<Vec<_>>();
+      context.rating_breakdowns.load(&context.db, &card_ids)?;
+    }
+    Ok(db_cards.iter().map(Card::from).collect())
+  }
+}
+
+pub struct TimeDeckResult {
+  results: Vec<TimeDeck>,
+  last_cursor: Option<(i32, i32)>,
+  has_next_page: bool,
+}
+
+impl TimeDeckResultFields for TimeDeckResult {
+  fn field_results(
+    &self,
+    _: &Executor<'_, Context>,
+    _: &QueryTrail<'_, TimeDeck, Walked>,
+  ) -> Result<&Vec<TimeDeck>, GqlError> {
+    Ok(&self.results)
+  }
+
+  fn field_last_cursor(&self, _: &Executor<'_, Context>) -> Result<Option<ID>, GqlError> {
+    Ok(self.last_cursor.map(|c| c.to_encoded_id()))
+  }
+
+  fn field_has_next_page(&self, _: &Executor<'_, Context>) -> Result<bool, GqlError> {
+    Ok(self.has_next_page)
+  }
+}
+
+/// A user-owned deck. Decks are stored as sets with an owner, so they can be
+/// passed to `cards(sets:)` like any official set.
+#[derive(Clone)]
+pub struct Deck {
+  id: i32,
+  name: String,
+  visibility: DeckVisibility,
+  owner: User,
+}
+
+impl From<&GetDeckResults> for Deck {
+  fn from(deck: &GetDeckResults) -> Self {
+    Deck {
+      id: deck.id,
+      name: deck.name.to_owned(),
+      visibility: DeckVisibility::from_name(&deck.visibility).unwrap_or(DeckVisibility::Private),
+      owner: User {
+        id: deck.owner_user_id,
+        username: deck.owner_username.to_owned(),
+      },
+    }
+  }
+}
+
+impl Deck {
+  /// Unlisted and public decks are visible to anyone with their ID, while
+  /// private decks are only visible to the owner (and admins)
+  fn is_visible(&self, context: &Context) -> bool {
+    match self.visibility {
+      DeckVisibility::Private => self.is_owner(context, Scope::Read),
+      _ => true,
+    }
+  }
+
+  fn is_owner(&self, context: &Context, scope: Scope) -> bool {
+    context.require(scope).ok() == Some(self.owner.id) || context.has_scope(Scope::Admin)
+  }
+
+  /// Active decks by their IDs that are visible to the caller. Private decks of
+  /// other users result in a `GqlError::DeckNotFound` error, as if they did not exist.
+  fn visible(context: &Context, ids: &[i32]) -> Result<Vec<Deck>, GqlError> {
+    let decks = db::get_decks_by_ids(&context.db, ids)?
+      .iter()
+      .map(Deck::from)
+      .collect::<Vec<_>>();
+
+    match decks.iter().all(|d| d.is_visible(context)) {
+      true => Ok(decks),
+      false => Err(GqlError::DeckNotFound),
+    }
+  }
+
+  /// A visible deck by its ID
+  fn find(context: &Context, id: &ID) -> Result<Deck, GqlError> {
+    Deck::visible(context, &[id.parse()?])?
+      .pop()
+      .ok_or(GqlError::DeckNotFound)
+  }
+
+  /// A deck by its ID, which the caller must own (or be an admin) to modify
+  fn find_owned(context: &Context, id: &ID) -> Result<Deck, GqlError> {
+    context.require(Scope::Submit)?;
+    let deck = Deck::find(context, id)?;
+
+    match deck.is_owner(context, Scope::Submit) {
+      true => Ok(deck),
+      false => Err(GqlError::Forbidden),
+    }
+  }
+}
+
+impl DeckFields for Deck {
+  fn field_id(&self, _: &Executor<'_, Context>) -> Result<ID, GqlError> {
+    Ok(ID::from(self.id.to_string()))
+  }
+
+  fn field_name(&self, _: &Executor<'_, Context>) -> Result<&String, GqlError> {
+    Ok(&self.name)
+  }
+
+  fn field_visibility(&self, _: &Executor<'_, Context>) -> Result<DeckVisibility, GqlError> {
+    Ok(self.visibility)
+  }
+
+  fn field_owner(
+    &self,
+    _: &Executor<'_, Context>,
+    _: &QueryTrail<'_, User, Walked>,
+  ) -> Result<&User, GqlError> {
+    Ok(&self.owner)
+  }
+
+  /// Cards of the deck, which may include approved user-submitted cards.
+  /// Works the same way as `Set.cards`.
+  fn field_cards(
+    &self,
+    executor: &Executor<'_, Context>,
+    _: &QueryTrail<'_, CardResult, Walked>,
+    search: Option<String>,
+    card_color: Option<CardColor>,
+    pick: Option<i32>,
+    pagination: Pagination,
+    randomized: Option<bool>,
+  ) -> Result<CardResult, GqlError> {
+    let mut get_cards = GetCards::default();
+    get_cards.search = search;
+    get_cards.card_sets = Some(vec![self.id]);
+    get_cards.pick = pick;
+    get_cards.user_submitted = None;
+
+    set_cards(executor, get_cards, card_color, pagination, randomized)
+  }
+}
+
+pub struct DeckResult {
+  results: Vec<Deck>,
+  last_cursor: Option<i32>,
+  has_next_page: bool,
+}
+
+impl DeckResult {
+  /// Active decks of a user, ordered by deck. Private and unlisted decks are
+  /// only listed when `include_unlisted`.
+  fn for_user(
+    executor: &Executor<'_, Context>,
+    user_id: i32,
+    include_unlisted: bool,
+    pagination: Pagination,
+  ) -> Result<DeckResult, GqlError> {
+    let limit = pagination.page_size;
+
+    if limit > 1000 || limit < 0 {
+      return Err(GqlError::LimitOutOfBounds);
+    }
+
+    let cursor = match pagination.cursor.map(|v| i32::from_encoded_id(v)) {
+      Some(Ok(v)) => Some(v),
+      Some(Err(e)) => {
+        return Err(e.into());
+      }
+      None => None,
+    };
+
+    let db_decks = db::get_user_decks(
       &executor.context().db,
-      id.parse().expect("Id was not valid"),
+      &GetUserDecks {
+        user_id,
+        cursor,
+        n_results: limit + 1,
+        include_unlisted,
+      },
     )?;
-    Ok(Set {
-      id: set.id,
-      name: set.name,
+
+    let (db_decks, has_more, last_cursor) = page(db_decks, limit, |r| r.id);
+
+    Ok(DeckResult {
+      results: db_decks.iter().map(Deck::from).collect(),
+      has_next_page: has_more,
+      last_cursor,
     })
   }
+}
+
+impl DeckResultFields for DeckResult {
+  fn field_results(
+    &self,
+    _: &Executor<'_, Context>,
+    _: &QueryTrail<'_, Deck, Walked>,
+  ) -> Result<&Vec<Deck>, GqlError> {
+    Ok(&self.results)
+  }
+
+  fn field_last_cursor(&self, _: &Executor<'_, Context>) -> Result<Option<ID>, GqlError> {
+    Ok(self.last_cursor.map(|c| c.to_encoded_id()))
+  }
+
+  fn field_has_next_page(&self, _: &Executor<'_, Context>) -> Result<bool, GqlError> {
+    Ok(self.has_next_page)
+  }
+}
+
+/// A card along with the active reports of it. The card is included even
+/// when its reports have deactivated it.
+pub struct ReportedCard {
+  id: i32,
+  format_text: String,
+  color: CardColor,
+  is_active: bool,
+  reports: Vec<CardReport>,
+}
+
+impl ReportedCardFields for ReportedCard {
+  fn field_id(&self, _: &Executor<'_, Context>) -> Result<ID, GqlError> {
+    Ok(ID::from(self.id.to_string()))
+  }
+
+  fn field_format_text(&self, _: &Executor<'_, Context>) -> Result<&String, GqlError> {
+    Ok(&self.format_text)
+  }
+
+  fn field_color(&self, _: &Executor<'_, Context>) -> Result<CardColor, GqlError> {
+    Ok(self.color)
+  }
+
+  /// `false` once the card has been hidden
+  fn field_is_active(&self, _: &Executor<'_, Context>) -> Result<bool, GqlError> {
+    Ok(self.is_active)
+  }
+
+  /// Reports of the card, from oldest to newest
+  fn field_reports(
+    &self,
+    _: &Executor<'_, Context>,
+    _: &QueryTrail<'_, CardReport, Walked>,
+  ) -> Result<&Vec<CardReport>, GqlError> {
+    Ok(&self.reports)
+  }
+}
+
+pub struct CardReport {
+  reported_by: User,
+  reason: String,
+  reported_date: String,
+}
+
+impl CardReportFields for CardReport {
+  fn field_reported_by(
+    &self,
+    _: &Executor<'_, Context>,
+    _: &QueryTrail<'_, User, Walked>,
+  ) -> Result<&User, GqlError> {
+    Ok(&self.reported_by)
+  }
+
+  fn field_reason(&self, _: &Executor<'_, Context>) -> Result<&String, GqlError> {
+    Ok(&self.reason)
+  }
+
+  /// When the card was reported (UTC, ISO 8601)
+  fn field_reported_date(&self, _: &Executor<'_, Context>) -> Result<&String, GqlError> {
+    Ok(&self.reported_date)
+  }
+}
+
+pub struct ReportedCardResult {
+  results: Vec<ReportedCard>,
+  last_cursor: Option<i32>,
+  has_next_page: bool,
+}
+
+impl ReportedCardResultFields for ReportedCardResult {
+  fn field_results(
+    &self,
+    _: &Executor<'_, Context>,
+    _: &QueryTrail<'_, ReportedCard, Walked>,
+  ) -> Result<&Vec<ReportedCard>, GqlError> {
+    Ok(&self.results)
+  }
+
+  fn field_last_cursor(&self, _: &Executor<'_, Context>) -> Result<Option<ID>, GqlError> {
+    Ok(self.last_cursor.map(|c| c.to_encoded_id()))
+  }
+
+  fn field_has_next_page(&self, _: &Executor<'_, Context>) -> Result<bool, GqlError> {
+    Ok(self.has_next_page)
+  }
+}
+
+#[derive(Clone)]
+pub struct User {
+  id: i32,
+  username: String,
+}
+
+impl User {
+  /// A user's ratings and API keys are only visible to that user (and admins)
+  fn require_self(&self, executor: &Executor<'_, Context>) -> Result<(), GqlError> {
+    let context = executor.context();
+    match context.require(Scope::Read)? == self.id || context.has_scope(Scope::Admin) {
+      true => Ok(()),
+      false => Err(GqlError::Forbidden),
+    }
+  }
+}
+
+impl UserFields for User {
+  fn field_id(&self, _: &Executor<'_, Context>) -> Result<ID, GqlError> {
+    Ok(ID::from(self.id.to_string()))
+  }
+
+  fn field_username(&self, _: &Executor<'_, Context>) -> Result<&String, GqlError> {
+    Ok(&self.username)
+  }
+
+  /// Active cards submitted through `addCard`, in the order they were submitted.
+  /// Cards that have not been approved are only listed for the user (and admins).
+  fn field_submitted_cards(
+    &self,
+    executor: &Executor<'_, Context>,
+    _: &QueryTrail<'_, CardResult, Walked>,
+    pagination: Pagination,
+  ) -> Result<CardResult, GqlError> {
+    let limit = pagination.page_size;
+
+    if limit > 1000 || limit < 0 {
+      return Err(GqlError::LimitOutOfBounds);
+    }
+
+    let cursor = match pagination.cursor.map(|v| i32::from_encoded_id(v)) {
+      Some(Ok(v)) => Some(v),
+      Some(Err(e)) => {
+        return Err(e.into());
+      }
+      None => None,
+    };
+
+    let db_cards = db::get_user_submitted_cards(
+      &executor.context().db,
+      &GetUserSubmittedCards {
+        user_id: self.id,
+        cursor,
+        n_results: limit + 1,
+        include_unapproved: self.require_self(executor).is_ok(),
+      },
+    )?;
+
+    let (db_cards, has_more, last_cursor) = page(db_cards, limit, |r| r.id.to_encoded_id());
+
+    Ok(CardResult {
+      results: db_cards.iter().map(Card::from).collect(),
+      has_next_page: has_more,
+      last_cursor,
+      random_seed: None,
+    })
+  }
+
+  /// The user's own card ratings, ordered by card. Only available to the user.
+  fn field_ratings(
+    &self,
+    executor: &Executor<'_, Context>,
+    _: &QueryTrail<'_, UserCardRatingResult, Walked>,
+    pagination: Pagination,
+  ) -> Result<UserCardRatingResult, GqlError> {
+    self.require_self(executor)?;
+    let limit = pagination.page_size;
+
+    if limit > 1000 || limit < 0 {
+      return Err(GqlError::LimitOutOfBounds);
+    }
+
+    let cursor = match pagination.cursor.map(|v| i32::from_encoded_id(v)) {
+      Some(Ok(v)) => Some(v),
+      Some(Err(e)) => {
+        return Err(e.into());
+      }
+      None => None,
+    };
+
+    let con = &executor.context().db;
+    let db_ratings = db::get_user_card_ratings(
+      con,
+      &GetUserHistory {
+        user_id: self.id,
+        cursor,
+        n_results: limit + 1,
+      },
+    )?;
+
+    let (db_ratings, has_more, last_cursor) = page(db_ratings, limit, |r| r.card_id);
+
+    let card_ids = db_ratings.iter().map(|r| r.card_id).collect::<Vec<_>>();
+    let mut cards = executor
+      .context()
+      .visible_cards_by_ids(&card_ids)?
+      .iter()
+      .map(|c| (c.id, Card::from(c)))
+      .collect::<HashMap<_, _>>();
+
+    Ok(UserCardRatingResult {
+      results: db_ratings
+        .iter()
+        .filter_map(|r| {
+          cards.remove(&r.card_id).map(|card| UserCardRating {
+            card,
+            rating: r.rating,
+          })
+        })
+        .collect(),
+      has_next_page: has_more,
+      last_cursor,
+    })
+  }
+
+  /// The user's own card combination ratings, ordered by black card, white card,
+  /// and ordinal. Only available to the user.
+  fn field_combo_ratings(
+    &self,
+    executor: &Executor<'_, Context>,
+    _: &QueryTrail<'_, UserCardComboRatingResult, Walked>,
+    pagination: Pagination,
+  ) -> Result<UserCardComboRatingResult, GqlError> {
+    self.require_self(executor)?;
+    let limit = pagination.page_size;
+
+    if limit > 1000 || limit < 0 {
+      return Err(GqlError::LimitOutOfBounds);
+    }
+
+    let cursor = match pagination
+      .cursor
+      .map(|v| <(i32, i32, i32)>::from_encoded_id(v))
+    {
+      Some(Ok(v)) => Some(v),
+      Some(Err(e)) => {
+        return Err(e.into());
+      }
+      None => None,
+    };
+
+    let con = &executor.context().db;
+    let db_ratings = db::get_user_card_combination_ratings(
+      con,
+      &GetUserCardCombinationRatings {
+        user_id: self.id,
+        cursor,
+        n_results: limit + 1,
+      },
+    )?;
+
+    let (db_ratings, has_more, last_cursor) = page(db_ratings, limit, |r| {
+      (r.black_card_id, r.white_card_id, r.ordinal)
+    });
+
+    let card_ids = db_ratings
+      .iter()
+      .flat_map(|r| vec![r.black_card_id, r.white_card_id])
+      .collect::<Vec<_>>();
+    let cards = executor
+      .context()
+      .visible_cards_by_ids(&card_ids)?
+      .iter()
+      .map(|c| (c.id, Card::from(c)))
+      .collect::<HashMap<_, _>>();
+
+    Ok(UserCardComboRatingResult {
+      results: db_ratings
+        .iter()
+        .filter_map(
+          |r| match (cards.get(&r.black_card_id), cards.get(&r.white_card_id)) {
+            (Some(black_card), Some(white_card)) => Some(UserCardComboRating {
+              black_card: black_card.clone(),
+              white_card: white_card.clone(),
+              ordinal: r.ordinal,
+              rating: r.rating,
+            }),
+            _ => None,
+          },
+        )
+        .collect(),
+      has_next_page: has_more,
+      last_cursor,
+    })
+  }
+
+  /// Active API keys of the user. Only available to the user.
+  fn field_api_keys(
+    &self,
+    executor: &Executor<'_, Context>,
+    _: &QueryTrail<'_, ApiKey, Walked>,
+  ) -> Result<Vec<ApiKey>, GqlError> {
+    self.require_self(executor)?;
+
+    let api_keys = db::get_user_api_keys(&executor.context().db, self.id)?;
+
+    Ok(
+      api_keys
+        .into_iter()
+        .map(|k| ApiKey {
+          id: k.id,
+          name: k.name,
+          scopes: k
+            .scopes
+            .iter()
+            .filter_map(|s| Scope::from_name(s))
+            .map(ApiScope::from)
+            .collect(),
+        })
+        .collect(),
+    )
+  }
+
+  /// Public decks of the user. The user (and admins) also see their private
+  /// and unlisted decks.
+  fn field_decks(
+    &self,
+    executor: &Executor<'_, Context>,
+    _: &QueryTrail<'_, DeckResult, Walked>,
+    pagination: Pagination,
+  ) -> Result<DeckResult, GqlError> {
+    let include_unlisted = self.require_self(executor).is_ok();
+    DeckResult::for_user(executor, self.id, include_unlisted, pagination)
+  }
+}
+
+pub struct ApiKey {
+  id: i32,
+  name: String,
+  scopes: Vec<ApiScope>,
+}
+
+impl ApiKeyFields for ApiKey {
+  fn field_id(&self, _: &Executor<'_, Context>) -> Result<ID, GqlError> {
+    Ok(ID::from(self.id.to_string()))
+  }
+
+  fn field_name(&self, _: &Executor<'_, Context>) -> Result<&String, GqlError> {
+    Ok(&self.name)
+  }
+
+  fn field_scopes(&self, _: &Executor<'_, Context>) -> Result<&Vec<ApiScope>, GqlError> {
+    Ok(&self.scopes)
+  }
+}
+
+pub struct ApiKeyCreation {
+  api_key: ApiKey,
+  key: String,
+}
+
+impl ApiKeyCreationFields for ApiKeyCreation {
+  fn field_api_key(
+    &self,
+    _: &Executor<'_, Context>,
+    _: &QueryTrail<'_, ApiKey, Walked>,
+  ) -> Result<&ApiKey, GqlError> {
+    Ok(&self.api_key)
+  }
+
+  /// The key to send as `Authorization: ApiKey {key}`. This is the only
+  /// time the key is returned.
+  fn field_key(&self, _: &Executor<'_, Context>) -> Result<&String, GqlError> {
+    Ok(&self.key)
+  }
+}
+
+pub struct UserCardRating {
+  card: Card,
+  rating: f32,
+}
+
+impl UserCardRatingFields for UserCardRating {
+  fn field_card(
+    &self,
+    _: &Executor<'_, Context>,
+    _: &QueryTrail<'_, Card, Walked>,
+  ) -> Result<&Card, GqlError> {
+    Ok(&self.card)
+  }
+
+  fn field_rating(&self, _: &Executor<'_, Context>) -> Result<f64, GqlError> {
+    Ok(self.rating.into())
+  }
+}
+
+pub struct UserCardRatingResult {
+  results: Vec<UserCardRating>,
+  last_cursor: Option<i32>,
+  has_next_page: bool,
+}
+
+impl UserCardRatingResultFields for UserCardRatingResult {
+  fn field_results(
+    &self,
+    _: &Executor<'_, Context>,
+    _: &QueryTrail<'_, UserCardRating, Walked>,
+  ) -> Result<&Vec<UserCardRating>, GqlError> {
+    Ok(&self.results)
+  }
+
+  fn field_last_cursor(&self, _: &Executor<'_, Context>) -> Result<Option<ID>, GqlError> {
+    Ok(self.last_cursor.map(|c| c.to_encoded_id()))
+  }
+
+  fn field_has_next_page(&self, _: &Executor<'_, Context>) -> Result<bool, GqlError> {
+    Ok(self.has_next_page)
+  }
+}
+
+pub struct UserCardComboRating {
+  black_card: Card,
+  white_card: Card,
+  ordinal: i32,
+  rating: f32,
+}
+
+impl UserCardComboRatingFields for UserCardComboRating {
+  fn field_black_card(
+    &self,
+    _: &Executor<'_, Context>,
+    _: &QueryTrail<'_, Card, Walked>,
+  ) -> Result<&Card, GqlError> {
+    Ok(&self.black_card)
+  }
+
+  fn field_white_card(
+    &self,
+    _: &Executor<'_, Context>,
+    _: &QueryTrail<'_, Card, Walked>,
+  ) -> Result<&Card, GqlError> {
+    Ok(&self.white_card)
+  }
+
+  fn field_ordinal(&self, _: &Executor<'_, Context>) -> Result<i32, GqlError> {
+    Ok(self.ordinal)
+  }
+
+  fn field_rating(&self, _: &Executor<'_, Context>) -> Result<f64, GqlError> {
+    Ok(self.rating.into())
+  }
+}
+
+pub struct UserCardComboRatingResult {
+  results: Vec<UserCardComboRating>,
+  last_cursor: Option<(i32, i32, i32)>,
+  has_next_page: bool,
+}
+
+impl UserCardComboRatingResultFields for UserCardComboRatingResult {
+  fn field_results(
+    &self,
+    _: &Executor<'_, Context>,
+    _: &QueryTrail<'_, UserCardComboRating, Walked>,
+  ) -> Result<&Vec<UserCardComboRating>, GqlError> {
+    Ok(&self.results)
+  }
+
+  fn field_last_cursor(&self, _: &Executor<'_, Context>) -> Result<Option<ID>, GqlError> {
+    Ok(self.last_cursor.map(|c| c.to_encoded_id()))
+  }
+
+  fn field_has_next_page(&self, _: &Executor<'_, Context>) -> Result<bool, GqlError> {
+    Ok(self.has_next_page)
+  }
+}
+
+pub struct UserRegistration {
+  user: User,
+  token: String,
+}
+
+impl UserRegistrationFields for UserRegistration {
+  fn field_user(
+    &self,
+    _: &Executor<'_, Context>,
+    _: &QueryTrail<'_, User, Walked>,
+  ) -> Result<&User, GqlError> {
+    Ok(&self.user)
+  }
+
+  /// Bearer token for the new user. This is the only time the token is
+  /// returned, and is the only credential of the account until a login
+  /// provider is linked through `/login`.
+  fn field_token(&self, _: &Executor<'_, Context>) -> Result<&String, GqlError> {
+    Ok(&self.token)
+  }
+}
+
+/// Biggest Blackest API Schema documentation
+/// Primary (only) access to cards in the database
+pub struct Query {}
+
+impl QueryFields for Query {
+  fn field_cards(
+    &self,
+    executor: &Executor<'_, Context>,
+    _: &QueryTrail<'_, CardResult, Walked>,
+    search: Option<String>,
+    color: Option<CardColor>,
+    pick: Option<i32>,
+    pagination: Pagination,
+    set_ids: Option<Vec<juniper::ID>>,
+    randomized: Option<bool>,
+    card_source: CardSource,
+    sort_by: CardSort,
+    direction: Option<SortDirection>,
+  ) -> Result<CardResult, GqlError> {
+    //AWError handling
+    let limit = pagination.page_size;
+
+    if limit > 1000 || limit < 0 {
+      return Err(GqlError::LimitOutOfBounds);
+    }
+
+    let mut get_cards = GetCards::default();
+
+    get_cards.n_cards = Some(limit + 1);
+
+    if let Some(v) = set_ids {
+      let card_sets = v
+        .iter()
+        .map(|i| i.parse::<i32>())
+        .collect::<Result<Vec<_>, _>>()?;
+
+      Deck::visible(executor.context(), &card_sets)?;
+      get_cards.card_sets = Some(card_sets);
+    }
+
+    let sort_by = match randomized {
+      Some(true) => CardSort::Id,
+      _ => sort_by,
+    };
+    if sort_by == CardSort::SearchRelevance && search.is_none() {
+      return Err(GqlError::SearchRequired);
+    }
+    get_cards.sort_by = sort_by.name().map(String::from);
+    get_cards.sort_descending =
+      Some(direction.unwrap_or_else(|| sort_by.default_direction()) == SortDirection::Desc);
+
+    // Cards sorted by ID are paginated by the ID of the last card, and by its
+    // sort key along with its ID otherwise
+    match (sort_by, pagination.cursor) {
+      (CardSort::Id, Some(v)) => get_cards.previous_cursor = Some(i32::from_encoded_id(v)?),
+      (_, Some(v)) => {
+        let (sort_key, id) = <(f64, i32)>::from_encoded_id(v)?;
+        get_cards.previous_sort_key = Some(sort_key);
+        get_cards.previous_cursor = Some(id);
+      }
+      (_, None) => get_cards.previous_cursor = None,
+    }
+    get_cards.search = search;
+    get_cards.pick = pick;
+
+    get_cards.user_submitted = match card_source {
+      CardSource::All => None,
+      CardSource::User => Some(true),
+      CardSource::Official => Some(false),
+    };
+
+    match color {
+      Some(CardColor::Black) => {
+        get_cards.filter_black = Some(true);
+      }
+      Some(CardColor::White) => {
+        get_cards.filter_black = Some(false);
+      }
+      _ => {}
+    }
+
+    if let Some(r) = randomized {
+      get_cards.get_random = Some(r);
+
+      match pagination.random_seed.map(|v| f32::from_encoded_id(v)) {
+        Some(Ok(v)) => get_cards.random_seed = Some(v),
+        Some(Err(e)) => {
+          return Err(e.into());
+        }
+        None => get_cards.random_seed = Some(random::<f32>()),
+      }
+    }
+
+    let con = &executor.context().db;
+    let db_cards = db::get_cards(con, &get_cards)?;
+
+    let (db_cards, has_more, last_cursor) =
+      page(db_cards, limit, |r| match (sort_by, r.sort_key) {
+        (CardSort::Id, _) | (_, None) => r.id.to_encoded_id(),
+        (_, Some(sort_key)) => (sort_key, r.id).to_encoded_id(),
+      });
+    let last_cursor = match get_cards.get_random {
+      Some(true) => Some((get_cards.previous_cursor.unwrap_or(0) + limit).to_encoded_id()),
+      _ => last_cursor,
+    };
+
+    let db_cards = db_cards.iter().map(Card::from).collect::<Vec<_>>();
+
+    Ok(CardResult {
+      results: db_cards,
+      has_next_page: has_more,
+      last_cursor: last_cursor,
+      random_seed: get_cards.random_seed,
+    })
+  }
+
+  /// A single card by its ID. Unknown and inactive cards result in a
+  /// `GqlError::CardNotFound` error.
+  fn field_card(
+    &self,
+    executor: &Executor<'_, Context>,
+    _: &QueryTrail<'_, Card, Walked>,
+    id: ID,
+  ) -> Result<Card, GqlError> {
+    let card_id = id.parse()?;
+    let db_cards = executor.context().visible_cards_by_ids(&[card_id])?;
+
+    db_cards
+      .first()
+      .map(Card::from)
+      .ok_or_else(|| GqlError::CardNotFound(vec![id.to_string()]))
+  }
+
+  /// Cards by their IDs, returned in the same order as `ids`. This is intended
+  /// for game servers holding on to card IDs. When any of the cards is unknown
+  /// or inactive, the missing IDs are listed in the `GqlError::CardNotFound` error.
+  fn field_cards_by_ids(
+    &self,
+    executor: &Executor<'_, Context>,
+    trail: &QueryTrail<'_, Card, Walked>,
+    ids: Vec<ID>,
+  ) -> Result<Vec<Card>, GqlError> {
+    if ids.len() > 1000 {
+      return Err(GqlError::LimitOutOfBounds);
+    }
+
+    let card_ids = ids
+      .iter()
+      .map(|id| id.parse::<i32>())
+      .collect::<Result<Vec<_>, _>>()?;
+
+    let context = executor.context();
+    let cards = context
+      .visible_cards_by_ids(&card_ids)?
+      .iter()
+      .map(|c| (c.id, Card::from(c)))
+      .collect::<HashMap<_, _>>();
+
+    let missing = card_ids
+      .iter()
+      .filter(|id| !cards.contains_key(id))
+      .map(|id| id.to_string())
+      .collect::<Vec<_>>();
+
+    if !missing.is_empty() {
+      return Err(GqlError::CardNotFound(missing));
+    }
+
+    if trail.rating_breakdown().walk().is_some() {
+      context.rating_breakdowns.load(&context.db, &card_ids)?;
+    }
+    if trail.moderation().walk().is_some() {
+      let card_ids = cards
+        .values()
+        .filter(|c| c.moderation_visible(context))
+        .map(|c| c.id)
+        .collect::<Vec<_>>();
+      context.moderations.load(&context.db, &card_ids)?;
+    }
+
+    Ok(card_ids.iter().map(|id| cards[id].clone()).collect())
+  }
+
+  /// To get cards belonging to a specific set
+  /// The return types on this are different than the subsequent
+  /// `sets` field to reduce nested query results, and nested pagination
+  ///
+  /// This allows finer control of where the cards are coming from,
+  /// as well as allowing cards to be queried in a *shuffled* order
+  fn field_set(
+    &self,
+    executor: &Executor<'_, Context>,
+    _: &QueryTrail<'_, Set, Walked>,
+    id: ID,
+  ) -> Result<Set, GqlError> {
+    let set =
+      db::get_active_set(&executor.context().db, id.parse()?)?.ok_or(GqlError::SetNotFound)?;
+    Ok(Set {
+      id: set.id,
+      name: set.name,
+    })
+  }
+
+  /// A user-owned deck by its ID. Private decks are only visible to their owner.
+  fn field_deck(
+    &self,
+    executor: &Executor<'_, Context>,
+    _: &QueryTrail<'_, Deck, Walked>,
+    id: ID,
+  ) -> Result<Deck, GqlError> {
+    Deck::find(executor.context(), &id)
+  }
+
+  /// A game by its ID. Games are visible to anyone with their ID, but only the
+  /// hand of the authenticated player is returned.
+  fn field_game(
+    &self,
+    executor: &Executor<'_, Context>,
+    _: &QueryTrail<'_, Game, Walked>,
+    id: ID,
+  ) -> Result<Game, GqlError> {
+    Game::find(executor.context(), id.parse()?)
+  }
+
+  /// The authenticated user, `null` when the request is anonymous
+  fn field_me(
+    &self,
+    executor: &Executor<'_, Context>,
+    _: &QueryTrail<'_, User, Walked>,
+  ) -> Result<Option<User>, GqlError> {
+    match executor.context().caller.as_ref().map(|c| c.user_id) {
+      Some(id) => {
+        let user = db::get_user_by_id(&executor.context().db, id)?;
+        Ok(user.map(|u| User {
+          id: u.id,
+          username: u.username,
+        }))
+      }
+      None => Ok(None),
+    }
+  }
+
+  /// Public profile of a user, `null` when the user does not exist
+  /// or is no longer active
+  fn field_user(
+    &self,
+    executor: &Executor<'_, Context>,
+    _: &QueryTrail<'_, User, Walked>,
+    id: ID,
+  ) -> Result<Option<User>, GqlError> {
+    let user = db::get_user_by_id(&executor.context().db, id.parse()?)?;
+    Ok(user.map(|u| User {
+      id: u.id,
+      username: u.username,
+    }))
+  }
+
+  /// All active decks of the authenticated user, ordered by deck
+  fn field_my_decks(
+    &self,
+    executor: &Executor<'_, Context>,
+    _: &QueryTrail<'_, DeckResult, Walked>,
+    pagination: Pagination,
+  ) -> Result<DeckResult, GqlError> {
+    let user_id = executor.context().require(Scope::Read)?;
+    DeckResult::for_user(executor, user_id, true, pagination)
+  }
+
+  /// Cards submitted through `addCard` waiting to be approved or rejected,
+  /// in the order they were submitted. Only available to admins.
+  fn field_moderation_queue(
+    &self,
+    executor: &Executor<'_, Context>,
+    _: &QueryTrail<'_, CardResult, Walked>,
+    pagination: Pagination,
+  ) -> Result<CardResult, GqlError> {
+    executor.context().require(Scope::Admin)?;
+    let limit = pagination.page_size;
+
+    if limit > 1000 || limit < 0 {
+      return Err(GqlError::LimitOutOfBounds);
+    }
+
+    let cursor = match pagination.cursor.map(|v| i32::from_encoded_id(v)) {
+      Some(Ok(v)) => Some(v),
+      Some(Err(e)) => {
+        return Err(e.into());
+      }
+      None => None,
+    };
+
+    let db_cards = db::get_moderation_queue(
+      &executor.context().db,
+      &GetModerationQueue {
+        cursor,
+        n_results: limit + 1,
+      },
+    )?;
+
+    let (db_cards, has_more, last_cursor) = page(db_cards, limit, |r| r.id.to_encoded_id());
+
+    Ok(CardResult {
+      results: db_cards.iter().map(Card::from).collect(),
+      has_next_page: has_more,
+      last_cursor,
+      random_seed: None,
+    })
+  }
+
+  /// Reported cards along with their reports, ordered by card. Cards hidden by
+  /// their reports are included. Only available to admins.
+  fn field_card_reports(
+    &self,
+    executor: &Executor<'_, Context>,
+    _: &QueryTrail<'_, ReportedCardResult, Walked>,
+    pagination: Pagination,
+  ) -> Result<ReportedCardResult, GqlError> {
+    executor.context().require(Scope::Admin)?;
+    let limit = pagination.page_size;
+
+    if limit > 1000 || limit < 0 {
+      return Err(GqlError::LimitOutOfBounds);
+    }
+
+    let cursor = match pagination.cursor.map(|v| i32::from_encoded_id(v)) {
+      Some(Ok(v)) => Some(v),
+      Some(Err(e)) => {
+        return Err(e.into());
+      }
+      None => None,
+    };
+
+    let db_reports = db::get_card_reports(
+      &executor.context().db,
+      &GetCardReports {
+        cursor,
+        n_results: limit + 1,
+      },
+    )?;
+
+    let (db_reports, has_more, last_cursor) = page(db_reports, limit, |r| r.card_id);
+
+    Ok(ReportedCardResult {
+      results: db_reports
+        .into_iter()
+        .map(|r| ReportedCard {
+          id: r.card_id,
+          format_text: r.format_text,
+          color: match r.is_black {
+            true => CardColor::Black,
+            false => CardColor::White,
+          },
+          is_active: r.is_active,
+          reports: r
+            .reported_by_user_ids
+            .into_iter()
+            .zip(r.reported_by_usernames)
+            .zip(r.reasons)
+            .zip(r.reported_dates)
+            .map(|(((id, username), reason), reported_date)| CardReport {
+              reported_by: User { id, username },
+              reason,
+              reported_date,
+            })
+            .collect(),
+        })
+        .collect(),
+      has_next_page: has_more,
+      last_cursor,
+    })
+  }
+
+  /// This returns all of the card sets within the database,
+  /// or the matched sets when using the `search` parameter.
+  /// `search` allows for a full-text-search of the set name
+  fn field_sets(
+    &self,
+    executor: &Executor<'_, Context>,
+    _: &QueryTrail<'_, SetResult, Walked>,
+    search: Option<String>,
+    pagination: Pagination,
+  ) -> Result<SetResult, GqlError> {
+    let limit = pagination.page_size;
+
+    if limit > 1000 || limit < 0 {
+      return Err(GqlError::LimitOutOfBounds);
+    }
+
+    let mut get_sets = GetSets::default();
+    get_sets.n_results = Some(limit);
+    get_sets.search = search;
+
+    match pagination.cursor.map(|v| i32::from_encoded_id(v)) {
+      Some(Ok(v)) => get_sets.cursor = Some(v),
+      Some(Err(e)) => {
+        return Err(e.into());
+      }
+      _ => get_sets.cursor = None,
+    }
+
+    let con = &executor.context().db;
+    let db_sets = db::get_sets(con, &get_sets)?;
+
+    let has_more = db_sets.iter().len() as i32 > limit;
+    let last_cursor = db_sets.iter().nth(limit as usize - 1).map(|r| r.id);
+
+    let db_sets = db_sets
+      .iter()
+      .take(limit as usize)
+      .map(|s| SetInfo {
+        id: s.id,
+        name: s.name.to_owned(),
+      })
+      .collect::<Vec<_>>();
+
+    Ok(SetResult {
+      results: db_sets,
+      has_next_page: has_more,
+      last_cursor: last_cursor,
+    })
+  }
+
+  /// Generated time-decks of a period (or of every period), most recent first
+  fn field_time_decks(
+    &self,
+    executor: &Executor<'_, Context>,
+    _: &QueryTrail<'_, TimeDeckResult, Walked>,
+    period: Option<TimeDeckPeriod>,
+    pagination: Pagination,
+  ) -> Result<TimeDeckResult, GqlError> {
+    let limit = pagination.page_size;
+
+    if limit > 1000 || limit < 0 {
+      return Err(GqlError::LimitOutOfBounds);
+    }
+
+    let cursor = match pagination.cursor.map(|v| <(i32, i32)>::from_encoded_id(v)) {
+      Some(Ok(v)) => Some(v),
+      Some(Err(e)) => {
+        return Err(e.into());
+      }
+      None => None,
+    };
+
+    let db_time_decks = db::get_time_decks(
+      &executor.context().db,
+      &GetTimeDecks {
+        period: period.map(|p| p.name().to_string()),
+        cursor,
+        n_results: limit + 1,
+      },
+    )?;
+
+    let (db_time_decks, has_more, last_cursor) =
+      page(db_time_decks, limit, |t| (t.start_day, t.id));
+
+    Ok(TimeDeckResult {
+      results: db_time_decks
+        .into_iter()
+        .map(TimeDeck::from_result)
+        .collect::<Result<Vec<_>, _>>()?,
+      has_next_page: has_more,
+      last_cursor,
+    })
+  }
+
+  /// The time-deck of the period containing a day (`YYYY-MM-DD`)
+  fn field_time_deck(
+    &self,
+    executor: &Executor<'_, Context>,
+    _: &QueryTrail<'_, TimeDeck, Walked>,
+    period: TimeDeckPeriod,
+    date: String,
+  ) -> Result<TimeDeck, GqlError> {
+    if !is_valid_date(&date) {
+      return Err(GqlError::InvalidDate);
+    }
+
+    let time_deck = db::get_time_deck(
+      &executor.context().db,
+      &GetTimeDeck {
+        period: period.name().to_string(),
+        date,
+      },
+    )?
+    .ok_or(GqlError::TimeDeckNotFound)?;
+    TimeDeck::from_result(time_deck)
+  }
+
+  fn field_license(&self, _: &Executor<'_, Context>) -> Result<Url, GqlError> {
+    Ok(Url::parse(
+      "https://creativecommons.org/licenses/by-nc-sa/2.0/legalcode",
+    )?)
+  }
+
+  fn field_api_version(&self, _: &Executor<'_, Context>) -> Result<String, GqlError> {
+    Ok(String::from("0.1.0"))
+  }
+
+  fn field_authors(&self, _: &Executor<'_, Context>) -> Result<Vec<String>, GqlError> {
+    Ok(vec![
+      String::from("Nicholas Dolan"),
+      String::from("Cameron Otts"),
+      String::from("Grace Chenevert"),
+      String::from("Aaron Dentro"),
+      String::from("Patrick Dolan"),
+    ])
+  }
+
+  fn field_cards_against_humanity(
+    &self,
+    _: &Executor<'_, Context>,
+    _: &QueryTrail<'_, CardsAgainstHumanity, Walked>,
+  ) -> Result<CardsAgainstHumanity, GqlError> {
+    Ok(CardsAgainstHumanity {})
+  }
+
+  /// Usage statistics of the cards and ratings, for tracking how the community
+  /// uses the deck
+  fn field_stats(
+    &self,
+    _: &Executor<'_, Context>,
+    _: &QueryTrail<'_, Stats, Walked>,
+  ) -> Result<Stats, GqlError> {
+    Ok(Stats {})
+  }
+}
+
+pub struct CardRatingResult {
+  id: i32,
+  rating: f32,
+  total_votes: i32,
+  average_rating: f32,
+  previous_rating: Option<f32>,
+}
+
+impl CardRatingResultFields for CardRatingResult {
+  fn field_id(&self, _: &Executor<'_, Context>) -> Result<ID, GqlError> {
+    Ok(ID::from(self.id.to_string()))
+  }
+
+  fn field_rating(&self, _: &Executor<'_, Context>) -> Result<f64, GqlError> {
+    Ok(self.rating.into())
+  }
+
+  fn field_total_votes(&self, _: &Executor<'_, Context>) -> Result<i32, GqlError> {
+    Ok(self.total_votes)
+  }
+
+  fn field_average_rating(&self, _: &Executor<'_, Context>) -> Result<f64, GqlError> {
+    Ok(self.average_rating.into())
+  }
+
+  /// The user's rating of the card before this one, `null` for a first vote
+  fn field_previous_rating(&self, _: &Executor<'_, Context>) -> Result<Option<f64>, GqlError> {
+    Ok(self.previous_rating.map(f64::from))
+  }
+}
+
+pub struct CardUnrateResult {
+  id: i32,
+  previous_rating: Option<f32>,
+  total_votes: i32,
+  average_rating: Option<f32>,
+}
+
+impl CardUnrateResultFields for CardUnrateResult {
+  fn field_id(&self, _: &Executor<'_, Context>) -> Result<ID, GqlError> {
+    Ok(ID::from(self.id.to_string()))
+  }
+
+  /// The removed rating, `null` when the user had not rated the card
+  fn field_previous_rating(&self, _: &Executor<'_, Context>) -> Result<Option<f64>, GqlError> {
+    Ok(self.previous_rating.map(f64::from))
+  }
+
+  fn field_total_votes(&self, _: &Executor<'_, Context>) -> Result<i32, GqlError> {
+    Ok(self.total_votes)
+  }
+
+  /// `null` once the last vote has been removed
+  fn field_average_rating(&self, _: &Executor<'_, Context>) -> Result<Option<f64>, GqlError> {
+    Ok(self.average_rating.map(f64::from))
+  }
+}
+
+/// Not currently implemented
+pub struct Mutation {}
+
+impl Mutation {
+  /// Records the decision of an admin on a card, returning the moderated card
+  fn moderate_card(
+    executor: &Executor<'_, Context>,
+    id: ID,
+    status: ModerationStatus,
+    reason: Option<String>,
+  ) -> Result<Card, GqlError> {
+    let context = executor.context();
+    let user_id = context.require(Scope::Admin)?;
+    let card_id = id.parse()?;
+
+    let moderated = db::moderate_card(
+      &context.db,
+      &ModerateCard {
+        card_id,
+        moderation_status: status.name().to_owned(),
+        moderated_by_user_id: user_id,
+        rejection_reason: reason,
+      },
+    )?;
+
+    let card = match moderated {
+      true => db::get_cards_by_ids(&context.db, &[card_id])?,
+      false => vec![],
+    };
+
+    card
+      .first()
+      .map(Card::from)
+      .ok_or_else(|| GqlError::CardNotFound(vec![id.to_string()]))
+  }
+
+  /// An active card by its ID, which the caller must have submitted (or be an
+  /// admin) to modify. Returns the caller's `bb.user` id along with the card.
+  fn submitted_card(context: &Context, id: &ID) -> Result<(i32, Card), GqlError> {
+    let user_id = context.require(Scope::Submit)?;
+    let card = db::get_cards_by_ids(&context.db, &[id.parse()?])?
+      .first()
+      .map(Card::from)
+      .ok_or_else(|| GqlError::CardNotFound(vec![id.to_string()]))?;
+
+    match card.submitted_by.as_ref().map(|u| u.id) == Some(user_id)
+      || context.has_scope(Scope::Admin)
+    {
+      true => Ok((user_id, card)),
+      false => Err(GqlError::Forbidden),
+    }
+  }
+
+  /// Parses the IDs of cards being added to a set or deck. When any of the
+  /// cards is unknown or inactive, the missing IDs are listed in the
+  /// `GqlError::CardNotFound` error.
+  fn active_card_ids(context: &Context, cards: &[ID]) -> Result<Vec<i32>, GqlError> {
+    if cards.len() > 1000 {
+      return Err(GqlError::LimitOutOfBounds);
+    }
+
+    let card_ids = cards
+      .iter()
+      .map(|id| id.parse::<i32>())
+      .collect::<Result<Vec<_>, _>>()?;
+
+    let found = context
+      .visible_cards_by_ids(&card_ids)?
+      .iter()
+      .map(|c| c.id)
+      .collect::<Vec<_>>();
+
+    let missing = card_ids
+      .iter()
+      .filter(|id| !found.contains(id))
+      .map(|id| id.to_string())
+      .collect::<Vec<_>>();
+
+    match missing.is_empty() {
+      true => Ok(card_ids),
+      false => Err(GqlError::CardNotFound(missing)),
+    }
+  }
+}
+
+impl MutationFields for Mutation {
+  fn field_add_card(
+    &self,
+    executor: &Executor<'_, Context>,
+    _: &QueryTrail<'_, CardOperation, Walked>,
+    card: CreateCard,
+  ) -> Result<CardOperation, GqlError> {
+    let format_text = validate_format_text(&card.format_text, card.color)?;
+
+    let user_id = executor.context().require(Scope::Submit)?;
+    let con = &executor.context().db;
+    let card_create_result = db::add_card(
+      con,
+      &AddCard {
+        user_id,
+        format_text: format_text.clone(),
+        is_black: match card.color {
+          CardColor::Black => true,
+          CardColor::White => false,
+        },
+      },
+    )?;
+
+    if card_create_result.is_duplicate {
+      return Err(GqlError::DuplicateCard(card_create_result.id));
+    }
+
+    Ok(CardOperation {
+      id: card_create_result.id,
+      format_text,
+      color: card.color,
+    })
+  }
+
+  /// Changes the wording of a card, recording it as a new revision. The text is
+  /// validated the same way as `addCard`. Cards edited by their submitter go back
+  /// to the moderation queue. Only available to the submitter (and admins).
+  fn field_edit_card(
+    &self,
+    executor: &Executor<'_, Context>,
+    _: &QueryTrail<'_, Card, Walked>,
+    id: ID,
+    format_text: String,
+  ) -> Result<Card, GqlError> {
+    let context = executor.context();
+    let (user_id, card) = Mutation::submitted_card(context, &id)?;
+    let format_text = validate_format_text(&format_text, card.color)?;
+
+    let duplicate = db::edit_card(
+      &context.db,
+      &EditCard {
+        card_id: card.id,
+        format_text,
+        edited_by_user_id: user_id,
+        resubmit: !context.has_scope(Scope::Admin),
+      },
+    )?;
+
+    if let Some(duplicate_id) = duplicate {
+      return Err(GqlError::DuplicateCard(duplicate_id));
+    }
+
+    db::get_cards_by_ids(&context.db, &[card.id])?
+      .first()
+      .map(Card::from)
+      .ok_or_else(|| GqlError::CardNotFound(vec![id.to_string()]))
+  }
+
+  /// Deactivates a card. Its revisions and ratings are kept.
+  /// Only available to the submitter (and admins).
+  fn field_delete_card(
+    &self,
+    executor: &Executor<'_, Context>,
+    id: ID,
+  ) -> Result<OperationResult, GqlError> {
+    let context = executor.context();
+    let (_, card) = Mutation::submitted_card(context, &id)?;
+
+    match db::deactivate_card(&context.db, card.id)? {
+      true => Ok(OperationResult::Ok),
+      false => Err(GqlError::CardNotFound(vec![id.to_string()])),
+    }
+  }
+
+  fn field_rate_card(
+    &self,
+    executor: &Executor<'_, Context>,
+    _: &QueryTrail<'_, CardRatingResult, Walked>,
+    rating: CardRating,
+  ) -> Result<CardRatingResult, GqlError> {
+    if rating.rating < 0_f64 || rating.rating > 1_f64 {
+      return Err(GqlError::RatingOutOfBounds);
+    }
+
+    let user_id = executor.context().require(Scope::Rate)?;
+    let card_id = rating.id.parse()?;
+    let rating = rating.rating as f32;
+
+    let add_card_rating = AddCardRating {
+      user_id,
+      card_id,
+      rating,
+    };
+
+    let rating_result = db::add_user_rating_to_card(&executor.context().db, &add_card_rating)?
+      .ok_or_else(|| GqlError::CardNotFound(vec![card_id.to_string()]))?;
+
+    Ok(CardRatingResult {
+      id: card_id,
+      rating: rating,
+      total_votes: rating_result.total_votes,
+      average_rating: rating_result.average_rating.unwrap_or(rating),
+      previous_rating: rating_result.previous_rating,
+    })
+  }
+
+  /// Removes the user's vote on a card. Removing a vote that was never cast
+  /// leaves the card as-is.
+  fn field_unrate_card(
+    &self,
+    executor: &Executor<'_, Context>,
+    _: &QueryTrail<'_, CardUnrateResult, Walked>,
+    id: ID,
+  ) -> Result<CardUnrateResult, GqlError> {
+    let user_id = executor.context().require(Scope::Rate)?;
+    let card_id = id.parse()?;
+
+    let rating_result = db::remove_user_rating_from_card(
+      &executor.context().db,
+      &RemoveCardRating { user_id, card_id },
+    )?
+    .ok_or_else(|| GqlError::CardNotFound(vec![id.to_string()]))?;
+
+    Ok(CardUnrateResult {
+      id: card_id,
+      previous_rating: rating_result.previous_rating,
+      total_votes: rating_result.total_votes,
+      average_rating: rating_result.average_rating,
+    })
+  }
+
+  fn field_rate_card_combo(
+    &self,
+    executor: &Executor<'_, Context>,
+    card_rating: CardComboRating,
+  ) -> Result<OperationResult, GqlError> {
+    if card_rating.rating < 0_f64 || card_rating.rating > 1_f64 {
+      return Err(GqlError::RatingOutOfBounds);
+    }
+
+    if card_rating.ordinal < 0_i32 {
+      return Err(GqlError::NegativeOrdinal);
+    }
+
+    let user_id = executor.context().require(Scope::Rate)?;
+    let con = &executor.context().db;
+    let black_card_id = card_rating.black_card.parse::<i32>()?;
+    let white_card_id = card_rating.white_card.parse::<i32>()?;
+
+    let cards = executor
+      .context()
+      .visible_cards_by_ids(&[black_card_id, white_card_id])?
+      .into_iter()
+      .map(|c| (c.id, c))
+      .collect::<HashMap<_, _>>();
+
+    let missing = [black_card_id, white_card_id]
+      .iter()
+      .filter(|id| !cards.contains_key(id))
+      .map(|id| id.to_string())
+      .collect::<Vec<_>>();
+
+    if !missing.is_empty() {
+      return Err(GqlError::CardNotFound(missing));
+    }
+
+    let black_card = &cards[&black_card_id];
+    if !black_card.is_black {
+      return Err(GqlError::NotBlackCard);
+    }
+    if cards[&white_card_id].is_black {
+      return Err(GqlError::NotWhiteCard);
+    }
+
+    // The ordinal is the blank the white card was played on
+    if card_rating.ordinal >= black_card.pick {
+      return Err(GqlError::OrdinalOutOfBounds);
+    }
+
+    let add_card_rating_combination = AddCardRatingCombination {
+      user_id,
+      white_card_id,
+      black_card_id,
+      rating: card_rating.rating as f32,
+      ordinal: card_rating.ordinal,
+    };
+    db::add_user_rate_card_combination(con, &add_card_rating_combination)?;
+    Ok(OperationResult::Ok)
+  }
+
+  /// Creates an account for an anonymous caller, returning a token for the new
+  /// user. Callers that are already authenticated cannot register again.
+  fn field_register_user(
+    &self,
+    executor: &Executor<'_, Context>,
+    _: &QueryTrail<'_, UserRegistration, Walked>,
+    username: String,
+  ) -> Result<UserRegistration, GqlError> {
+    if executor.context().caller.is_some() {
+      return Err(GqlError::AlreadyRegistered);
+    }
+
+    let username = validate_username(&username)?;
+    let con = &executor.context().db;
+    let user_id = db::register_user(
+      con,
+      &RegisterUser {
+        username: username.clone(),
+      },
+    )?
+    .ok_or(GqlError::UsernameTaken)?;
+
+    let token = auth::generate_token();
+    db::create_user_token(
+      con,
+      &CreateUserToken {
+        token: token.clone(),
+        user_id,
+        lifetime_days: TOKEN_LIFETIME_DAYS,
+      },
+    )?;
+
+    Ok(UserRegistration {
+      user: User {
+        id: user_id,
+        username,
+      },
+      token,
+    })
+  }
+
+  fn field_update_username(
+    &self,
+    executor: &Executor<'_, Context>,
+    _: &QueryTrail<'_, User, Walked>,
+    username: String,
+  ) -> Result<User, GqlError> {
+    let user_id = executor.context().session_user_id()?;
+    let username = validate_username(&username)?;
+
+    let updated = db::update_username(
+      &executor.context().db,
+      &UpdateUsername {
+        user_id,
+        username: username.clone(),
+      },
+    )?;
+
+    if !updated {
+      return Err(GqlError::UsernameTaken);
+    }
+
+    Ok(User {
+      id: user_id,
+      username,
+    })
+  }
+
+  /// Creates an API key for the user, for machine clients which cannot use the
+  /// interactive login. Keys can only be granted scopes the user holds, and cannot
+  /// be created with another API key.
+  fn field_create_api_key(
+    &self,
+    executor: &Executor<'_, Context>,
+    _: &QueryTrail<'_, ApiKeyCreation, Walked>,
+    name: String,
+    scopes: Vec<ApiScope>,
+  ) -> Result<ApiKeyCreation, GqlError> {
+    let context = executor.context();
+    let user_id = context.session_user_id()?;
+    let name = name.trim().to_owned();
+
+    if name.is_empty() {
+      return Err(GqlError::EmptyApiKeyName);
+    }
+
+    let mut granted: Vec<ApiScope> = vec![];
+    for scope in scopes {
+      if !context.has_scope(scope.into()) {
+        return Err(GqlError::Forbidden);
+      }
+      if !granted.contains(&scope) {
+        granted.push(scope);
+      }
+    }
+
+    let key = auth::generate_token();
+    let id = db::create_api_key(
+      &context.db,
+      &CreateApiKey {
+        key: key.clone(),
+        user_id,
+        name: name.clone(),
+        scopes: granted
+          .iter()
+          .map(|s| Scope::from(*s).name().to_owned())
+          .collect(),
+      },
+    )?;
+
+    Ok(ApiKeyCreation {
+      api_key: ApiKey {
+        id,
+        name,
+        scopes: granted,
+      },
+      key,
+    })
+  }
+
+  /// Revokes one of the user's API keys. Admins may revoke any key.
+  fn field_revoke_api_key(
+    &self,
+    executor: &Executor<'_, Context>,
+    id: ID,
+  ) -> Result<OperationResult, GqlError> {
+    let context = executor.context();
+    let user_id = context.session_user_id()?;
+
+    let revoked = db::revoke_api_key(
+      &context.db,
+      &RevokeApiKey {
+        id: id.parse()?,
+        user_id: match context.has_scope(Scope::Admin) {
+          true => None,
+          false => Some(user_id),
+        },
+      },
+    )?;
+
+    match revoked {
+      true => Ok(OperationResult::Ok),
+      false => Err(GqlError::ApiKeyNotFound),
+    }
+  }
+
+  /// Reports an offensive or broken card. Once enough distinct users have
+  /// reported a card, it is deactivated. Reporting a card again replaces the
+  /// reason of the previous report.
+  fn field_report_card(
+    &self,
+    executor: &Executor<'_, Context>,
+    id: ID,
+    reason: String,
+  ) -> Result<OperationResult, GqlError> {
+    let reason = reason.trim();
+    if reason.is_empty() || reason.chars().count() > 500 {
+      return Err(GqlError::InvalidReportReason);
+    }
+
+    let context = executor.context();
+    let user_id = context.require(Scope::Rate)?;
+
+    let reported = db::report_card(
+      &context.db,
+      &ReportCard {
+        card_id: id.parse()?,
+        user_id,
+        reason: reason.to_owned(),
+        report_threshold: context.config.report_threshold,
+      },
+    )?;
+
+    match reported {
+      true => Ok(OperationResult::Ok),
+      false => Err(GqlError::CardNotFound(vec![id.to_string()])),
+    }
+  }
+
+  /// Creates an empty set. Only available to admins.
+  fn field_create_set(
+    &self,
+    executor: &Executor<'_, Context>,
+    _: &QueryTrail<'_, Set, Walked>,
+    name: String,
+  ) -> Result<Set, GqlError> {
+    let name = validate_set_name(&name)?;
+    let context = executor.context();
+    context.require(Scope::Admin)?;
+
+    let id = db::create_set(&context.db, &CreateSet { name: name.clone() })?
+      .ok_or(GqlError::SetNameTaken)?;
+
+    Ok(Set { id, name })
+  }
+
+  /// Renames an active set. Only available to admins.
+  fn field_rename_set(
+    &self,
+    executor: &Executor<'_, Context>,
+    _: &QueryTrail<'_, Set, Walked>,
+    id: ID,
+    name: String,
+  ) -> Result<Set, GqlError> {
+    let name = validate_set_name(&name)?;
+    let context = executor.context();
+    context.require(Scope::Admin)?;
+
+    let set = db::get_active_set(&context.db, id.parse()?)?.ok_or(GqlError::SetNotFound)?;
+    let renamed = db::rename_set(
+      &context.db,
+      &RenameSet {
+        set_id: set.id,
+        name: name.clone(),
+      },
+    )?;
+
+    match renamed {
+      true => Ok(Set { id: set.id, name }),
+      false => Err(GqlError::SetNameTaken),
+    }
+  }
+
+  /// Deactivates a set, after which its cards are no longer returned as part
+  /// of it. Nothing is deleted. Only available to admins.
+  fn field_deactivate_set(
+    &self,
+    executor: &Executor<'_, Context>,
+    id: ID,
+  ) -> Result<OperationResult, GqlError> {
+    let context = executor.context();
+    context.require(Scope::Admin)?;
+
+    match db::deactivate_set(&context.db, id.parse()?)? {
+      true => Ok(OperationResult::Ok),
+      false => Err(GqlError::SetNotFound),
+    }
+  }
+
+  /// Adds active cards to an active set. Cards already in the set are left as-is.
+  /// When any of the cards is unknown or inactive, the missing IDs are listed in
+  /// the `GqlError::CardNotFound` error and no cards are added. Only available to admins.
+  fn field_add_cards_to_set(
+    &self,
+    executor: &Executor<'_, Context>,
+    _: &QueryTrail<'_, Set, Walked>,
+    id: ID,
+    cards: Vec<ID>,
+  ) -> Result<Set, GqlError> {
+    let context = executor.context();
+    context.require(Scope::Admin)?;
+
+    let set = db::get_active_set(&context.db, id.parse()?)?.ok_or(GqlError::SetNotFound)?;
+    let card_ids = Mutation::active_card_ids(context, &cards)?;
+
+    db::add_cards_to_set(
+      &context.db,
+      &UpdateSetCards {
+        set_id: set.id,
+        card_ids,
+      },
+    )?;
+
+    Ok(Set {
+      id: set.id,
+      name: set.name,
+    })
+  }
+
+  /// Removes cards from an active set. Cards that are not in the set are
+  /// ignored. Only available to admins.
+  fn field_remove_cards_from_set(
+    &self,
+    executor: &Executor<'_, Context>,
+    _: &QueryTrail<'_, Set, Walked>,
+    id: ID,
+    cards: Vec<ID>,
+  ) -> Result<Set, GqlError> {
+    let context = executor.context();
+    context.require(Scope::Admin)?;
+
+    if cards.len() > 1000 {
+      return Err(GqlError::LimitOutOfBounds);
+    }
+
+    let set = db::get_active_set(&context.db, id.parse()?)?.ok_or(GqlError::SetNotFound)?;
+    let card_ids = cards
+      .iter()
+      .map(|id| id.parse::<i32>())
+      .collect::<Result<Vec<_>, _>>()?;
+
+    db::remove_cards_from_set(
+      &context.db,
+      &UpdateSetCards {
+        set_id: set.id,
+        card_ids,
+      },
+    )?;
+
+    Ok(Set {
+      id: set.id,
+      name: set.name,
+    })
+  }
+
+  /// Generates the time-deck of the period containing a day (`YYYY-MM-DD`),
+  /// once the period has ended, to backfill missed periods. Cards are ranked by
+  /// their current ratings. An existing time-deck is only regenerated with
+  /// `replace: true`. Only available to admins.
+  fn field_generate_time_deck(
+    &self,
+    executor: &Executor<'_, Context>,
+    _: &QueryTrail<'_, TimeDeck, Walked>,
+    period: TimeDeckPeriod,
+    date: String,
+    top_n: i32,
+    replace: bool,
+  ) -> Result<TimeDeck, GqlError> {
+    let context = executor.context();
+    context.require(Scope::Admin)?;
+
+    if !is_valid_date(&date) {
+      return Err(GqlError::InvalidDate);
+    }
+
+    if top_n > 1000 || top_n < 0 {
+      return Err(GqlError::LimitOutOfBounds);
+    }
+
+    let period_ended = db::time_deck_period_ended(
+      &context.db,
+      &TimeDeckPeriodEnded {
+        period: period.name().to_string(),
+        date: date.clone(),
+        offset_hours: context.config.time_deck_hour,
+      },
+    )?;
+    if !period_ended {
+      return Err(GqlError::TimeDeckPeriodNotEnded);
+    }
+
+    let generated = db::generate_time_deck(
+      &context.db,
+      &GenerateTimeDeck {
+        period: period.name().to_string(),
+        date: date.clone(),
+        offset_hours: context.config.time_deck_hour,
+        top_n_cards: top_n,
+        replace,
+      },
+    )?;
+    let time_deck = db::get_time_deck(
+      &context.db,
+      &GetTimeDeck {
+        period: period.name().to_string(),
+        date,
+      },
+    )?;
+
+    match (generated, time_deck) {
+      (Some(_), Some(t)) => TimeDeck::from_result(t),
+      (None, Some(_)) => Err(GqlError::TimeDeckExists),
+      (_, None) => Err(GqlError::SetNameTaken),
+    }
+  }
+
+  /// Creates an empty deck owned by the authenticated user. Deck names do not
+  /// need to be unique.
+  fn field_create_deck(
+    &self,
+    executor: &Executor<'_, Context>,
+    _: &QueryTrail<'_, Deck, Walked>,
+    name: String,
+    visibility: DeckVisibility,
+  ) -> Result<Deck, GqlError> {
+    let name = validate_set_name(&name)?;
+    let context = executor.context();
+    let user_id = context.require(Scope::Submit)?;
+
+    let id = db::create_deck(
+      &context.db,
+      &CreateDeck {
+        user_id,
+        name,
+        visibility: visibility.name().to_owned(),
+      },
+    )?;
+
+    Deck::find(context, &ID::from(id.to_string()))
+  }
+
+  /// Renames a deck and/or changes its visibility. Arguments that are not
+  /// passed are left unchanged. Only available to the owner (and admins).
+  fn field_update_deck(
+    &self,
+    executor: &Executor<'_, Context>,
+    _: &QueryTrail<'_, Deck, Walked>,
+    id: ID,
+    name: Option<String>,
+    visibility: Option<DeckVisibility>,
+  ) -> Result<Deck, GqlError> {
+    let name = name.map(|n| validate_set_name(&n)).transpose()?;
+    let context = executor.context();
+    let deck = Deck::find_owned(context, &id)?;
+
+    db::update_deck(
+      &context.db,
+      &UpdateDeck {
+        deck_id: deck.id,
+        name,
+        visibility: visibility.map(|v| v.name().to_owned()),
+      },
+    )?;
+
+    Deck::find(context, &id)
+  }
+
+  /// Deactivates a deck, after which it can no longer be used.
+  /// Only available to the owner (and admins).
+  fn field_delete_deck(
+    &self,
+    executor: &Executor<'_, Context>,
+    id: ID,
+  ) -> Result<OperationResult, GqlError> {
+    let context = executor.context();
+    let deck = Deck::find_owned(context, &id)?;
+
+    match db::deactivate_set(&context.db, deck.id)? {
+      true => Ok(OperationResult::Ok),
+      false => Err(GqlError::DeckNotFound),
+    }
+  }
+
+  /// Adds active cards to a deck, the same way as `addCardsToSet`. Cards from any
+  /// set, as well as user-submitted cards, can be added. Only available to the
+  /// owner (and admins).
+  fn field_add_cards_to_deck(
+    &self,
+    executor: &Executor<'_, Context>,
+    _: &QueryTrail<'_, Deck, Walked>,
+    id: ID,
+    cards: Vec<ID>,
+  ) -> Result<Deck, GqlError> {
+    let context = executor.context();
+    let deck = Deck::find_owned(context, &id)?;
+    let card_ids = Mutation::active_card_ids(context, &cards)?;
+
+    db::add_cards_to_set(
+      &context.db,
+      &UpdateSetCards {
+        set_id: deck.id,
+        card_ids,
+      },
+    )?;
+
+    Ok(deck)
+  }
+
+  /// Removes cards from a deck. Cards that are not in the deck are ignored.
+  /// Only available to the owner (and admins).
+  fn field_remove_cards_from_deck(
+    &self,
+    executor: &Executor<'_, Context>,
+    _: &QueryTrail<'_, Deck, Walked>,
+    id: ID,
+    cards: Vec<ID>,
+  ) -> Result<Deck, GqlError> {
+    let context = executor.context();
+    let deck = Deck::find_owned(context, &id)?;
+
+    if cards.len() > 1000 {
+      return Err(GqlError::LimitOutOfBounds);
+    }
+
+    let card_ids = cards
+      .iter()
+      .map(|id| id.parse::<i32>())
+      .collect::<Result<Vec<_>, _>>()?;
+
+    db::remove_cards_from_set(
+      &context.db,
+      &UpdateSetCards {
+        set_id: deck.id,
+        card_ids,
+      },
+    )?;
+
+    Ok(deck)
+  }
+
+  /// Creates a game drawing cards from sets (and decks), which the caller joins
+  /// as its owner. The cards are shuffled with `seed`, a `randomSeed` from
+  /// `cards`, or a random seed when it is not passed.
+  fn field_create_game(
+    &self,
+    executor: &Executor<'_, Context>,
+    _: &QueryTrail<'_, Game, Walked>,
+    sets: Vec<ID>,
+    seed: Option<ID>,
+    hand_size: i32,
+  ) -> Result<Game, GqlError> {
+    let context = executor.context();
+    let user_id = context.require(Scope::Play)?;
+
+    if sets.len() > 1000 {
+      return Err(GqlError::LimitOutOfBounds);
+    }
+    if sets.is_empty() {
+      return Err(GqlError::EmptyDeck);
+    }
+    if hand_size > 50 || hand_size < 1 {
+      return Err(GqlError::InvalidHandSize);
+    }
+
+    let card_sets = sets
+      .iter()
+      .map(|id| id.parse::<i32>())
+      .collect::<Result<Vec<_>, _>>()?;
+    Deck::visible(context, &card_sets)?;
+
+    let random_seed = match seed {
+      Some(s) => f32::from_encoded_id(s)?,
+      None => random::<f32>(),
+    };
+    if !(-1.0..=1.0).contains(&random_seed) {
+      return Err(GqlError::InvalidRandomSeed);
+    }
+
+    let id = db::create_game(
+      &context.db,
+      &CreateGame {
+        user_id,
+        card_sets,
+        random_seed,
+        hand_size,
+      },
+    )?;
+
+    Game::find(context, id)
+  }
+
+  /// Joins a game, until it has finished. Players joining after the hands were
+  /// dealt are dealt a hand at the start of the next round.
+  fn field_join_game(
+    &self,
+    executor: &Executor<'_, Context>,
+    _: &QueryTrail<'_, Game, Walked>,
+    id: ID,
+  ) -> Result<Game, GqlError> {
+    let context = executor.context();
+    let game_move = GameMove {
+      game_id: id.parse()?,
+      user_id: context.require(Scope::Play)?,
+    };
+
+    let refusal = db::join_game(&context.db, &game_move)?;
+    Game::after_move(context, game_move.game_id, refusal)
+  }
+
+  /// Deals the hands and starts the first round, once at least 3 players have
+  /// joined. Only available to the owner.
+  fn field_deal_hands(
+    &self,
+    executor: &Executor<'_, Context>,
+    _: &QueryTrail<'_, Game, Walked>,
+    id: ID,
+  ) -> Result<Game, GqlError> {
+    let context = executor.context();
+    let game_move = GameMove {
+      game_id: id.parse()?,
+      user_id: context.require(Scope::Play)?,
+    };
+
+    let refusal = db::deal_game_hands(&context.db, &game_move)?;
+    Game::after_move(context, game_move.game_id, refusal)
+  }
+
+  /// Plays white cards from the hand, as many as the black card picks, in the
+  /// order of its blanks. The Card Czar does not play.
+  fn field_play_cards(
+    &self,
+    executor: &Executor<'_, Context>,
+    _: &QueryTrail<'_, Game, Walked>,
+    id: ID,
+    cards: Vec<ID>,
+  ) -> Result<Game, GqlError> {
+    let context = executor.context();
+    let user_id = context.require(Scope::Play)?;
+
+    if cards.len() > 1000 {
+      return Err(GqlError::LimitOutOfBounds);
+    }
+
+    let card_ids = cards
+      .iter()
+      .map(|id| id.parse::<i32>())
+      .collect::<Result<Vec<_>, _>>()?;
+
+    let play = PlayGameCards {
+      game_id: id.parse()?,
+      user_id,
+      card_ids,
+    };
+
+    let refusal = db::play_game_cards(&context.db, &play)?;
+    Game::after_move(context, play.game_id, refusal)
+  }
+
+  /// Picks the winning play by its position in `Game.plays`, scoring a point
+  /// for its player. Only available to the Card Czar.
+  fn field_pick_winner(
+    &self,
+    executor: &Executor<'_, Context>,
+    _: &QueryTrail<'_, Game, Walked>,
+    id: ID,
+    play: i32,
+  ) -> Result<Game, GqlError> {
+    let context = executor.context();
+    let pick = PickGameWinner {
+      game_id: id.parse()?,
+      user_id: context.require(Scope::Play)?,
+      play,
+    };
+
+    let refusal = db::pick_game_winner(&context.db, &pick)?;
+    Game::after_move(context, pick.game_id, refusal)
+  }
+
+  /// Starts the next round once the winner has been picked, or finishes the
+  /// game when the black cards have run out. Only available to the Card Czar
+  /// and the owner.
+  fn field_next_round(
+    &self,
+    executor: &Executor<'_, Context>,
+    _: &QueryTrail<'_, Game, Walked>,
+    id: ID,
+  ) -> Result<Game, GqlError> {
+    let context = executor.context();
+    let game_move = GameMove {
+      game_id: id.parse()?,
+      user_id: context.require(Scope::Play)?,
+    };
+
+    let refusal = db::next_game_round(&context.db, &game_move)?;
+    Game::after_move(context, game_move.game_id, refusal)
+  }
+
+  /// Approves a card, after which it is returned by `cards`. Only available to admins.
+  fn field_approve_card(
+    &self,
+    executor: &Executor<'_, Context>,
+    _: &QueryTrail<'_, Card, Walked>,
+    id: ID,
+  ) -> Result<Card, GqlError> {
+    Mutation::moderate_card(executor, id, ModerationStatus::Approved, None)
+  }
+
+  /// Rejects a card, giving the submitter a reason. Only available to admins.
+  fn field_reject_card(
+    &self,
+    executor: &Executor<'_, Context>,
+    _: &QueryTrail<'_, Card, Walked>,
+    id: ID,
+    reason: String,
+  ) -> Result<Card, GqlError> {
+    let reason = reason.trim();
+    if reason.is_empty() {
+      return Err(GqlError::EmptyRejectionReason);
+    }
+
+    Mutation::moderate_card(
+      executor,
+      id,
+      ModerationStatus::Rejected,
+      Some(reason.to_owned()),
+    )
+  }
+}
+
+fn playground() -> HttpResponse {
+  let html = playground_source("");
+  HttpResponse::Ok()
+    .content_type("text/html; charset=utf-8")
+    .body(html)
+}
+
+/// Resolves the caller before executing the request. Requests without
+/// credentials are executed anonymously; mutations requiring a user will fail
+/// with `GqlError::Unauthenticated`, and those requiring a scope the caller
+/// was not granted with `GqlError::Forbidden`.
+async fn graphql(
+  req: HttpRequest,
+  schema: Data<Arc<Schema>>,
+  data: Json<GraphQLRequest>,
+  db_pool: Data<Pool>,
+  config: Data<GqlConfig>,
+) -> Result<HttpResponse, AWError> {
+  let credentials = auth::credentials_from_request(&req);
+  let auth_pool = db_pool.clone();
+  let caller = web::block(move || auth::authenticate(&auth_pool, credentials))
+    .await
+    .map_err(|e| match e {
+      BlockingError::Error(AuthError::InvalidToken) => ErrorUnauthorized(AuthError::InvalidToken),
+      e => AWError::from(e),
+    })?;
+
+  let ctx = Context {
+    db: db_pool,
+    caller,
+    config,
+    rating_breakdowns: RatingBreakdownLoader::default(),
+    moderations: CardModerationLoader::default(),
+  };
+
+  let res = web::block(move || {
+    let res = data.execute(&schema, &ctx);
+    Ok::<_, SError>(to_string(&res)?)
+  })
+  .await?;
+  Ok(
+    HttpResponse::Ok()
+      .content_type("application/json")
+      .body(res),
+  )
+}
+
+pub fn register(config: &mut ServiceConfig, gql_config: GqlConfig) {
+  let schema = Arc::new(Schema::new(Query {}, Mutation {}));
+
+  config
+    .data(schema)
+    .data(gql_config)
+    .route("/", web::post().to(graphql))
+    .route("/", web::get().to(playground));
+}
+
+impl StatsInterval {
+  /// Name of the interval in `bb.get_rating_volume`
+  fn name(self) -> &'static str {
+    match self {
+      StatsInterval::Day => "day",
+      StatsInterval::Week => "week",
+      StatsInterval::Month => "month",
+    }
+  }
+}
+
+/// Usage statistics of the cards and ratings. Each field is only computed when
+/// it is requested.
+pub struct Stats {}
+
+impl StatsFields for Stats {
+  /// Active, approved cards by color and source
+  fn field_cards(
+    &self,
+    executor: &Executor<'_, Context>,
+    _: &QueryTrail<'_, CardCount, Walked>,
+  ) -> Result<Vec<CardCount>, GqlError> {
+    let counts = db::get_card_counts(&executor.context().db)?;
+
+    Ok(
+      counts
+        .iter()
+        .map(|c| CardCount {
+          color: match c.is_black {
+            true => CardColor::Black,
+            false => CardColor::White,
+          },
+          source: match c.user_submitted {
+            true => CardSource::User,
+            false => CardSource::Official,
+          },
+          count: c.n_cards,
+        })
+        .collect(),
+    )
+  }
+
+  /// Active sets, not counting the decks of users
+  fn field_active_sets(&self, executor: &Executor<'_, Context>) -> Result<i32, GqlError> {
+    Ok(db::get_active_set_count(&executor.context().db)?)
+  }
+
+  /// Card ratings of each of the last `buckets` days, weeks, or months, oldest
+  /// first, by the date they were first given
+  fn field_rating_volume(
+    &self,
+    executor: &Executor<'_, Context>,
+    _: &QueryTrail<'_, RatingVolume, Walked>,
+    interval: StatsInterval,
+    buckets: i32,
+  ) -> Result<Vec<RatingVolume>, GqlError> {
+    if buckets > 1000 || buckets < 1 {
+      return Err(GqlError::LimitOutOfBounds);
+    }
+
+    let volume = db::get_rating_volume(
+      &executor.context().db,
+      &GetRatingVolume {
+        interval: interval.name().to_string(),
+        n_buckets: buckets,
+      },
+    )?;
+
+    Ok(
+      volume
+        .into_iter()
+        .map(|v| RatingVolume {
+          start: v.bucket_start,
+          ratings: v.n_ratings,
+          raters: v.n_raters,
+        })
+        .collect(),
+    )
+  }
+
+  /// Users with the most card ratings
+  fn field_top_raters(
+    &self,
+    executor: &Executor<'_, Context>,
+    _: &QueryTrail<'_, TopRater, Walked>,
+    first: i32,
+  ) -> Result<Vec<TopRater>, GqlError> {
+    if first > 1000 || first < 0 {
+      return Err(GqlError::LimitOutOfBounds);
+    }
+
+    let raters = db::get_top_raters(&executor.context().db, first)?;
+
+    Ok(
+      raters
+        .into_iter()
+        .map(|r| TopRater {
+          user: User {
+            id: r.user_id,
+            username: r.username,
+          },
+          ratings: r.n_ratings,
+          last_rated_date: r.last_rated_date,
+        })
+        .collect(),
+    )
+  }
+
+  /// Histogram of the average rating of the cards with votes, in `buckets`
+  /// buckets of equal width between 0 and 1
+  fn field_rating_distribution(
+    &self,
+    executor: &Executor<'_, Context>,
+    _: &QueryTrail<'_, RatingDistribution, Walked>,
+    buckets: i32,
+  ) -> Result<Vec<RatingDistribution>, GqlError> {
+    if buckets > 1000 || buckets < 1 {
+      return Err(GqlError::LimitOutOfBounds);
+    }
+
+    let distribution = db::get_rating_distribution(&executor.context().db, buckets)?;
+
+    Ok(
+      distribution
+        .iter()
+        .map(|d| RatingDistribution {
+          min: f64::from(d.bucket) / f64::from(buckets),
+          max: f64::from(d.bucket + 1) / f64::from(buckets),
+          cards: d.n_cards,
+        })
+        .collect(),
+    )
+  }
+}
+
+pub struct CardCount {
+  color: CardColor,
+  source: CardSource,
+  count: i32,
+}
+
+impl CardCountFields for CardCount {
+  fn field_color(&self, _: &Executor<'_, Context>) -> Result<CardColor, GqlError> {
+    Ok(self.color)
+  }
+
+  fn field_source(&self, _: &Executor<'_, Context>) -> Result<CardSource, GqlError> {
+    Ok(self.source)
+  }
+
+  fn field_count(&self, _: &Executor<'_, Context>) -> Result<i32, GqlError> {
+    Ok(self.count)
+  }
+}
+
+pub struct RatingVolume {
+  start: String,
+  ratings: i32,
+  raters: i32,
+}
+
+impl RatingVolumeFields for RatingVolume {
+  fn field_start(&self, _: &Executor<'_, Context>) -> Result<&String, GqlError> {
+    Ok(&self.start)
+  }
+
+  fn field_ratings(&self, _: &Executor<'_, Context>) -> Result<i32, GqlError> {
+    Ok(self.ratings)
+  }
+
+  fn field_raters(&self, _: &Executor<'_, Context>) -> Result<i32, GqlError> {
+    Ok(self.raters)
+  }
+}
+
+pub struct TopRater {
+  user: User,
+  ratings: i32,
+  last_rated_date: String,
+}
+
+impl TopRaterFields for TopRater {
+  fn field_user(
+    &self,
+    _: &Executor<'_, Context>,
+    _: &QueryTrail<'_, User, Walked>,
+  ) -> Result<&User, GqlError> {
+    Ok(&self.user)
+  }
+
+  fn field_ratings(&self, _: &Executor<'_, Context>) -> Result<i32, GqlError> {
+    Ok(self.ratings)
+  }
+
+  fn field_last_rated_date(&self, _: &Executor<'_, Context>) -> Result<&String, GqlError> {
+    Ok(&self.last_rated_date)
+  }
+}
+
+pub struct RatingDistribution {
+  min: f64,
+  max: f64,
+  cards: i32,
+}
+
+impl RatingDistributionFields for RatingDistribution {
+  fn field_min(&self, _: &Executor<'_, Context>) -> Result<f64, GqlError> {
+    Ok(self.min)
+  }
 
-  /// This returns all of the card sets within the database,
-  /// or the matched sets when using the `search` parameter.
-  /// `search` allows for a full-text-search of the set name
-  fn field_sets(
-    &self,
-    executor: &Executor<'_, Context>,
-    _: &QueryTrail<'_, SetResult, Walked>,
-    search: Option<String>,
-    pagination: Pagination,
-  ) -> Result<SetResult, GqlError> {
-    let limit = pagination.page_size;
+  fn field_max(&self, _: &Executor<'_, Context>) -> Result<f64, GqlError> {
+    Ok(self.max)
+  }
 
-    if limit > 1000 || limit < 0 {
-      return Err(GqlError::LimitOutOfBounds);
+  fn field_cards(&self, _: &Executor<'_, Context>) -> Result<i32, GqlError> {
+    Ok(self.cards)
+  }
+}
+
+impl GameStatus {
+  fn from_name(name: &str) -> Option<GameStatus> {
+    match name {
+      "lobby" => Some(GameStatus::Lobby),
+      "playing" => Some(GameStatus::Playing),
+      "judging" => Some(GameStatus::Judging),
+      "round_over" => Some(GameStatus::RoundOver),
+      "finished" => Some(GameStatus::Finished),
+      _ => None,
     }
+  }
+}
 
-    let mut get_sets = GetSets::default();
-    get_sets.n_results = Some(limit);
-    get_sets.search = search;
+/// A game played on the server. The state of the game is stored in the
+/// database, and every move is made by one of the `bb.*_game*` functions.
+pub struct Game {
+  id: i32,
+  owner: User,
+  card_sets: Vec<i32>,
+  random_seed: f32,
+  hand_size: i32,
+  status: GameStatus,
+  round: i32,
+  czar_player_id: Option<i32>,
+  black_card_id: Option<i32>,
+  winner_player_id: Option<i32>,
+}
 
-    match pagination.cursor.map(|v| i32::from_encoded_id(v)) {
-      Some(Ok(v)) => get_sets.cursor = Some(v),
-      Some(Err(e)) => {
-        return Err(e.into());
-      }
-      _ => get_sets.cursor = None,
-    }
+impl Game {
+  fn from_result(g: GetGameResults) -> Result<Game, GqlError> {
+    Ok(Game {
+      id: g.id,
+      owner: User {
+        id: g.owner_user_id,
+        username: g.owner_username,
+      },
+      card_sets: g.card_sets,
+      random_seed: g.random_seed,
+      hand_size: g.hand_size,
+      status: GameStatus::from_name(&g.status).ok_or(GqlError::UnexpectedError)?,
+      round: g.round,
+      czar_player_id: g.czar_player_id,
+      black_card_id: g.black_card_id,
+      winner_player_id: g.winner_player_id,
+    })
+  }
 
-    let con = &executor.context().db;
-    let db_sets = db::get_sets(con, &get_sets)?;
+  fn find(context: &Context, id: i32) -> Result<Game, GqlError> {
+    Game::from_result(db::get_game(&context.db, id)?.ok_or(GqlError::GameNotFound)?)
+  }
 
-    let has_more = db_sets.iter().len() as i32 > limit;
-    let last_cursor = db_sets.iter().nth(limit as usize - 1).map(|r| r.id);
+  /// The game after a move, or the error for the reason the move was refused
+  fn after_move(context: &Context, id: i32, refusal: Option<String>) -> Result<Game, GqlError> {
+    match refusal.as_deref() {
+      None => Game::find(context, id),
+      Some("not_found") => Err(GqlError::GameNotFound),
+      Some("not_player") => Err(GqlError::NotInGame),
+      Some("forbidden") => Err(GqlError::Forbidden),
+      Some("status") => Err(GqlError::InvalidGameStatus),
+      Some("not_enough_players") => Err(GqlError::NotEnoughPlayers),
+      Some("empty_deck") => Err(GqlError::EmptyDeck),
+      Some("czar") => Err(GqlError::CzarCannotPlay),
+      Some("already_played") => Err(GqlError::AlreadyPlayed),
+      Some("pick") => Err(GqlError::PickMismatch),
+      Some("not_in_hand") => Err(GqlError::CardNotInHand),
+      Some("not_czar") => Err(GqlError::NotCzar),
+      Some("play_not_found") => Err(GqlError::PlayNotFound),
+      Some(_) => Err(GqlError::UnexpectedError),
+    }
+  }
 
-    let db_sets = db_sets
+  /// Cards by their IDs, in the same order. Cards deactivated (or no longer
+  /// approved) since they were drawn are left out.
+  fn cards(context: &Context, card_ids: &[i32]) -> Result<Vec<Card>, GqlError> {
+    let cards = context
+      .visible_cards_by_ids(card_ids)?
       .iter()
-      .take(limit as usize)
-      .map(|s| SetInfo {
-        id: s.id,
-        name: s.name.to_owned(),
-      })
-      .collect::<Vec<_>>();
+      .map(|c| (c.id, Card::from(c)))
+      .collect::<HashMap<_, _>>();
 
-    Ok(SetResult {
-      results: db_sets,
-      has_next_page: has_more,
-      last_cursor: last_cursor,
-    })
+    Ok(
+      card_ids
+        .iter()
+        .filter_map(|id| cards.get(id).cloned())
+        .collect(),
+    )
   }
 
-  fn field_license(&self, _: &Executor<'_, Context>) -> Result<Url, GqlError> {
-    Ok(Url::parse(
-      "https://creativecommons.org/licenses/by-nc-sa/2.0/legalcode",
-    )?)
+  fn players(&self, context: &Context) -> Result<Vec<Player>, GqlError> {
+    Ok(
+      db::get_game_players(&context.db, self.id)?
+        .into_iter()
+        .map(|p| Player {
+          id: p.id,
+          user: User {
+            id: p.user_id,
+            username: p.username,
+          },
+          score: p.score,
+          card_count: p.n_cards,
+          has_played: p.has_played,
+        })
+        .collect(),
+    )
   }
 
-  fn field_api_version(&self, _: &Executor<'_, Context>) -> Result<String, GqlError> {
-    Ok(String::from("0.1.0"))
+  fn player(&self, context: &Context, id: Option<i32>) -> Result<Option<Player>, GqlError> {
+    match id {
+      Some(id) => Ok(self.players(context)?.into_iter().find(|p| p.id == id)),
+      None => Ok(None),
+    }
   }
+}
 
-  fn field_authors(&self, _: &Executor<'_, Context>) -> Result<Vec<String>, GqlError> {
-    Ok(vec![
-      String::from("Nicholas Dolan"),
-      String::from("Cameron Otts"),
-      String::from("Grace Chenevert"),
-      String::from("Aaron Dentro"),
-      String::from("Patrick Dolan"),
-    ])
+impl GameFields for Game {
+  fn field_id(&self, _: &Executor<'_, Context>) -> Result<ID, GqlError> {
+    Ok(ID::from(self.id.to_string()))
   }
 
-  fn field_cards_against_humanity(
+  fn field_owner(
     &self,
     _: &Executor<'_, Context>,
-    _: &QueryTrail<'_, CardsAgainstHumanity, Walked>,
-  ) -> Result<CardsAgainstHumanity, GqlError> {
-    Ok(CardsAgainstHumanity {})
+    _: &QueryTrail<'_, User, Walked>,
+  ) -> Result<&User, GqlError> {
+    Ok(&self.owner)
   }
-}
 
-pub struct CardRatingResult {
-  id: i32,
-  rating: f32,
-  total_votes: i32,
-  average_rating: f32,
-}
+  /// IDs of the sets (and decks) the cards of the game are drawn from
+  fn field_sets(&self, _: &Executor<'_, Context>) -> Result<Vec<ID>, GqlError> {
+    Ok(
+      self
+        .card_sets
+        .iter()
+        .map(|id| ID::from(id.to_string()))
+        .collect(),
+    )
+  }
 
-impl CardRatingResultFields for CardRatingResult {
-  fn field_id(&self, _: &Executor<'_, Context>) -> Result<ID, GqlError> {
-    Ok(ID::from(self.id.to_string()))
+  /// The cards are drawn in the order of `cards(randomized: true)` for this seed
+  fn field_random_seed(&self, _: &Executor<'_, Context>) -> Result<ID, GqlError> {
+    Ok(self.random_seed.to_encoded_id())
   }
 
-  fn field_rating(&self, _: &Executor<'_, Context>) -> Result<f64, GqlError> {
-    Ok(self.rating.into())
+  fn field_hand_size(&self, _: &Executor<'_, Context>) -> Result<i32, GqlError> {
+    Ok(self.hand_size)
   }
 
-  fn field_total_votes(&self, _: &Executor<'_, Context>) -> Result<i32, GqlError> {
-    Ok(self.total_votes)
+  fn field_status(&self, _: &Executor<'_, Context>) -> Result<GameStatus, GqlError> {
+    Ok(self.status)
   }
 
-  fn field_average_rating(&self, _: &Executor<'_, Context>) -> Result<f64, GqlError> {
-    Ok(self.average_rating.into())
+  fn field_round(&self, _: &Executor<'_, Context>) -> Result<i32, GqlError> {
+    Ok(self.round)
   }
-}
 
-/// Not currently implemented
-pub struct Mutation {}
+  /// Players in the order of their seats, which the Card Czar passes along
+  fn field_players(
+    &self,
+    executor: &Executor<'_, Context>,
+    _: &QueryTrail<'_, Player, Walked>,
+  ) -> Result<Vec<Player>, GqlError> {
+    self.players(executor.context())
+  }
 
-impl MutationFields for Mutation {
-  fn field_add_card(
+  fn field_czar(
     &self,
     executor: &Executor<'_, Context>,
-    _: &QueryTrail<'_, CardOperation, Walked>,
-    card: CreateCard,
-  ) -> Result<CardOperation, GqlError> {
-    if card.format_text == "" {
-      return Err(GqlError::EmptyFormatText);
+    _: &QueryTrail<'_, Player, Walked>,
+  ) -> Result<Option<Player>, GqlError> {
+    self.player(executor.context(), self.czar_player_id)
+  }
+
+  fn field_black_card(
+    &self,
+    executor: &Executor<'_, Context>,
+    _: &QueryTrail<'_, Card, Walked>,
+  ) -> Result<Option<Card>, GqlError> {
+    match self.black_card_id {
+      Some(id) => Ok(Game::cards(executor.context(), &[id])?.pop()),
+      None => Ok(None),
     }
+  }
 
-    let con = &executor.context().db;
-    let card_create_result = db::add_card(
-      con,
-      &AddCard {
-        user_id: executor.context().authenticated_user_id,
-        format_text: card.format_text.clone(),
-        is_black: match card.color {
-          CardColor::Black => true,
-          CardColor::White => false,
-        },
+  /// Cards held by the authenticated player. Empty for anyone else.
+  fn field_hand(
+    &self,
+    executor: &Executor<'_, Context>,
+    _: &QueryTrail<'_, Card, Walked>,
+  ) -> Result<Vec<Card>, GqlError> {
+    let context = executor.context();
+    let user_id = match context.require(Scope::Play) {
+      Ok(id) => id,
+      Err(_) => return Ok(vec![]),
+    };
+
+    let card_ids = db::get_game_hand(
+      &context.db,
+      &GameMove {
+        game_id: self.id,
+        user_id,
       },
     )?;
 
-    Ok(CardOperation {
-      id: card_create_result.id,
-      format_text: card.format_text,
-      color: card.color,
-    })
+    Game::cards(context, &card_ids)
   }
 
-  fn field_rate_card(
+  /// Plays of the current round, in shuffled order, once every player has
+  /// played. Players are only revealed once the winner has been picked.
+  fn field_plays(
     &self,
     executor: &Executor<'_, Context>,
-    _: &QueryTrail<'_, CardRatingResult, Walked>,
-    rating: CardRating,
-  ) -> Result<CardRatingResult, GqlError> {
-    if rating.rating < 0_f64 || rating.rating > 1_f64 {
-      return Err(GqlError::RatingOutOfBounds);
+    _: &QueryTrail<'_, Play, Walked>,
+  ) -> Result<Vec<Play>, GqlError> {
+    if self.status == GameStatus::Lobby || self.status == GameStatus::Playing {
+      return Ok(vec![]);
     }
 
-    let user_id = executor.context().authenticated_user_id;
-    let card_id = rating.id.parse()?;
-    let rating = rating.rating as f32;
-
-    let add_card_rating = AddCardRating {
-      user_id,
-      card_id,
-      rating,
+    let context = executor.context();
+    let players = match self.winner_player_id {
+      Some(_) => self.players(context)?,
+      None => vec![],
     };
 
-    let rating_result = db::add_user_rating_to_card(&executor.context().db, &add_card_rating)?;
-
-    Ok(CardRatingResult {
-      id: card_id,
-      rating: rating,
-      total_votes: rating_result.total_votes,
-      average_rating: rating_result.average_rating,
-    })
+    db::get_game_plays(&context.db, self.id)?
+      .into_iter()
+      .map(|p| {
+        Ok(Play {
+          cards: Game::cards(context, &p.card_ids)?,
+          player: players.iter().find(|r| r.id == p.player_id).cloned(),
+        })
+      })
+      .collect()
   }
 
-  fn field_rate_card_combo(
+  fn field_winner(
     &self,
     executor: &Executor<'_, Context>,
-    card_rating: CardComboRating,
-  ) -> Result<OperationResult, GqlError> {
-    if card_rating.rating < 0_f64 || card_rating.rating > 1_f64 {
-      return Err(GqlError::RatingOutOfBounds);
-    }
-
-    if card_rating.ordinal < 0_i32 {
-      return Err(GqlError::NegativeOrdinal);
-    }
-
-    let add_card_rating_combination = AddCardRatingCombination {
-      user_id: executor.context().authenticated_user_id,
-      white_card_id: card_rating.white_card.parse().expect("Must be a valid ID"),
-      black_card_id: card_rating.black_card.parse().expect("Must be a valid ID"),
-      rating: card_rating.rating as f32,
-      ordinal: card_rating.ordinal,
-    };
-    db::add_user_rate_card_combination(&executor.context().db, &add_card_rating_combination)?;
-    Ok(OperationResult::Ok)
+    _: &QueryTrail<'_, Player, Walked>,
+  ) -> Result<Option<Player>, GqlError> {
+    self.player(executor.context(), self.winner_player_id)
   }
 }
 
-fn playground() -> HttpResponse {
-  let html = playground_source("");
-  HttpResponse::Ok()
-    .content_type("text/html; charset=utf-8")
-    .body(html)
+#[derive(Clone)]
+pub struct Player {
+  id: i32,
+  user: User,
+  score: i32,
+  card_count: i32,
+  has_played: bool,
 }
 
-async fn graphql(
-  schema: Data<Arc<Schema>>,
-  data: Json<GraphQLRequest>,
-  db_pool: Data<Pool>,
-) -> Result<HttpResponse, AWError> {
-  let ctx = Context {
-    db: db_pool,
-    authenticated_user_id: 1,
-  };
+impl PlayerFields for Player {
+  fn field_id(&self, _: &Executor<'_, Context>) -> Result<ID, GqlError> {
+    Ok(ID::from(self.id.to_string()))
+  }
 
-  let res = web::block(move || {
-    let res = data.execute(&schema, &ctx);
-    Ok::<_, SError>(to_string(&res)?)
-  })
-  .await?;
-  Ok(
-    HttpResponse::Ok()
-      .content_type("application/json")
-      .body(res),
-  )
+  fn field_user(
+    &self,
+    _: &Executor<'_, Context>,
+    _: &QueryTrail<'_, User, Walked>,
+  ) -> Result<&User, GqlError> {
+    Ok(&self.user)
+  }
+
+  fn field_score(&self, _: &Executor<'_, Context>) -> Result<i32, GqlError> {
+    Ok(self.score)
+  }
+
+  /// Number of cards the player holds
+  fn field_card_count(&self, _: &Executor<'_, Context>) -> Result<i32, GqlError> {
+    Ok(self.card_count)
+  }
+
+  /// Whether the player has played in the current round
+  fn field_has_played(&self, _: &Executor<'_, Context>) -> Result<bool, GqlError> {
+    Ok(self.has_played)
+  }
 }
 
-pub fn register(config: &mut ServiceConfig) {
-  let schema = Arc::new(Schema::new(Query {}, Mutation {}));
+/// White cards played in a round, in the order of the blanks of the black card
+pub struct Play {
+  cards: Vec<Card>,
+  player: Option<Player>,
+}
 
-  config
-    .data(schema)
-    .route("/", web::post().to(graphql))
-    .route("/", web::get().to(playground));
+impl PlayFields for Play {
+  fn field_cards(
+    &self,
+    _: &Executor<'_, Context>,
+    _: &QueryTrail<'_, Card, Walked>,
+  ) -> Result<&Vec<Card>, GqlError> {
+    Ok(&self.cards)
+  }
+
+  fn field_player(
+    &self,
+    _: &Executor<'_, Context>,
+    _: &QueryTrail<'_, Player, Walked>,
+  ) -> Result<Option<Player>, GqlError> {
+    Ok(self.player.clone())
+  }
 }
 
 pub struct CardsAgainstHumanity {}
@@ -679,26 +4171,133 @@ impl CardsAgainstHumanityFields for CardsAgainstHumanity {
 
 #[derive(Debug, Clone)]
 pub enum GqlError {
+  AlreadyPlayed,
+  AlreadyRegistered,
+  ApiKeyNotFound,
+  CardNotFound(Vec<String>),
+  CardNotInHand,
+  CzarCannotPlay,
+  DeckNotFound,
   DecodeError,
+  DuplicateCard(i32),
+  EmptyApiKeyName,
+  EmptyDeck,
   EmptyFormatText,
+  EmptyRejectionReason,
+  Forbidden,
+  FormatTextTooLong,
+  GameNotFound,
+  InvalidDate,
+  InvalidFills,
+  InvalidGameStatus,
+  InvalidHandSize,
   InvalidID,
+  InvalidRandomSeed,
+  InvalidReportReason,
+  InvalidSetName,
+  InvalidUsername,
   LimitOutOfBounds,
+  MalformedPrompt,
   NegativeOrdinal,
+  NotBlackCard,
+  NotCzar,
+  NotEnoughPlayers,
+  NotInGame,
+  NotWhiteCard,
+  OrdinalOutOfBounds,
+  PickMismatch,
+  PlayNotFound,
+  PromptOutOfBounds,
   RatingOutOfBounds,
+  SearchRequired,
+  SetNameTaken,
+  SetNotFound,
+  TimeDeckExists,
+  TimeDeckNotFound,
+  TimeDeckPeriodNotEnded,
+  Unauthenticated,
   UnexpectedError,
   UrlParseError(ParseError),
+  UsernameTaken,
+  WhiteCardPrompt,
 }
 
 impl IntoFieldError for GqlError {
   fn into_field_error(self) -> FieldError {
+    if let GqlError::CardNotFound(ids) = self {
+      return FieldError::new(
+        "Card not found",
+        Value::object(
+          vec![(
+            "ids",
+            Value::list(ids.into_iter().map(Value::scalar).collect()),
+          )]
+          .into_iter()
+          .collect(),
+        ),
+      );
+    }
+
+    if let GqlError::DuplicateCard(id) = self {
+      return FieldError::new(
+        "Card has already been submitted",
+        Value::object(
+          vec![("id", Value::scalar(id.to_string()))]
+            .into_iter()
+            .collect(),
+        ),
+      );
+    }
+
     FieldError::from(match self {
+      GqlError::AlreadyPlayed => "Cards have already been played this round",
+      GqlError::AlreadyRegistered => "Already registered as a user",
+      GqlError::ApiKeyNotFound => "API key not found",
+      GqlError::CardNotInHand => "Cards must be played from your hand",
+      GqlError::CzarCannotPlay => "The Card Czar does not play cards",
+      GqlError::DeckNotFound => "Deck not found",
       GqlError::DecodeError => "Provided ID value was not a valid format",
+      GqlError::EmptyApiKeyName => "API key name cannot be empty",
+      GqlError::EmptyDeck => "Games need sets with both black and white cards",
       GqlError::EmptyFormatText => "Format text cannot be empty",
+      GqlError::EmptyRejectionReason => "Rejection reason cannot be empty",
+      GqlError::Forbidden => "Not permitted to perform this action",
+      GqlError::FormatTextTooLong => "Format text cannot be longer than 256 characters",
+      GqlError::GameNotFound => "Game not found",
+      GqlError::InvalidDate => "Dates must be valid days formatted as YYYY-MM-DD",
+      GqlError::InvalidFills => "Fills must be exactly Pick white cards",
+      GqlError::InvalidGameStatus => "Not allowed at this stage of the game",
+      GqlError::InvalidHandSize => "1 ≤ Hand Size ≤ 50",
       GqlError::InvalidID => "ID Field not a valid ID type",
+      GqlError::InvalidRandomSeed => "-1 ≤ Random Seed ≤ 1",
+      GqlError::InvalidReportReason => "Report reason must be 1 to 500 characters",
+      GqlError::InvalidSetName => "Set name must be 1 to 100 characters",
+      GqlError::InvalidUsername => {
+        "Username must be 3 to 32 letters, digits, '_', '-', or '.' characters"
+      }
       GqlError::LimitOutOfBounds => "0 ≤ Page Size ≤ 1000",
+      GqlError::MalformedPrompt => "Blanks must be written as <prompt/>",
       GqlError::NegativeOrdinal => "Ordinal cannot be negative",
+      GqlError::NotBlackCard => "Card is not a black card",
+      GqlError::NotCzar => "Only the Card Czar can pick the winner",
+      GqlError::NotEnoughPlayers => "At least 3 players are needed to deal",
+      GqlError::NotInGame => "Not a player of this game",
+      GqlError::NotWhiteCard => "Card is not a white card",
+      GqlError::OrdinalOutOfBounds => "0 ≤ Ordinal < Pick of the black card",
+      GqlError::PickMismatch => "Pick of the black card and number of cards played must match",
+      GqlError::PlayNotFound => "Play not found",
+      GqlError::PromptOutOfBounds => "Black cards can have at most 3 blanks",
       GqlError::RatingOutOfBounds => "0 ≤ Rating ≤ 1",
+      GqlError::SearchRequired => "Sorting by search relevance requires a search",
+      GqlError::SetNameTaken => "Set name has already been taken",
+      GqlError::SetNotFound => "Set not found",
+      GqlError::TimeDeckExists => "Time-deck has already been generated",
+      GqlError::TimeDeckNotFound => "Time-deck not found",
+      GqlError::TimeDeckPeriodNotEnded => "Time-deck period has not ended yet",
+      GqlError::Unauthenticated => "Must be logged in to perform this action",
       GqlError::UrlParseError(_) => "Tried to parse an invalid URL",
+      GqlError::UsernameTaken => "Username has already been taken",
+      GqlError::WhiteCardPrompt => "White cards cannot have blanks",
       _ => "Server Error!",
     })
   }
@@ -727,3 +4326,166 @@ impl From<DecodeError> for GqlError {
     GqlError::DecodeError
   }
 }
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn validate_username_trims() {
+    assert_eq!(validate_username("  player.one ").unwrap(), "player.one");
+    assert_eq!(validate_username("a_b-c").unwrap(), "a_b-c");
+  }
+
+  #[test]
+  fn validate_username_length() {
+    assert!(matches!(
+      validate_username("ab"),
+      Err(GqlError::InvalidUsername)
+    ));
+    assert!(matches!(
+      validate_username("  ab  "),
+      Err(GqlError::InvalidUsername)
+    ));
+    assert!(validate_username("abc").is_ok());
+    assert!(validate_username(&"a".repeat(32)).is_ok());
+    assert!(matches!(
+      validate_username(&"a".repeat(33)),
+      Err(GqlError::InvalidUsername)
+    ));
+    // Counted in characters rather than bytes
+    assert!(validate_username(&"é".repeat(32)).is_ok());
+  }
+
+  #[test]
+  fn validate_username_characters() {
+    for username in &[
+      "two words",
+      "a@example.com",
+      "tab\tname",
+      "<b>bold</b>",
+      "a:b",
+    ] {
+      assert!(
+        matches!(validate_username(username), Err(GqlError::InvalidUsername)),
+        "{}",
+        username
+      );
+    }
+  }
+
+  #[test]
+  fn validate_format_text_normalizes() {
+    assert_eq!(
+      validate_format_text("  Why \n <prompt/>?  ", CardColor::Black).unwrap(),
+      "Why <prompt/>?"
+    );
+    assert!(matches!(
+      validate_format_text(" \t\n ", CardColor::White),
+      Err(GqlError::EmptyFormatText)
+    ));
+  }
+
+  #[test]
+  fn validate_format_text_length() {
+    let max = "a".repeat(MAX_FORMAT_TEXT_LENGTH);
+    assert!(validate_format_text(&max, CardColor::White).is_ok());
+    assert!(matches!(
+      validate_format_text(&format!("{}a", max), CardColor::White),
+      Err(GqlError::FormatTextTooLong)
+    ));
+    // Collapsed whitespace does not count towards the length
+    assert!(validate_format_text(&format!("{}   ", max), CardColor::White).is_ok());
+  }
+
+  #[test]
+  fn validate_format_text_prompts() {
+    assert!(validate_format_text("No blanks.", CardColor::Black).is_ok());
+    assert!(validate_format_text("<prompt/> <prompt/> <prompt/>", CardColor::Black).is_ok());
+    assert!(matches!(
+      validate_format_text("<prompt/> <prompt/> <prompt/> <prompt/>", CardColor::Black),
+      Err(GqlError::PromptOutOfBounds)
+    ));
+    assert!(matches!(
+      validate_format_text("A <prompt/>.", CardColor::White),
+      Err(GqlError::WhiteCardPrompt)
+    ));
+    for format_text in &["<prompt>", "</prompt>", "<Prompt />", "Why <prompt"] {
+      assert!(
+        matches!(
+          validate_format_text(format_text, CardColor::Black),
+          Err(GqlError::MalformedPrompt)
+        ),
+        "{}",
+        format_text
+      );
+    }
+  }
+
+  #[test]
+  fn page_splits_rows() {
+    assert_eq!(page(vec![1, 2, 3], 2, |r| *r), (vec![1, 2], true, Some(2)));
+    assert_eq!(page(vec![1, 2], 2, |r| *r), (vec![1, 2], false, Some(2)));
+    assert_eq!(page(Vec::<i32>::new(), 2, |r| *r), (vec![], false, None));
+  }
+
+  #[test]
+  fn page_zero_limit() {
+    assert_eq!(page(vec![1], 0, |r| *r), (vec![], true, None));
+    assert_eq!(page(Vec::<i32>::new(), 0, |r| *r), (vec![], false, None));
+  }
+
+  /// A context whose pool never connects, for code paths that do not query the
+  /// database
+  fn offline_context() -> Context {
+    let manager = db::ConnectionManager::new(db::PgConfig::new(), postgres::NoTls);
+    Context {
+      db: Data::new(Pool::builder().min_idle(Some(0)).build_unchecked(manager)),
+      caller: None,
+      config: Data::new(GqlConfig {
+        report_threshold: 3,
+        time_deck_hour: 0,
+      }),
+      rating_breakdowns: RatingBreakdownLoader::default(),
+      moderations: CardModerationLoader::default(),
+    }
+  }
+
+  #[test]
+  fn after_move_refusals() {
+    let context = offline_context();
+    let refused = |refusal: &str| Game::after_move(&context, 1, Some(refusal.to_owned())).err();
+
+    assert!(matches!(refused("not_found"), Some(GqlError::GameNotFound)));
+    assert!(matches!(refused("not_player"), Some(GqlError::NotInGame)));
+    assert!(matches!(refused("forbidden"), Some(GqlError::Forbidden)));
+    assert!(matches!(
+      refused("status"),
+      Some(GqlError::InvalidGameStatus)
+    ));
+    assert!(matches!(
+      refused("not_enough_players"),
+      Some(GqlError::NotEnoughPlayers)
+    ));
+    assert!(matches!(refused("empty_deck"), Some(GqlError::EmptyDeck)));
+    assert!(matches!(refused("czar"), Some(GqlError::CzarCannotPlay)));
+    assert!(matches!(
+      refused("already_played"),
+      Some(GqlError::AlreadyPlayed)
+    ));
+    assert!(matches!(refused("pick"), Some(GqlError::PickMismatch)));
+    assert!(matches!(
+      refused("not_in_hand"),
+      Some(GqlError::CardNotInHand)
+    ));
+    assert!(matches!(refused("not_czar"), Some(GqlError::NotCzar)));
+    assert!(matches!(
+      refused("play_not_found"),
+      Some(GqlError::PlayNotFound)
+    ));
+    assert!(matches!(
+      refused("unknown"),
+      Some(GqlError::UnexpectedError)
+    ));
+  }
+}
@@ -0,0 +1,54 @@
+use crate::{
+  db::{self, Pool},
+  gql::TimeDeckPeriod,
+  models::RunTimeDeckJob,
+};
+use log::{error, info};
+use std::{thread, time::Duration};
+
+/// Settings of the background jobs, built by `jobs_config_from_env()`
+#[derive(Clone)]
+pub struct JobsConfig {
+  /// Time-decks to generate, none to disable the scheduler
+  pub time_decks: Vec<TimeDeckPeriod>,
+  /// Hour (UTC) at which days, and so weeks, months, and years, start
+  pub time_deck_hour: i32,
+  /// Number of top cards in each time-deck
+  pub time_deck_size: i32,
+  /// Time between two checks for jobs to run
+  pub interval: Duration,
+}
+
+/// Starts the scheduler on its own thread. Every `interval`, the time-deck of
+/// each configured period is generated, unless it already was for the current
+/// period. The database decides which replica runs a job (see
+/// `db::run_time_deck_job()`), so every replica can run the scheduler, and
+/// restarting a replica does not generate a time-deck twice.
+pub fn start(pool: Pool, config: JobsConfig) {
+  if config.time_decks.is_empty() {
+    return;
+  }
+
+  thread::Builder::new()
+    .name("jobs".into())
+    .spawn(move || loop {
+      run_time_deck_jobs(&pool, &config);
+      thread::sleep(config.interval);
+    })
+    .expect("Failed to start the job scheduler");
+}
+
+fn run_time_deck_jobs(pool: &Pool, config: &JobsConfig) {
+  for period in &config.time_decks {
+    let job = RunTimeDeckJob {
+      period: period.name().to_string(),
+      offset_hours: config.time_deck_hour,
+      top_n_cards: config.time_deck_size,
+    };
+    match db::run_time_deck_job(pool, &job) {
+      Ok(true) => info!("Generated the {:?} time-deck", period),
+      Ok(false) => {}
+      Err(e) => error!("Failed to generate the {:?} time-deck: {}", period, e),
+    }
+  }
+}
@@ -1,16 +1,28 @@
+mod auth;
 mod db;
+mod format;
 mod gql;
+mod jobs;
 mod models;
+mod oauth;
 
 use actix_web::{middleware, web, App, HttpResponse, HttpServer};
 use dotenv::dotenv;
-use std::{env, io, path::Path};
+use std::{env, io, path::Path, time::Duration};
+use url::Url;
 
+use auth::Caller;
 use db::{PgConfig, Pool, PoolConfiguration};
+use gql::{CardModerationLoader, GqlConfig, RatingBreakdownLoader, TimeDeckPeriod};
+use jobs::JobsConfig;
+use oauth::OAuthConfig;
 
 pub struct Context {
   db: web::Data<Pool>,
-  authenticated_user_id: i32,
+  caller: Option<Caller>,
+  config: web::Data<GqlConfig>,
+  rating_breakdowns: RatingBreakdownLoader,
+  moderations: CardModerationLoader,
 }
 
 #[actix_rt::main]
@@ -20,16 +32,22 @@ async fn main() -> io::Result<()> {
   let pool_config = PoolConfiguration::default();
   let pg_config = pg_config_from_env().expect("Must provide connection to database");
   let host_binding = env::var("HOST_BIND").expect("Must provide a host and port to bind on");
+  let oauth_config = oauth_config_from_env().expect("Invalid OAuth2 provider configuration");
+  let jobs_config = jobs_config_from_env().expect("Invalid job configuration");
+  let gql_config = gql_config_from_env(&jobs_config).expect("Invalid GraphQL configuration");
 
   let pool = db::create_pool(pg_config, &pool_config);
 
   env_logger::init();
 
+  jobs::start(pool.clone(), jobs_config);
+
   // Start http server
   HttpServer::new(move || {
     App::new()
       .data(pool.clone())
-      .configure(gql::register)
+      .configure(|c| gql::register(c, gql_config.clone()))
+      .configure(|c| oauth::register(c, oauth_config.clone()))
       .wrap(middleware::Logger::default())
       .wrap(middleware::Compress::default())
       .default_service(web::route().to(|| HttpResponse::NotFound()))
@@ -73,3 +91,110 @@ pub fn pg_config_from_env() -> Result<PgConfig, String> {
   }
   Ok(config)
 }
+
+/// Reads the OAuth2 / OpenID Connect provider used for `/login`. Logging in is
+/// only enabled when `OAUTH_CLIENT_ID` is provided. The provider URLs may use
+/// plain `http`, to allow testing against a local mock identity provider.
+pub fn oauth_config_from_env() -> Result<Option<OAuthConfig>, String> {
+  let client_id = match env::var("OAUTH_CLIENT_ID") {
+    Ok(client_id) => client_id,
+    Err(_) => return Ok(None),
+  };
+  let required = |key: &str| {
+    env::var(key).map_err(|_| format!("Missing {}. Required when OAUTH_CLIENT_ID is set.", key))
+  };
+  let required_url = |key: &str| {
+    let value = required(key)?;
+    Url::parse(&value).map_err(|_| format!("Invalid {}: {}", key, value))?;
+    Ok::<_, String>(value)
+  };
+
+  Ok(Some(OAuthConfig {
+    provider: env::var("OAUTH_PROVIDER").unwrap_or_else(|_| "oidc".into()),
+    client_id,
+    client_secret: required("OAUTH_CLIENT_SECRET")?,
+    auth_uri: required_url("OAUTH_AUTH_URL")?,
+    token_uri: required_url("OAUTH_TOKEN_URL")?,
+    redirect_uri: required_url("OAUTH_REDIRECT_URL")?,
+    userinfo_uri: required_url("OAUTH_USERINFO_URL")?,
+    scopes: env::var("OAUTH_SCOPES")
+      .unwrap_or_else(|_| "openid profile".into())
+      .split_whitespace()
+      .map(String::from)
+      .collect(),
+  }))
+}
+
+/// Reads the settings of the GraphQL endpoint. `REPORT_THRESHOLD` is the number
+/// of distinct users reporting a card before it is deactivated, 3 by default.
+/// Time-decks generated on demand start at the same hour as the scheduler's.
+pub fn gql_config_from_env(jobs_config: &JobsConfig) -> Result<GqlConfig, String> {
+  let report_threshold = match env::var("REPORT_THRESHOLD") {
+    Ok(value) => match value.parse::<i32>() {
+      Ok(threshold) if threshold > 0 => threshold,
+      _ => return Err(format!("Invalid REPORT_THRESHOLD: {}", value)),
+    },
+    Err(_) => 3,
+  };
+
+  Ok(GqlConfig {
+    report_threshold,
+    time_deck_hour: jobs_config.time_deck_hour,
+  })
+}
+
+/// Reads the settings of the background jobs. `TIME_DECKS` lists the time-decks
+/// to generate (`daily,weekly,monthly,yearly` by default, empty to disable the
+/// scheduler), each made of the top `TIME_DECK_SIZE` cards (10 by default).
+/// Periods start at `TIME_DECK_HOUR` (UTC, 0 by default), and the scheduler
+/// checks for jobs to run every `JOB_INTERVAL_SECONDS` (60 by default).
+pub fn jobs_config_from_env() -> Result<JobsConfig, String> {
+  let time_decks = match env::var("TIME_DECKS") {
+    Ok(value) => value
+      .split(',')
+      .map(str::trim)
+      .filter(|name| !name.is_empty())
+      .map(|name| match name {
+        "daily" => Ok(TimeDeckPeriod::Daily),
+        "weekly" => Ok(TimeDeckPeriod::Weekly),
+        "monthly" => Ok(TimeDeckPeriod::Monthly),
+        "yearly" => Ok(TimeDeckPeriod::Yearly),
+        _ => Err(format!("Invalid TIME_DECKS: {}", value)),
+      })
+      .collect::<Result<Vec<_>, _>>()?,
+    Err(_) => vec![
+      TimeDeckPeriod::Daily,
+      TimeDeckPeriod::Weekly,
+      TimeDeckPeriod::Monthly,
+      TimeDeckPeriod::Yearly,
+    ],
+  };
+  let time_deck_hour = match env::var("TIME_DECK_HOUR") {
+    Ok(value) => match value.parse::<i32>() {
+      Ok(hour) if (0..24).contains(&hour) => hour,
+      _ => return Err(format!("Invalid TIME_DECK_HOUR: {}", value)),
+    },
+    Err(_) => 0,
+  };
+  let time_deck_size = match env::var("TIME_DECK_SIZE") {
+    Ok(value) => match value.parse::<i32>() {
+      Ok(size) if size > 0 => size,
+      _ => return Err(format!("Invalid TIME_DECK_SIZE: {}", value)),
+    },
+    Err(_) => 10,
+  };
+  let interval = match env::var("JOB_INTERVAL_SECONDS") {
+    Ok(value) => match value.parse::<u64>() {
+      Ok(seconds) if seconds > 0 => Duration::from_secs(seconds),
+      _ => return Err(format!("Invalid JOB_INTERVAL_SECONDS: {}", value)),
+    },
+    Err(_) => Duration::from_secs(60),
+  };
+
+  Ok(JobsConfig {
+    time_decks,
+    time_deck_hour,
+    time_deck_size,
+    interval,
+  })
+}
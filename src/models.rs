@@ -4,15 +4,26 @@ pub struct GetSetResults {
   pub name: String,
 }
 
-/// Struct returned from the `get_cards()` method
+/// Struct returned from the `get_cards()` and `get_cards_by_ids()` methods.
+/// User-submitted cards may not belong to any set. `parent_set_ids` and
+/// `parent_set_names` hold every (active) set the card belongs to.
+/// `sort_key` is only set by `get_cards()`, holding the value the cards were sorted by.
 pub struct GetCardResults {
   pub id: i32,
   pub format_text: String,
   pub is_black: bool,
-  pub parent_set_id: i32,
-  pub parent_set_name: String,
+  pub parent_set_id: Option<i32>,
+  pub parent_set_name: Option<String>,
   pub total_votes: i32,
   pub average_rating: Option<f32>,
+  pub submitted_by_user_id: Option<i32>,
+  pub submitted_by_username: Option<String>,
+  pub parent_set_ids: Vec<i32>,
+  pub parent_set_names: Vec<String>,
+  pub pick: i32,
+  pub draw: i32,
+  pub score: f32,
+  pub sort_key: Option<f64>,
 }
 
 /// Struct used to call the `add_card()` method.
@@ -24,9 +35,11 @@ pub struct AddCard {
 }
 
 /// Struct returned from the `add_card()` method containing
-/// the ID of the newly-inserted card object
+/// the ID of the newly-inserted card object, or of the existing
+/// card when `is_duplicate`
 pub struct AddCardResult {
   pub id: i32,
+  pub is_duplicate: bool,
 }
 
 /// Struct used to call the `add_user_card_rating()` method.
@@ -37,12 +50,20 @@ pub struct AddCardRating {
   pub rating: f32,
 }
 
-/// Struct returned from the `add_user_card_rating` method
-/// containing the new total_votes and average_rating after
-/// submission of the rating.
+/// Struct used to call the `remove_user_rating_from_card()` method.
+/// These fields are all required (hence no default impl)
+pub struct RemoveCardRating {
+  pub user_id: i32,
+  pub card_id: i32,
+}
+
+/// Struct returned from the `add_user_card_rating` and `remove_user_rating_from_card`
+/// methods containing the new total_votes and average_rating after
+/// submission of the rating, as well as the user's previous rating of the card.
 pub struct AddCardRatingResult {
   pub total_votes: i32,
-  pub average_rating: f32,
+  pub average_rating: Option<f32>,
+  pub previous_rating: Option<f32>,
 }
 
 /// Struct used to call the `add_card_combination_rating` method.
@@ -76,6 +97,8 @@ impl GetSets {
 
 /// Struct used to call the `get_cards()` method.
 /// The fields are `Option` to allow NULL database parameters.
+/// `previous_sort_key` is the sort key of the last card when sorting by anything
+/// other than the card ID.
 pub struct GetCards {
   pub search: Option<String>,
   pub filter_black: Option<bool>,
@@ -85,6 +108,10 @@ pub struct GetCards {
   pub get_random: Option<bool>,
   pub random_seed: Option<f32>,
   pub user_submitted: Option<bool>,
+  pub pick: Option<i32>,
+  pub sort_by: Option<String>,
+  pub previous_sort_key: Option<f64>,
+  pub sort_descending: Option<bool>,
 }
 
 impl GetCards {
@@ -100,6 +127,448 @@ impl GetCards {
       get_random: Some(false),
       random_seed: None,
       user_submitted: Some(false),
+      pick: None,
+      sort_by: None,
+      previous_sort_key: None,
+      sort_descending: Some(false),
     }
   }
 }
+
+/// Struct used to call the `login_user_identity()` method.
+/// `username` is the provider's suggested username, and `link_user_id`
+/// is set when an already-authenticated user logs in with a new provider.
+pub struct LoginUserIdentity {
+  pub provider: String,
+  pub subject: String,
+  pub username: Option<String>,
+  pub fallback_username: String,
+  pub link_user_id: Option<i32>,
+}
+
+/// Struct used to call the `create_user_token()` method.
+/// These fields are all required (hence no default impl)
+pub struct CreateUserToken {
+  pub token: String,
+  pub user_id: i32,
+  pub lifetime_days: i32,
+}
+
+/// Struct returned from the `get_user_by_id()` method
+pub struct GetUserResults {
+  pub id: i32,
+  pub username: String,
+}
+
+/// Struct used to call the `register_user()` method.
+/// These fields are all required (hence no default impl)
+pub struct RegisterUser {
+  pub username: String,
+}
+
+/// Struct used to call the `update_username()` method.
+/// These fields are all required (hence no default impl)
+pub struct UpdateUsername {
+  pub user_id: i32,
+  pub username: String,
+}
+
+/// Struct used to call the `get_user_card_ratings()` method.
+/// These fields are all required (hence no default impl)
+pub struct GetUserHistory {
+  pub user_id: i32,
+  pub cursor: Option<i32>,
+  pub n_results: i32,
+}
+
+/// Struct used to call the `get_visible_cards_by_ids()` method.
+/// Pending and rejected cards are only included when they were submitted by
+/// `user_id`, or when `include_unapproved` (which is reserved for admins).
+pub struct GetVisibleCards {
+  pub card_ids: Vec<i32>,
+  pub user_id: Option<i32>,
+  pub include_unapproved: bool,
+}
+
+/// Struct used to call the `get_user_submitted_cards()` method.
+/// Pending and rejected cards are only included when `include_unapproved`,
+/// which is reserved for the submitter (and admins).
+pub struct GetUserSubmittedCards {
+  pub user_id: i32,
+  pub cursor: Option<i32>,
+  pub n_results: i32,
+  pub include_unapproved: bool,
+}
+
+/// Struct used to call the `get_user_decks()` method.
+/// Private and unlisted decks are only included when `include_unlisted`,
+/// which is reserved for the owner (and admins).
+pub struct GetUserDecks {
+  pub user_id: i32,
+  pub cursor: Option<i32>,
+  pub n_results: i32,
+  pub include_unlisted: bool,
+}
+
+/// Struct returned from the `get_user_card_ratings()` method
+pub struct GetUserCardRatingResults {
+  pub card_id: i32,
+  pub rating: f32,
+}
+
+/// Struct used to call the `get_user_card_combination_ratings()` method.
+/// The cursor is the `(black_card_id, white_card_id, ordinal)` of the last result.
+pub struct GetUserCardCombinationRatings {
+  pub user_id: i32,
+  pub cursor: Option<(i32, i32, i32)>,
+  pub n_results: i32,
+}
+
+/// Struct returned from the `get_user_card_combination_ratings()` method
+pub struct GetUserCardCombinationRatingResults {
+  pub black_card_id: i32,
+  pub white_card_id: i32,
+  pub ordinal: i32,
+  pub rating: f32,
+}
+
+/// Struct used to call the `get_card_pairings()` method.
+/// Exactly one of the card IDs is set, to the card the pairings are for.
+pub struct GetCardPairings {
+  pub black_card_id: Option<i32>,
+  pub white_card_id: Option<i32>,
+  pub ordinal: Option<i32>,
+  pub n_results: i32,
+}
+
+/// Struct returned from the `get_card_pairings()` method
+pub struct GetCardPairingResults {
+  pub black_card_id: i32,
+  pub white_card_id: i32,
+  pub ordinal: i32,
+  pub total_votes: i32,
+  pub average_rating: f32,
+}
+
+/// Struct returned from the `get_card_counts()` method
+pub struct GetCardCountResults {
+  pub is_black: bool,
+  pub user_submitted: bool,
+  pub n_cards: i32,
+}
+
+/// Struct used to call the `get_rating_volume()` method.
+/// `interval` is one of `day`, `week`, or `month`.
+pub struct GetRatingVolume {
+  pub interval: String,
+  pub n_buckets: i32,
+}
+
+/// Struct returned from the `get_rating_volume()` method
+pub struct GetRatingVolumeResults {
+  pub bucket_start: String,
+  pub n_ratings: i32,
+  pub n_raters: i32,
+}
+
+/// Struct returned from the `get_top_raters()` method
+pub struct GetTopRaterResults {
+  pub user_id: i32,
+  pub username: String,
+  pub n_ratings: i32,
+  pub last_rated_date: String,
+}
+
+/// Struct returned from the `get_rating_distribution()` method
+pub struct GetRatingDistributionResults {
+  pub bucket: i32,
+  pub n_cards: i32,
+}
+
+/// Struct returned from the `get_card_rating_breakdowns()` method.
+/// `histogram` counts the ratings in 10 buckets of 0.1.
+pub struct GetCardRatingBreakdownResults {
+  pub card_id: i32,
+  pub upvotes: i32,
+  pub downvotes: i32,
+  pub low_votes: Option<f32>,
+  pub high_votes: Option<f32>,
+  pub histogram: Vec<i32>,
+}
+
+/// Struct returned from the `get_user_by_token()` method
+pub struct GetUserByTokenResults {
+  pub user_id: i32,
+  pub is_admin: bool,
+}
+
+/// Struct returned from the `get_api_key()` method.
+/// `is_admin` is whether the owner of the key is (still) an admin.
+pub struct GetApiKeyResults {
+  pub id: i32,
+  pub user_id: i32,
+  pub scopes: Vec<String>,
+  pub is_admin: bool,
+}
+
+/// Struct returned from the `get_user_api_keys()` method
+pub struct GetUserApiKeyResults {
+  pub id: i32,
+  pub name: String,
+  pub scopes: Vec<String>,
+}
+
+/// Struct used to call the `create_api_key()` method.
+/// These fields are all required (hence no default impl)
+pub struct CreateApiKey {
+  pub key: String,
+  pub user_id: i32,
+  pub name: String,
+  pub scopes: Vec<String>,
+}
+
+/// Struct used to call the `revoke_api_key()` method.
+/// `user_id` restricts the revocation to keys owned by the user,
+/// and is `None` only for admins.
+pub struct RevokeApiKey {
+  pub id: i32,
+  pub user_id: Option<i32>,
+}
+
+/// Struct used to call the `get_moderation_queue()` method.
+/// These fields are all required (hence no default impl)
+pub struct GetModerationQueue {
+  pub cursor: Option<i32>,
+  pub n_results: i32,
+}
+
+/// Struct used to call the `moderate_card()` method.
+/// `rejection_reason` is only set when rejecting a card.
+pub struct ModerateCard {
+  pub card_id: i32,
+  pub moderation_status: String,
+  pub moderated_by_user_id: i32,
+  pub rejection_reason: Option<String>,
+}
+
+/// Struct returned from the `get_card_moderations()` method.
+/// The moderator fields are `None` until the card has been moderated.
+pub struct GetCardModerationResults {
+  pub card_id: i32,
+  pub moderation_status: String,
+  pub moderated_by_user_id: Option<i32>,
+  pub moderated_by_username: Option<String>,
+  pub moderated_date: Option<String>,
+  pub rejection_reason: Option<String>,
+}
+
+/// Struct used to call the `edit_card()` method.
+/// `resubmit` sends the card back to the moderation queue.
+pub struct EditCard {
+  pub card_id: i32,
+  pub format_text: String,
+  pub edited_by_user_id: i32,
+  pub resubmit: bool,
+}
+
+/// Struct returned from the `get_card_revisions()` method. The votes are the
+/// active ratings given while the revision was the card's wording.
+pub struct GetCardRevisionResults {
+  pub revision: i32,
+  pub format_text: String,
+  pub edited_by_user_id: Option<i32>,
+  pub edited_by_username: Option<String>,
+  pub created_date: String,
+  pub total_votes: i32,
+  pub average_rating: Option<f32>,
+}
+
+/// Struct used to call the `report_card()` method.
+/// These fields are all required (hence no default impl)
+pub struct ReportCard {
+  pub card_id: i32,
+  pub user_id: i32,
+  pub reason: String,
+  pub report_threshold: i32,
+}
+
+/// Struct used to call the `get_card_reports()` method.
+/// These fields are all required (hence no default impl)
+pub struct GetCardReports {
+  pub cursor: Option<i32>,
+  pub n_results: i32,
+}
+
+/// Struct returned from the `get_card_reports()` method. The `reported_by_*`,
+/// `reasons`, and `reported_dates` arrays hold one entry per report of the card.
+pub struct GetCardReportResults {
+  pub card_id: i32,
+  pub format_text: String,
+  pub is_black: bool,
+  pub is_active: bool,
+  pub reported_by_user_ids: Vec<i32>,
+  pub reported_by_usernames: Vec<String>,
+  pub reasons: Vec<String>,
+  pub reported_dates: Vec<String>,
+}
+
+/// Struct used to call the `create_set()` method.
+/// These fields are all required (hence no default impl)
+pub struct CreateSet {
+  pub name: String,
+}
+
+/// Struct used to call the `rename_set()` method.
+/// These fields are all required (hence no default impl)
+pub struct RenameSet {
+  pub set_id: i32,
+  pub name: String,
+}
+
+/// Struct used to call the `add_cards_to_set()` and `remove_cards_from_set()` methods.
+/// These fields are all required (hence no default impl)
+pub struct UpdateSetCards {
+  pub set_id: i32,
+  pub card_ids: Vec<i32>,
+}
+
+/// Struct returned from the `get_decks_by_ids()` and `get_user_decks()` methods
+pub struct GetDeckResults {
+  pub id: i32,
+  pub name: String,
+  pub visibility: String,
+  pub owner_user_id: i32,
+  pub owner_username: String,
+}
+
+/// Struct used to call the `create_deck()` method.
+/// These fields are all required (hence no default impl)
+pub struct CreateDeck {
+  pub user_id: i32,
+  pub name: String,
+  pub visibility: String,
+}
+
+/// Struct used to call the `update_deck()` method.
+/// Fields that are `None` are left unchanged.
+pub struct UpdateDeck {
+  pub deck_id: i32,
+  pub name: Option<String>,
+  pub visibility: Option<String>,
+}
+
+/// Struct used to call the `run_time_deck_job()` method.
+/// `period` is one of `day`, `week`, `month`, or `year`.
+pub struct RunTimeDeckJob {
+  pub period: String,
+  pub offset_hours: i32,
+  pub top_n_cards: i32,
+}
+
+/// Struct used to call the `get_time_decks()` method.
+/// The cursor is the `(start_day, id)` of the last result, where `start_day` is
+/// the number of days since 1970-01-01.
+pub struct GetTimeDecks {
+  pub period: Option<String>,
+  pub cursor: Option<(i32, i32)>,
+  pub n_results: i32,
+}
+
+/// Struct used to call the `get_time_deck()` method.
+/// `date` is any day of the period, formatted as `YYYY-MM-DD`.
+pub struct GetTimeDeck {
+  pub period: String,
+  pub date: String,
+}
+
+/// Struct returned from the `get_time_decks()` and `get_time_deck()` methods
+pub struct GetTimeDeckResults {
+  pub id: i32,
+  pub name: String,
+  pub period: String,
+  pub start_date: String,
+  pub end_date: String,
+  pub start_day: i32,
+}
+
+/// Struct used to call the `time_deck_period_ended()` method.
+/// `date` is any day of the period, formatted as `YYYY-MM-DD`.
+pub struct TimeDeckPeriodEnded {
+  pub period: String,
+  pub date: String,
+  pub offset_hours: i32,
+}
+
+/// Struct used to call the `generate_time_deck()` method.
+/// These fields are all required (hence no default impl)
+pub struct GenerateTimeDeck {
+  pub period: String,
+  pub date: String,
+  pub offset_hours: i32,
+  pub top_n_cards: i32,
+  pub replace: bool,
+}
+
+/// Struct used to call the `create_game()` method.
+/// These fields are all required (hence no default impl)
+pub struct CreateGame {
+  pub user_id: i32,
+  pub card_sets: Vec<i32>,
+  pub random_seed: f32,
+  pub hand_size: i32,
+}
+
+/// Struct used to call the `join_game()`, `deal_game_hands()`, and
+/// `next_game_round()` methods, on behalf of the user
+pub struct GameMove {
+  pub game_id: i32,
+  pub user_id: i32,
+}
+
+/// Struct used to call the `play_game_cards()` method.
+/// The cards are in the order of the blanks of the black card.
+pub struct PlayGameCards {
+  pub game_id: i32,
+  pub user_id: i32,
+  pub card_ids: Vec<i32>,
+}
+
+/// Struct used to call the `pick_game_winner()` method.
+/// `play` is the position of the winning play in `get_game_plays()`.
+pub struct PickGameWinner {
+  pub game_id: i32,
+  pub user_id: i32,
+  pub play: i32,
+}
+
+/// Struct returned from the `get_game()` method.
+/// The round fields are `None` before the first round.
+pub struct GetGameResults {
+  pub id: i32,
+  pub owner_user_id: i32,
+  pub owner_username: String,
+  pub card_sets: Vec<i32>,
+  pub random_seed: f32,
+  pub hand_size: i32,
+  pub status: String,
+  pub round: i32,
+  pub czar_player_id: Option<i32>,
+  pub black_card_id: Option<i32>,
+  pub winner_player_id: Option<i32>,
+}
+
+/// Struct returned from the `get_game_players()` method
+pub struct GetGamePlayerResults {
+  pub id: i32,
+  pub user_id: i32,
+  pub username: String,
+  pub score: i32,
+  pub n_cards: i32,
+  pub has_played: bool,
+}
+
+/// Struct returned from the `get_game_plays()` method
+pub struct GetGamePlayResults {
+  pub player_id: i32,
+  pub card_ids: Vec<i32>,
+}
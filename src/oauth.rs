@@ -0,0 +1,455 @@
+use crate::{
+  auth::{self, Credentials, SESSION_COOKIE, TOKEN_LIFETIME_DAYS},
+  db::{self, Pool},
+  gql::validate_username,
+  models::{CreateUserToken, LoginUserIdentity},
+};
+use actix_web::{
+  client::Client,
+  cookie::SameSite,
+  error::{
+    BlockingError, ErrorBadGateway, ErrorBadRequest, ErrorForbidden, ErrorInternalServerError,
+    ErrorUnauthorized,
+  },
+  http::{
+    header::{ACCEPT, LOCATION},
+    Cookie, StatusCode,
+  },
+  web::{self, Data, ServiceConfig},
+  Error as AWError, HttpMessage, HttpRequest, HttpResponse,
+};
+use serde_json::{json, Value};
+use std::{collections::HashMap, fmt};
+use url::{form_urlencoded, Url};
+
+/// Cookie holding the `state` parameter between `/login` and the callback,
+/// protecting the callback from cross-site request forgery
+const STATE_COOKIE: &str = "oauth_state";
+
+/// Configuration of the OAuth2 / OpenID Connect provider users log in with.
+/// Built by `oauth_config_from_env()`.
+#[derive(Clone)]
+pub struct OAuthConfig {
+  /// Name of the provider, used to key the `bb.user_identity` table
+  pub provider: String,
+  pub client_id: String,
+  pub client_secret: String,
+  /// Authorization endpoint `/login` redirects users to
+  pub auth_uri: String,
+  /// Token endpoint the authorization code is exchanged at
+  pub token_uri: String,
+  /// `/login/callback`, as registered with the provider
+  pub redirect_uri: String,
+  /// OpenID Connect userinfo endpoint, used to resolve the provider's subject
+  pub userinfo_uri: String,
+  pub scopes: Vec<String>,
+}
+
+#[derive(Debug)]
+enum LoginError {
+  InactiveUser,
+  Provider(String),
+  UnexpectedError,
+}
+
+impl fmt::Display for LoginError {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    match self {
+      LoginError::InactiveUser => write!(f, "User is no longer active"),
+      LoginError::Provider(e) => write!(f, "Identity provider error: {}", e),
+      LoginError::UnexpectedError => write!(f, "Unable to log in"),
+    }
+  }
+}
+
+/// Identity of the user at the provider
+struct ProviderIdentity {
+  subject: String,
+  username: Option<String>,
+}
+
+/// Failures reaching the provider, reported by the callback as `502 Bad Gateway`
+fn provider_error(e: impl fmt::Display) -> LoginError {
+  LoginError::Provider(e.to_string())
+}
+
+/// Parses the JSON response of one of the provider's endpoints
+fn provider_json(uri: &str, status: StatusCode, body: &[u8]) -> Result<Value, LoginError> {
+  if !status.is_success() {
+    return Err(LoginError::Provider(format!(
+      "{} responded with {}",
+      uri, status
+    )));
+  }
+
+  serde_json::from_slice(body).map_err(provider_error)
+}
+
+/// Exchanges the authorization code for an access token, and uses the token to
+/// resolve the user from the provider's userinfo endpoint. The provider's
+/// username is only kept when it is a valid username, so that email addresses
+/// (or anything `updateUsername` would reject) are never made public.
+async fn provider_identity(
+  oauth: &OAuthConfig,
+  code: &str,
+) -> Result<ProviderIdentity, LoginError> {
+  let client = Client::default();
+
+  let mut response = client
+    .post(&oauth.token_uri)
+    .header(ACCEPT, "application/json")
+    .send_form(&[
+      ("grant_type", "authorization_code"),
+      ("code", code),
+      ("redirect_uri", &oauth.redirect_uri),
+      ("client_id", &oauth.client_id),
+      ("client_secret", &oauth.client_secret),
+    ])
+    .await
+    .map_err(provider_error)?;
+  let body = response.body().await.map_err(provider_error)?;
+  let token = provider_json(&oauth.token_uri, response.status(), &body)?;
+  let access_token = match token["access_token"].as_str() {
+    Some(t) => t.to_owned(),
+    None => {
+      return Err(LoginError::Provider(
+        "token is missing `access_token`".into(),
+      ))
+    }
+  };
+
+  let mut response = client
+    .get(&oauth.userinfo_uri)
+    .bearer_auth(access_token)
+    .header(ACCEPT, "application/json")
+    .send()
+    .await
+    .map_err(provider_error)?;
+  let body = response.body().await.map_err(provider_error)?;
+  let userinfo = provider_json(&oauth.userinfo_uri, response.status(), &body)?;
+
+  let subject = match &userinfo["sub"] {
+    Value::String(s) => s.to_owned(),
+    Value::Number(n) => n.to_string(),
+    _ => return Err(LoginError::Provider("userinfo is missing `sub`".into())),
+  };
+  let username = ["preferred_username", "nickname", "name"]
+    .iter()
+    .filter_map(|k| userinfo[*k].as_str())
+    .find_map(|u| validate_username(u).ok());
+
+  Ok(ProviderIdentity { subject, username })
+}
+
+/// Resolves the `bb.user` for the provider's identity and issues a token for it.
+/// When the callback is made with a valid session token, the identity is linked
+/// to that user rather than creating a new one. API keys cannot link identities.
+fn login_user(
+  pool: &Pool,
+  provider: String,
+  identity: ProviderIdentity,
+  link_credentials: Option<Credentials>,
+  token: String,
+) -> Result<i32, LoginError> {
+  let link_user_id = match link_credentials {
+    Some(Credentials::Token(t)) => auth::authenticate(pool, Some(Credentials::Token(t)))
+      .unwrap_or(None)
+      .map(|c| c.user_id),
+    _ => None,
+  };
+
+  let user_id = db::login_user_identity(
+    pool,
+    &LoginUserIdentity {
+      provider,
+      subject: identity.subject,
+      username: identity.username,
+      fallback_username: format!("user-{}", &auth::generate_token()[..8]),
+      link_user_id,
+    },
+  )
+  .map_err(|_| LoginError::UnexpectedError)?
+  .ok_or(LoginError::InactiveUser)?;
+
+  db::create_user_token(
+    pool,
+    &CreateUserToken {
+      token,
+      user_id,
+      lifetime_days: TOKEN_LIFETIME_DAYS,
+    },
+  )
+  .map_err(|_| LoginError::UnexpectedError)?;
+
+  Ok(user_id)
+}
+
+/// Starts the authorization-code flow by redirecting the user to the provider
+async fn login(oauth: Data<OAuthConfig>) -> Result<HttpResponse, AWError> {
+  let state = auth::generate_token();
+  let mut url = Url::parse(&oauth.auth_uri).map_err(|e| ErrorInternalServerError(e))?;
+  url
+    .query_pairs_mut()
+    .append_pair("response_type", "code")
+    .append_pair("client_id", &oauth.client_id)
+    .append_pair("redirect_uri", &oauth.redirect_uri)
+    .append_pair("scope", &oauth.scopes.join(" "))
+    .append_pair("state", &state);
+
+  Ok(
+    HttpResponse::Found()
+      .header(LOCATION, url.as_str())
+      .cookie(
+        Cookie::build(STATE_COOKIE, state)
+          .path("/login")
+          .http_only(true)
+          .max_age(600)
+          .finish(),
+      )
+      .finish(),
+  )
+}
+
+/// Completes the authorization-code flow. The token issued for this API is
+/// returned in the body for API clients, and set as the session cookie for
+/// browser clients.
+async fn callback(
+  req: HttpRequest,
+  oauth: Data<OAuthConfig>,
+  db_pool: Data<Pool>,
+) -> Result<HttpResponse, AWError> {
+  let params = form_urlencoded::parse(req.query_string().as_bytes())
+    .into_owned()
+    .collect::<HashMap<String, String>>();
+
+  if let Some(error) = params.get("error") {
+    return Err(ErrorUnauthorized(format!("Login was denied: {}", error)));
+  }
+
+  match (params.get("state"), req.cookie(STATE_COOKIE)) {
+    (Some(state), Some(expected)) if state == expected.value() => {}
+    _ => return Err(ErrorBadRequest("Invalid login state")),
+  }
+
+  let code = match params.get("code") {
+    Some(code) => code.to_owned(),
+    None => return Err(ErrorBadRequest("Missing authorization code")),
+  };
+
+  let identity = provider_identity(&oauth, &code)
+    .await
+    .map_err(ErrorBadGateway)?;
+
+  let link_credentials = auth::credentials_from_request(&req);
+  let token = auth::generate_token();
+  let issued_token = token.clone();
+  let provider = oauth.provider.clone();
+
+  let user_id =
+    web::block(move || login_user(&db_pool, provider, identity, link_credentials, issued_token))
+      .await
+      .map_err(|e| match e {
+        BlockingError::Error(LoginError::InactiveUser) => ErrorForbidden(LoginError::InactiveUser),
+        e => AWError::from(e),
+      })?;
+
+  let lifetime_seconds = i64::from(TOKEN_LIFETIME_DAYS) * 24 * 60 * 60;
+
+  Ok(
+    HttpResponse::Ok()
+      .del_cookie(&Cookie::build(STATE_COOKIE, "").path("/login").finish())
+      .cookie(
+        Cookie::build(SESSION_COOKIE, token.clone())
+          .path("/")
+          .http_only(true)
+          .secure(true)
+          .same_site(SameSite::Lax)
+          .max_age(lifetime_seconds)
+          .finish(),
+      )
+      .json(json!({
+        "token": token,
+        "tokenType": "Bearer",
+        "expiresIn": lifetime_seconds,
+        "userId": user_id.to_string(),
+      })),
+  )
+}
+
+/// Login endpoints are only registered when a provider has been configured
+pub fn register(config: &mut ServiceConfig, oauth: Option<OAuthConfig>) {
+  if let Some(oauth) = oauth {
+    config
+      .data(oauth)
+      .route("/login", web::get().to(login))
+      .route("/login/callback", web::get().to(callback));
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::db::{create_pool, PoolConfiguration};
+  use std::{
+    io::{BufRead, BufReader, Read, Write},
+    net::TcpListener,
+    thread,
+  };
+
+  /// Starts a mock identity provider on a random port, and returns the
+  /// configuration of a client registered with it. Codes are exchanged for
+  /// `at-{code}` access tokens, resolved to the `subject-{code}` subject;
+  /// codes starting with `denied` are refused, and codes starting with `email`
+  /// resolve to a numeric subject with a name and an email address only.
+  fn mock_provider() -> OAuthConfig {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let base = format!("http://{}", listener.local_addr().unwrap());
+
+    thread::spawn(move || {
+      for stream in listener.incoming() {
+        let mut stream = stream.unwrap();
+        let mut reader = BufReader::new(stream.try_clone().unwrap());
+
+        let mut request_line = String::new();
+        reader.read_line(&mut request_line).unwrap();
+        let mut headers = HashMap::new();
+        loop {
+          let mut line = String::new();
+          reader.read_line(&mut line).unwrap();
+          match line
+            .trim_end()
+            .splitn(2, ": ")
+            .collect::<Vec<_>>()
+            .as_slice()
+          {
+            [name, value] => headers.insert(name.to_lowercase(), value.to_string()),
+            _ => break,
+          };
+        }
+        let length = headers
+          .get("content-length")
+          .map_or(0, |l| l.parse::<usize>().unwrap());
+        let mut body = vec![0; length];
+        reader.read_exact(&mut body).unwrap();
+
+        let form = form_urlencoded::parse(&body)
+          .into_owned()
+          .collect::<HashMap<String, String>>();
+        let param = |k: &str| form.get(k).map_or("", String::as_str);
+        let bearer = headers
+          .get("authorization")
+          .and_then(|a| a.strip_prefix("Bearer at-"))
+          .unwrap_or("");
+
+        let (status, response) = match request_line.split(' ').nth(1) {
+          Some("/token")
+            if param("grant_type") == "authorization_code"
+              && param("client_id") == "bba"
+              && param("client_secret") == "secret"
+              && param("redirect_uri") == "http://127.0.0.1:8080/login/callback"
+              && !param("code").starts_with("denied") =>
+          {
+            (
+              "200 OK",
+              json!({ "access_token": format!("at-{}", param("code")), "token_type": "Bearer" }),
+            )
+          }
+          Some("/token") => ("400 Bad Request", json!({ "error": "invalid_grant" })),
+          Some("/userinfo") if bearer.starts_with("email") => (
+            "200 OK",
+            json!({ "sub": 42, "name": "Jane Doe", "email": "jane@example.com" }),
+          ),
+          Some("/userinfo") if !bearer.is_empty() => (
+            "200 OK",
+            json!({ "sub": format!("subject-{}", bearer), "preferred_username": bearer }),
+          ),
+          _ => ("401 Unauthorized", json!({ "error": "invalid_token" })),
+        };
+
+        let response = response.to_string();
+        write!(
+          stream,
+          "HTTP/1.1 {}\r\ncontent-type: application/json\r\ncontent-length: {}\r\nconnection: close\r\n\r\n{}",
+          status,
+          response.len(),
+          response
+        )
+        .unwrap();
+      }
+    });
+
+    OAuthConfig {
+      provider: "mock".into(),
+      client_id: "bba".into(),
+      client_secret: "secret".into(),
+      auth_uri: format!("{}/authorize", base),
+      token_uri: format!("{}/token", base),
+      redirect_uri: "http://127.0.0.1:8080/login/callback".into(),
+      userinfo_uri: format!("{}/userinfo", base),
+      scopes: vec!["openid".into(), "profile".into()],
+    }
+  }
+
+  #[actix_rt::test]
+  async fn provider_identity_exchanges_code() {
+    let oauth = mock_provider();
+
+    let identity = provider_identity(&oauth, "player.one").await.unwrap();
+    assert_eq!(identity.subject, "subject-player.one");
+    assert_eq!(identity.username.as_deref(), Some("player.one"));
+  }
+
+  #[actix_rt::test]
+  async fn provider_identity_skips_invalid_usernames() {
+    let oauth = mock_provider();
+
+    let identity = provider_identity(&oauth, "email").await.unwrap();
+    assert_eq!(identity.subject, "42");
+    assert_eq!(identity.username, None);
+  }
+
+  #[actix_rt::test]
+  async fn provider_identity_refused_code() {
+    let oauth = mock_provider();
+
+    assert!(matches!(
+      provider_identity(&oauth, "denied").await,
+      Err(LoginError::Provider(_))
+    ));
+  }
+
+  /// Needs the database configured in `.env`: `cargo test -- --ignored`
+  #[actix_rt::test]
+  #[ignore]
+  async fn login_user_with_mock_provider() {
+    dotenv::dotenv().ok();
+    let pool = create_pool(
+      crate::pg_config_from_env().unwrap(),
+      &PoolConfiguration::default(),
+    );
+    let oauth = mock_provider();
+    let code = format!("player-{}", &auth::generate_token()[..8]);
+
+    let identity = provider_identity(&oauth, &code).await.unwrap();
+    assert_eq!(identity.username.as_deref(), Some(code.as_str()));
+    let token = auth::generate_token();
+    let user_id = login_user(&pool, oauth.provider.clone(), identity, None, token.clone()).unwrap();
+
+    let caller = auth::authenticate(&pool, Some(Credentials::Token(token)))
+      .unwrap()
+      .unwrap();
+    assert_eq!(caller.user_id, user_id);
+
+    // Logging in again with the same identity resolves the same user
+    let identity = provider_identity(&oauth, &code).await.unwrap();
+    let relogin_user_id = login_user(
+      &pool,
+      oauth.provider.clone(),
+      identity,
+      None,
+      auth::generate_token(),
+    )
+    .unwrap();
+    assert_eq!(relogin_user_id, user_id);
+  }
+}